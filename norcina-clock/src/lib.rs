@@ -0,0 +1,347 @@
+//! A [Clock](https://www.worldcubeassociation.org/results/rankings/clock/)
+//!
+//! # Conventions
+//!
+//! Each face has 9 dials in a 3x3 grid, indexed in reading order as seen from
+//! that face. The 4 corner dials are the same physical pieces on both faces,
+//! so turning one clockwise on the front turns it counterclockwise as seen
+//! from the back. That leaves 14 independent dials: the 9 of the front and the
+//! 5 non-corner ones of the back.
+//!
+//! Dials are stored as hours between 0 and 11, where 0 is 12 o'clock (i.e.,
+//! solved).
+
+use owo_colors::{OwoColorize as _, Rgb};
+use std::fmt::{self, Write as _};
+
+pub mod mov;
+pub use mov::{Move, Pin, Pins};
+
+pub mod solve;
+
+pub type Alg = norcina_core::Alg<Move>;
+
+/// The indices of the corners in a 3x3 grid.
+const CORNERS: [u8; 4] = [0, 2, 6, 8];
+
+/// The indices of the non-corner dials in a 3x3 grid, in the same order as
+/// they are stored for the back.
+const BACK_DIALS: [u8; 5] = [1, 3, 4, 5, 7];
+
+/// A [Clock](https://www.worldcubeassociation.org/results/rankings/clock/)
+#[repr(transparent)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Clock {
+    /// Packed field: 14 nibbles, each one the hour of a dial (between 0 and
+    /// 11). The first 9 are the dials of the front, the last 5 are the
+    /// non-corner dials of the back (see [`BACK_DIALS`]).
+    ///
+    /// The 8 most significant bits are always 0.
+    data: u64,
+}
+
+impl Clock {
+    pub const SOLVED: Self = Self { data: 0 };
+
+    /// # Panics
+    ///
+    /// If any of the dials is 12 or more.
+    pub const fn from_dials(dials: [u8; 14]) -> Self {
+        let mut data = 0;
+        let mut i = 0;
+        while i < 14 {
+            assert!(dials[i] < 12);
+            data |= (dials[i] as u64) << (4 * i);
+            i += 1;
+        }
+
+        Self { data }
+    }
+
+    /// The 14 independent dials, in the order they are stored (the front in
+    /// reading order, then the back without the corners).
+    pub const fn dials(self) -> [u8; 14] {
+        let mut dials = [0; 14];
+        let mut i = 0;
+        while i < 14 {
+            dials[i] = self.dial(i as u8);
+            i += 1;
+        }
+
+        dials
+    }
+
+    #[inline]
+    pub const fn dial(self, index: u8) -> u8 {
+        debug_assert!(index < 14);
+        ((self.data >> (4 * index)) & 0b1111) as u8
+    }
+
+    /// The dials of the front, in reading order.
+    pub const fn front(self) -> [u8; 9] {
+        let mut front = [0; 9];
+        let mut i = 0;
+        while i < 9 {
+            front[i] = self.dial(i as u8);
+            i += 1;
+        }
+
+        front
+    }
+
+    /// The dials of the back, in reading order as seen from the back.
+    pub const fn back(self) -> [u8; 9] {
+        let front = self.front();
+        let mut back = [0; 9];
+
+        let mut i = 0;
+        while i < 4 {
+            // Mirror horizontally, and the opposite direction.
+            let corner = CORNERS[i] as usize;
+            back[corner] = (12 - front[mirror(corner)]) % 12;
+            i += 1;
+        }
+
+        let mut i = 0;
+        while i < 5 {
+            back[BACK_DIALS[i] as usize] = self.dial(9 + i as u8);
+            i += 1;
+        }
+
+        back
+    }
+
+    /// Turns the dials of the front that are connected when `pins` are up.
+    pub const fn turn(self, pins: Pins, amount: i8) -> Self {
+        let mask = pins.dial_mask();
+        let mut dials = self.dials();
+        let mut i = 0;
+        while i < 9 {
+            if mask & (1 << i) != 0 {
+                dials[i] = (dials[i] as i8 + amount).rem_euclid(12) as u8;
+            }
+            i += 1;
+        }
+
+        Self::from_dials(dials)
+    }
+
+    /// Turns the puzzle around, so the back becomes the front.
+    pub const fn flip(self) -> Self {
+        let front = self.front();
+        let mut dials = [0; 14];
+
+        // The new front is the old back.
+        let back = self.back();
+        let mut i = 0;
+        while i < 9 {
+            dials[i] = back[i];
+            i += 1;
+        }
+
+        // And the new back is the old front.
+        let mut i = 0;
+        while i < 5 {
+            dials[9 + i] = front[BACK_DIALS[i] as usize];
+            i += 1;
+        }
+
+        Self::from_dials(dials)
+    }
+
+    pub const fn mov_single(self, mov: Move) -> Self {
+        match mov {
+            Move::Turn { pins, amount } => self.turn(pins, amount),
+            Move::Flip => self.flip(),
+            Move::PinUp(_) => self,
+        }
+    }
+
+    pub fn mov(mut self, alg: impl IntoIterator<Item = Move>) -> Self {
+        for mov in alg {
+            self = self.mov_single(mov);
+        }
+        self
+    }
+
+    pub const fn is_solved(self) -> bool {
+        self.data == Self::SOLVED.data
+    }
+
+    /// Generates a uniformly random state.
+    pub fn random_with_rng(rng: &mut impl rand::Rng) -> Self {
+        Self::from_dials(std::array::from_fn(|_| rng.random_range(0..12)))
+    }
+
+    pub fn random() -> Self {
+        Self::random_with_rng(&mut rand::rng())
+    }
+
+    /// Writes the front and the back next to each other, each dial as the hour
+    /// it points at.
+    ///
+    /// If `face_colors` is `Some`, the front and back are written with the
+    /// given colors, respectively.
+    pub fn write(self, f: &mut fmt::Formatter<'_>, face_colors: Option<[Rgb; 2]>) -> fmt::Result {
+        let faces = [self.front(), self.back()];
+        for row in 0..3 {
+            for (face, dials) in faces.iter().enumerate() {
+                if face > 0 {
+                    f.write_str("   ")?;
+                }
+
+                for dial in &dials[3 * row..3 * row + 3] {
+                    let hour = if *dial == 0 { 12 } else { *dial };
+                    match face_colors {
+                        Some(colors) => write!(f, "{:>3}", hour.color(colors[face]))?,
+                        None => write!(f, "{hour:>3}")?,
+                    }
+                }
+            }
+
+            f.write_char('\n')?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The index of the same position of a 3x3 grid when seen from the other side.
+const fn mirror(index: usize) -> usize {
+    index - index % 3 + (2 - index % 3)
+}
+
+pub const DEFAULT_FACE_COLORS: [Rgb; 2] = [Rgb(109, 178, 242), Rgb(242, 242, 242)];
+
+impl fmt::Debug for Clock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Clock")
+            .field("front", &self.front())
+            .field("back", &self.back())
+            .finish()
+    }
+}
+
+impl fmt::Display for Clock {
+    /// Uses [`DEFAULT_FACE_COLORS`], or no colors with the alternate flag
+    /// (`{:#}`).
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let face_colors = (!f.alternate()).then_some(DEFAULT_FACE_COLORS);
+        self.write(f, face_colors)
+    }
+}
+
+impl norcina_core::Puzzle for Clock {
+    type Move = Move;
+
+    const SOLVED: Self = Self::SOLVED;
+
+    fn apply(self, mov: Move) -> Self {
+        self.mov_single(mov)
+    }
+
+    fn random_state(rng: &mut impl rand::Rng) -> Self {
+        Self::random_with_rng(rng)
+    }
+
+    /// Every turn with at least one pin up, and the flip. Pins are left out,
+    /// since they don't change the state.
+    fn neighbors(self) -> impl Iterator<Item = (Move, Self)> {
+        let turns = (1..16).flat_map(|pins| {
+            (-5..=6)
+                .filter(|&amount| amount != 0)
+                .map(move |amount| Move::turn(Pins::from_u8(pins), amount))
+        });
+
+        turns
+            .chain([Move::Flip])
+            .map(move |mov| (mov, self.mov_single(mov)))
+    }
+
+    /// Consecutive turns with the same pins can be merged, and consecutive
+    /// flips cancel. Turns commute with each other, so different pins are
+    /// only allowed in increasing order.
+    fn is_redundant(previous: Move, next: Move) -> bool {
+        match (previous, next) {
+            (Move::Turn { pins: previous, .. }, Move::Turn { pins: next, .. }) => {
+                next.u8() <= previous.u8()
+            }
+            (Move::Flip, Move::Flip) => true,
+            _ => false,
+        }
+    }
+
+    fn render(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+#[cfg(feature = "quickcheck")]
+mod quickcheck_impl {
+    use super::*;
+    use quickcheck::{Arbitrary, Gen};
+
+    impl Arbitrary for Clock {
+        fn arbitrary(g: &mut Gen) -> Self {
+            Self::from_dials(std::array::from_fn(|_| u8::arbitrary(g) % 12))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solved_back_is_solved() {
+        assert_eq!(Clock::SOLVED.back(), [0; 9]);
+    }
+
+    #[test]
+    fn corners_are_shared() {
+        let clock = Clock::SOLVED.mov_single(Move::turn(Pins::UR, 3));
+        assert_eq!(clock.front(), [0, 3, 3, 0, 3, 3, 0, 0, 0]);
+        assert_eq!(clock.back(), [9, 0, 0, 0, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn flip_swaps_faces() {
+        let clock = Clock::SOLVED.mov([Move::turn(Pins::U, 1), Move::Flip, Move::turn(Pins::D, 2)]);
+        assert_eq!(clock.front(), [11, 0, 11, 2, 2, 2, 2, 2, 2]);
+        assert_eq!(clock.back(), [1, 1, 1, 1, 1, 1, 10, 0, 10]);
+    }
+
+    #[test]
+    fn renders_both_faces() {
+        let clock = Clock::SOLVED.mov_single(Move::turn(Pins::UR, 3));
+        assert_eq!(
+            format!("{clock:#}"),
+            " 12  3  3     9 12 12\n 12  3  3    12 12 12\n 12 12 12    12 12 12\n"
+        );
+    }
+
+    #[test]
+    fn parse_and_display_roundtrip() {
+        let scramble = "UR3+ DR1- DL0+ UL6+ U5- R2+ D4- L1+ ALL3- y2 U2+ R0+ D6+ L1- ALL4+ UR DL";
+        let alg: Alg = scramble.parse().unwrap();
+        assert_eq!(alg.len(), 17);
+        assert_eq!(alg.to_string(), scramble);
+        assert_eq!(
+            "URDL2-".parse(),
+            Ok(Move::turn(Pins::UR.union(Pins::DL), -2))
+        );
+        assert!("UR7+".parse::<Move>().is_err());
+    }
+
+    #[cfg(feature = "quickcheck")]
+    quickcheck::quickcheck! {
+        fn move_and_inverse_is_identity(clock: Clock, mov: Move) -> bool {
+            use norcina_core::mov::InvertibleMove as _;
+            clock.mov([mov, mov.inverse()]) == clock
+        }
+
+        fn flip_twice_is_identity(clock: Clock) -> bool {
+            clock.flip().flip() == clock
+        }
+    }
+}
@@ -0,0 +1,318 @@
+use std::{fmt, str::FromStr};
+
+/// One of the 4 pins, named by the corner it is closest to (as seen from the
+/// front).
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Pin {
+    UR = 0,
+    DR = 1,
+    DL = 2,
+    UL = 3,
+}
+
+impl Pin {
+    pub const ALL: [Self; 4] = [Self::UR, Self::DR, Self::DL, Self::UL];
+
+    /// The dials (of the face the pin is pushed towards) that the pin
+    /// connects, as indices of a 3x3 grid in reading order.
+    pub const fn dials(self) -> [u8; 4] {
+        match self {
+            Self::UR => [1, 2, 4, 5],
+            Self::DR => [4, 5, 7, 8],
+            Self::DL => [3, 4, 6, 7],
+            Self::UL => [0, 1, 3, 4],
+        }
+    }
+
+    const fn name(self) -> &'static str {
+        match self {
+            Self::UR => "UR",
+            Self::DR => "DR",
+            Self::DL => "DL",
+            Self::UL => "UL",
+        }
+    }
+}
+
+impl fmt::Display for Pin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Which pins are up (i.e., pushed towards the front).
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Pins {
+    /// Packed field: `----lldr`, one bit per [`Pin`], set if it is up.
+    data: u8,
+}
+
+impl Pins {
+    pub const NONE: Self = Self { data: 0 };
+    pub const UR: Self = Self::single(Pin::UR);
+    pub const DR: Self = Self::single(Pin::DR);
+    pub const DL: Self = Self::single(Pin::DL);
+    pub const UL: Self = Self::single(Pin::UL);
+    pub const U: Self = Self::UR.union(Self::UL);
+    pub const R: Self = Self::UR.union(Self::DR);
+    pub const D: Self = Self::DR.union(Self::DL);
+    pub const L: Self = Self::DL.union(Self::UL);
+    pub const ALL: Self = Self { data: 0b1111 };
+
+    /// The configurations used in WCA notation, along with their names.
+    pub const NAMED: [(Self, &str); 9] = [
+        (Self::UR, "UR"),
+        (Self::DR, "DR"),
+        (Self::DL, "DL"),
+        (Self::UL, "UL"),
+        (Self::U, "U"),
+        (Self::R, "R"),
+        (Self::D, "D"),
+        (Self::L, "L"),
+        (Self::ALL, "ALL"),
+    ];
+
+    #[inline]
+    pub const fn single(pin: Pin) -> Self {
+        Self {
+            data: 1 << pin as u8,
+        }
+    }
+
+    /// # Panics
+    ///
+    /// If `data` is 16 or more.
+    #[inline]
+    pub const fn from_u8(data: u8) -> Self {
+        assert!(data < 16);
+        Self { data }
+    }
+
+    #[inline(always)]
+    pub const fn u8(self) -> u8 {
+        self.data
+    }
+
+    #[inline]
+    pub const fn union(self, other: Self) -> Self {
+        Self {
+            data: self.data | other.data,
+        }
+    }
+
+    #[inline]
+    pub const fn is_up(self, pin: Pin) -> bool {
+        self.data & (1 << pin as u8) != 0
+    }
+
+    pub fn up(self) -> impl Iterator<Item = Pin> {
+        Pin::ALL.into_iter().filter(move |&pin| self.is_up(pin))
+    }
+
+    /// Bitmask of the dials of the front (as in [`Pin::dials`]) that turn
+    /// together when these pins are up.
+    pub const fn dial_mask(self) -> u16 {
+        let mut mask = 0;
+        let mut i = 0;
+        while i < 4 {
+            let pin = Pin::ALL[i];
+            if self.is_up(pin) {
+                let dials = pin.dials();
+                let mut j = 0;
+                while j < 4 {
+                    mask |= 1 << dials[j];
+                    j += 1;
+                }
+            }
+            i += 1;
+        }
+
+        mask
+    }
+
+    /// The name used in WCA notation, if it has one.
+    pub fn name(self) -> Option<&'static str> {
+        Self::NAMED
+            .into_iter()
+            .find_map(|(pins, name)| (pins == self).then_some(name))
+    }
+}
+
+impl fmt::Display for Pins {
+    /// Uses the WCA name if there is one, otherwise each pin that is up, one
+    /// after the other (e.g., `URDL`).
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(name) = self.name() {
+            return f.write_str(name);
+        }
+
+        for pin in self.up() {
+            write!(f, "{pin}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for Pins {
+    type Err = ParseMoveError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some((pins, _)) = Self::NAMED.into_iter().find(|(_, name)| *name == s) {
+            return Ok(pins);
+        }
+
+        let mut pins = Self::NONE;
+        let mut rest = s;
+        while !rest.is_empty() {
+            let pin = Pin::ALL
+                .into_iter()
+                .find(|pin| rest.starts_with(pin.name()))
+                .ok_or_else(|| ParseMoveError(s.to_string()))?;
+
+            pins = pins.union(Self::single(pin));
+            rest = &rest[2..];
+        }
+
+        if pins == Self::NONE {
+            return Err(ParseMoveError(s.to_string()));
+        }
+
+        Ok(pins)
+    }
+}
+
+/// A move of a Clock, in WCA notation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Move {
+    /// Pushes up `pins` (and down the rest), then turns a wheel next to one of
+    /// them `amount` hours clockwise, as seen from the front. Written like
+    /// `UR3+` or `ALL5-`.
+    ///
+    /// `amount` is always normalized between -5 and 6 (inclusive).
+    Turn { pins: Pins, amount: i8 },
+    /// Turns the whole puzzle around, so the back becomes the front. Written
+    /// `y2`.
+    Flip,
+    /// Leaves a pin up, which is how WCA scrambles end. It doesn't change the
+    /// dials. Written like `UR`.
+    PinUp(Pin),
+}
+
+impl Move {
+    /// Normalizes the amount to be between -5 and 6, as done by the WCA.
+    pub const fn turn(pins: Pins, amount: i8) -> Self {
+        Self::Turn {
+            pins,
+            amount: normalize(amount),
+        }
+    }
+}
+
+/// Maps any amount of hours to the equivalent between -5 and 6.
+pub const fn normalize(amount: i8) -> i8 {
+    (amount as i16 + 5).rem_euclid(12) as i8 - 5
+}
+
+impl norcina_core::Move for Move {}
+impl norcina_core::mov::InvertibleMove for Move {
+    fn inverse(&self) -> Self {
+        match *self {
+            Self::Turn { pins, amount } => Self::turn(pins, -amount),
+            other => other,
+        }
+    }
+}
+
+impl norcina_core::mov::RandomMove for Move {
+    /// Generates a random turn, with at least one pin up.
+    fn random(rng: &mut impl rand::Rng) -> Self {
+        Self::turn(
+            Pins::from_u8(rng.random_range(1..16)),
+            rng.random_range(-5..=6),
+        )
+    }
+}
+
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Turn { pins, amount } => {
+                let sign = if *amount < 0 { '-' } else { '+' };
+                write!(f, "{pins}{}{sign}", amount.unsigned_abs())
+            }
+            Self::Flip => f.write_str("y2"),
+            Self::PinUp(pin) => write!(f, "{pin}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseMoveError(String);
+
+impl fmt::Display for ParseMoveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid Clock move {:?}", self.0)
+    }
+}
+
+impl std::error::Error for ParseMoveError {}
+
+impl FromStr for Move {
+    type Err = ParseMoveError;
+
+    /// Parses `y2`, a single pin (`UR`) or a turn (`UR3+`, `ALL0+`, `URDL2-`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseMoveError(s.to_string());
+        let s = s.trim();
+        if s == "y2" {
+            return Ok(Self::Flip);
+        }
+
+        if let Some(pin) = Pin::ALL.into_iter().find(|pin| pin.name() == s) {
+            return Ok(Self::PinUp(pin));
+        }
+
+        let (rest, sign) = match s.as_bytes().last().ok_or_else(err)? {
+            b'+' => (&s[..s.len() - 1], 1),
+            b'-' => (&s[..s.len() - 1], -1),
+            _ => return Err(err()),
+        };
+
+        let digits = rest.len() - rest.bytes().rev().take_while(u8::is_ascii_digit).count();
+        let (pins, amount) = rest.split_at(digits);
+        let amount: i8 = amount.parse().map_err(|_| err())?;
+        if amount > 6 {
+            return Err(err());
+        }
+
+        Ok(Self::turn(pins.parse().map_err(|_| err())?, sign * amount))
+    }
+}
+
+#[cfg(feature = "quickcheck")]
+mod quickcheck_impl {
+    use super::*;
+    use quickcheck::{Arbitrary, Gen};
+
+    impl Arbitrary for Pin {
+        fn arbitrary(g: &mut Gen) -> Self {
+            *g.choose(&Pin::ALL).unwrap()
+        }
+    }
+
+    impl Arbitrary for Move {
+        fn arbitrary(g: &mut Gen) -> Self {
+            match u8::arbitrary(g) % 8 {
+                0 => Self::Flip,
+                1 => Self::PinUp(Pin::arbitrary(g)),
+                _ => Self::turn(
+                    Pins::from_u8(u8::arbitrary(g) % 15 + 1),
+                    i8::arbitrary(g) % 12,
+                ),
+            }
+        }
+    }
+}
@@ -0,0 +1,158 @@
+//! Solving and scrambling, with linear algebra over Z/12.
+//!
+//! Every turn adds its amount to a fixed set of dials, so applying
+//! [`WCA_SEQUENCE`] with some amounts is a linear map from the 14 amounts to
+//! the 14 [dials](Clock::dials). The map is invertible, so there is exactly
+//! one set of amounts that solves each state (and that scrambles into each
+//! state). This is also why picking the amounts at random gives a uniformly
+//! random state.
+//!
+//! # Resources
+//! - WCA regulations on Clock scrambles: <https://www.worldcubeassociation.org/regulations/#4b3d>
+
+use norcina_core::Alg;
+
+use crate::{Clock, Move, Pin, Pins};
+
+/// The pins of each turn of a WCA scramble, and whether the turn is done
+/// after flipping the puzzle (`y2`).
+pub const WCA_SEQUENCE: [(Pins, bool); 14] = [
+    (Pins::UR, false),
+    (Pins::DR, false),
+    (Pins::DL, false),
+    (Pins::UL, false),
+    (Pins::U, false),
+    (Pins::R, false),
+    (Pins::D, false),
+    (Pins::L, false),
+    (Pins::ALL, false),
+    (Pins::U, true),
+    (Pins::R, true),
+    (Pins::D, true),
+    (Pins::L, true),
+    (Pins::ALL, true),
+];
+
+/// Finds the amounts of [`WCA_SEQUENCE`] that solve `clock`.
+pub fn solve(clock: Clock) -> Alg<Move> {
+    let target = clock.dials().map(|dial| (12 - dial) % 12);
+    wca_sequence(solve_linear(target))
+}
+
+/// Finds the amounts of [`WCA_SEQUENCE`] that take the solved state to `clock`.
+pub fn scramble_to(clock: Clock) -> Alg<Move> {
+    // We end up flipped, so we need to solve for the flipped state.
+    wca_sequence(solve_linear(clock.flip().dials()))
+}
+
+/// Generates a random-state scramble, as the WCA does.
+///
+/// Ends by leaving each pin up at random.
+pub fn scramble(rng: &mut impl rand::Rng) -> Alg<Move> {
+    let mut scramble = scramble_to(Clock::random_with_rng(rng));
+    for pin in Pin::ALL {
+        if rng.random() {
+            scramble.moves.push(Move::PinUp(pin));
+        }
+    }
+
+    scramble
+}
+
+fn wca_sequence(amounts: [u8; 14]) -> Alg<Move> {
+    let mut moves = Vec::with_capacity(15);
+    for (i, (pins, flipped)) in WCA_SEQUENCE.into_iter().enumerate() {
+        if flipped && !WCA_SEQUENCE[i - 1].1 {
+            moves.push(Move::Flip);
+        }
+
+        moves.push(Move::turn(pins, amounts[i] as i8));
+    }
+
+    Alg { moves }
+}
+
+/// The effect of each turn of [`WCA_SEQUENCE`] on the dials, as seen from the
+/// starting orientation. Column `j` is the effect of the `j`th turn.
+fn wca_matrix() -> [[u8; 14]; 14] {
+    let columns = WCA_SEQUENCE.map(|(pins, flipped)| {
+        let turned = Clock::SOLVED.turn(pins, 1);
+        // Flipping twice does nothing, so the effect of a turn done after a
+        // flip is the same as flipping after the turn.
+        if flipped { turned.flip() } else { turned }.dials()
+    });
+
+    std::array::from_fn(|row| std::array::from_fn(|column| columns[column][row]))
+}
+
+/// Finds the amounts of [`WCA_SEQUENCE`] that add `target` to the dials, with
+/// Gauss-Jordan elimination over Z/12.
+fn solve_linear(target: [u8; 14]) -> [u8; 14] {
+    let mut matrix = wca_matrix();
+    let mut target = target;
+
+    for column in 0..14 {
+        // Only units (1, 5, 7 and 11) can be pivots, since other numbers
+        // don't have an inverse in Z/12. Conveniently, they are their own
+        // inverse.
+        let pivot = (column..14)
+            .find(|&row| [1, 5, 7, 11].contains(&matrix[row][column]))
+            .expect("The WCA sequence can reach every state");
+
+        matrix.swap(column, pivot);
+        target.swap(column, pivot);
+
+        let inverse = matrix[column][column];
+        matrix[column] = matrix[column].map(|x| x * inverse % 12);
+        target[column] = target[column] * inverse % 12;
+
+        for row in 0..14 {
+            let factor = matrix[row][column];
+            if row == column || factor == 0 {
+                continue;
+            }
+
+            let pivot_row = matrix[column];
+            for (x, pivot_x) in matrix[row].iter_mut().zip(pivot_row) {
+                *x = (*x + 12 * 12 - factor * pivot_x) % 12;
+            }
+            target[row] = (target[row] + 12 * 12 - factor * target[column]) % 12;
+        }
+    }
+
+    target
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{SeedableRng as _, rngs::SmallRng};
+
+    #[test]
+    fn solves_random_states() {
+        let mut rng = SmallRng::seed_from_u64(12);
+        for _ in 0..100 {
+            let clock = Clock::random_with_rng(&mut rng);
+            assert!(clock.mov(solve(clock)).is_solved());
+        }
+    }
+
+    #[test]
+    fn scrambles_to_state() {
+        let mut rng = SmallRng::seed_from_u64(12);
+        for _ in 0..100 {
+            let clock = Clock::random_with_rng(&mut rng);
+            assert_eq!(Clock::SOLVED.mov(scramble_to(clock)), clock);
+        }
+    }
+
+    #[test]
+    fn scramble_has_wca_format() {
+        let scramble = scramble(&mut SmallRng::seed_from_u64(1));
+        let turns = scramble
+            .iter()
+            .filter(|mov| matches!(mov, Move::Turn { .. }));
+        assert_eq!(turns.count(), 14);
+        assert_eq!(scramble[9], Move::Flip);
+    }
+}
@@ -2,11 +2,12 @@ use core::fmt;
 use std::{
     fmt::Write,
     ops::{Deref, DerefMut},
+    str::FromStr,
 };
 
 use crate::mov::{InvertibleMove, RandomMove};
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Alg<M> {
     pub moves: Vec<M>,
 }
@@ -63,6 +64,20 @@ impl<M: fmt::Display> fmt::Display for Alg<M> {
     }
 }
 
+impl<M: FromStr> FromStr for Alg<M> {
+    type Err = M::Err;
+
+    /// Parses each move separated by whitespace.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self {
+            moves: s
+                .split_whitespace()
+                .map(str::parse)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
 impl<M> Deref for Alg<M> {
     type Target = [M];
     fn deref(&self) -> &Self::Target {
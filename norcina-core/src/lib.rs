@@ -11,4 +11,9 @@ pub use mov::Move;
 
 pub mod math;
 
+mod puzzle;
+pub use puzzle::Puzzle;
+
+pub mod search;
+
 pub mod types;
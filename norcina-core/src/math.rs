@@ -27,3 +27,93 @@ pub const fn choose(n: usize, m: usize) -> usize {
 
     output
 }
+
+/// The index of a permutation of `0..n` in lexicographic order (its Lehmer
+/// code), between 0 and n!.
+pub fn permutation_rank(permutation: &[u8]) -> usize {
+    let mut index = 0;
+    for (i, p1) in permutation.iter().enumerate() {
+        index *= permutation.len() - i;
+        for p2 in &permutation[i + 1..] {
+            if p1 > p2 {
+                index += 1;
+            }
+        }
+    }
+
+    index
+}
+
+/// The permutation of `0..N` with the given [rank](permutation_rank).
+pub fn permutation_unrank<const N: usize>(mut index: usize) -> [u8; N] {
+    let mut permutation = [0; N];
+    for i in (0..N.saturating_sub(1)).rev() {
+        permutation[i] = (index % (N - i)) as u8;
+        index /= N - i;
+        for j in (i + 1)..N {
+            if permutation[j] >= permutation[i] {
+                permutation[j] += 1;
+            }
+        }
+    }
+
+    permutation
+}
+
+/// The index of which elements are selected in colexicographic order,
+/// between 0 and `choose(n, k)` (where `k` is the number of selected ones).
+pub fn combination_rank(selected: impl IntoIterator<Item = bool>) -> usize {
+    let mut index = 0;
+    let mut seen = 0;
+    for (i, is_selected) in selected.into_iter().enumerate() {
+        if is_selected {
+            seen += 1;
+            index += choose(i, seen);
+        }
+    }
+
+    index
+}
+
+/// The `k` selected elements out of `N` with the given
+/// [rank](combination_rank).
+pub fn combination_unrank<const N: usize>(mut index: usize, mut k: usize) -> [bool; N] {
+    let mut selected = [false; N];
+    for i in (0..N).rev() {
+        if k > 0 && index >= choose(i, k) {
+            index -= choose(i, k);
+            k -= 1;
+            selected[i] = true;
+        }
+    }
+
+    selected
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn permutation_rank_unrank() {
+        assert_eq!(permutation_rank(&[0, 1, 2, 3]), 0);
+        assert_eq!(permutation_rank(&[3, 2, 1, 0]), fac(4) - 1);
+        for i in 0..fac(5) {
+            assert_eq!(permutation_rank(&permutation_unrank::<5>(i)), i);
+        }
+    }
+
+    #[test]
+    fn combination_rank_unrank() {
+        assert_eq!(combination_rank([true, true, false, false]), 0);
+        assert_eq!(
+            combination_rank([false, false, true, true]),
+            choose(4, 2) - 1
+        );
+        for i in 0..choose(12, 4) {
+            let selected = combination_unrank::<12>(i, 4);
+            assert_eq!(selected.iter().filter(|&&s| s).count(), 4);
+            assert_eq!(combination_rank(selected), i);
+        }
+    }
+}
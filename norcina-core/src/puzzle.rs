@@ -0,0 +1,73 @@
+use std::{fmt, hash::Hash};
+
+use crate::{Alg, Move};
+
+/// The state of a puzzle.
+///
+/// Every puzzle crate implements this for its state, so that searching,
+/// scrambling and displaying can be written once for all of them.
+pub trait Puzzle: Copy + Eq + Hash + fmt::Debug {
+    type Move: Move + Copy + Eq + fmt::Debug + fmt::Display;
+
+    const SOLVED: Self;
+
+    /// Applies a single move.
+    fn apply(self, mov: Self::Move) -> Self;
+
+    /// Applies every move of `alg`, in order.
+    fn apply_alg(mut self, alg: impl IntoIterator<Item = Self::Move>) -> Self {
+        for mov in alg {
+            self = self.apply(mov);
+        }
+        self
+    }
+
+    fn is_solved(self) -> bool {
+        self == Self::SOLVED
+    }
+
+    /// Whether doing `next` right after `previous` is never needed in a
+    /// shortest solution, e.g. because it could be merged into a single move
+    /// or done in the other order.
+    ///
+    /// Searches use this to skip equivalent move sequences, so at least one
+    /// order of every pair of commuting moves has to be allowed.
+    fn is_redundant(previous: Self::Move, next: Self::Move) -> bool {
+        let _ = (previous, next);
+        false
+    }
+
+    /// Generates a uniformly random (reachable) state.
+    fn random_state(rng: &mut impl rand::Rng) -> Self;
+
+    /// Every state that is one move away from `self`, along with the move that
+    /// gets there.
+    fn neighbors(self) -> impl Iterator<Item = (Self::Move, Self)>;
+
+    /// Writes the state in the puzzle's default color scheme.
+    fn render(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result;
+
+    /// The move that takes `self` to `other`, if there is one.
+    fn connecting_move(self, other: Self) -> Option<Self::Move> {
+        self.neighbors()
+            .find_map(|(mov, state)| (state == other).then_some(mov))
+    }
+
+    /// Finds the moves that connect each pair of consecutive states.
+    ///
+    /// # Panics
+    ///
+    /// If any two consecutive states are not one move apart.
+    fn connect(states: &[Self]) -> Alg<Self::Move> {
+        Alg {
+            moves: states
+                .windows(2)
+                .map(|window| {
+                    window[0]
+                        .connecting_move(window[1])
+                        .expect("Consecutive states should be one move apart")
+                })
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,468 @@
+//! Generic [iterative deepening A*](https://en.wikipedia.org/wiki/Iterative_deepening_A*).
+//!
+//! The search skips moves that are [redundant](Puzzle::is_redundant) after the
+//! previous one, doesn't allocate once the buffers of each depth are warm,
+//! and can find every solution of the optimal length (or of every length up
+//! to some maximum) instead of just one.
+
+use std::hash::{DefaultHasher, Hasher as _};
+
+use crate::{Alg, Puzzle};
+
+/// An IDA* search, with an admissible `heuristic` (one that never
+/// overestimates the number of moves left) towards the states that satisfy
+/// `goal`.
+pub struct IdaStar<P: Puzzle, H, G> {
+    heuristic: H,
+    goal: G,
+    transpositions: Option<TranspositionTable<P>>,
+    generators: Option<Vec<P::Move>>,
+    max_depth: u8,
+}
+
+impl<P, H, G> IdaStar<P, H, G>
+where
+    P: Puzzle,
+    H: FnMut(P) -> u8,
+    G: FnMut(P) -> bool,
+{
+    pub fn new(heuristic: H, goal: G) -> Self {
+        Self {
+            heuristic,
+            goal,
+            transpositions: None,
+            generators: None,
+            max_depth: u8::MAX,
+        }
+    }
+
+    /// Remembers the depth at which states were last reached, in a table of
+    /// `2^bits` entries, and skips them when they are reached again with as
+    /// many moves or more.
+    ///
+    /// This skips solutions that go through the same state as another one
+    /// with the same number of moves, so it's best not to use it when looking
+    /// for every solution.
+    pub fn with_transposition_table(mut self, bits: u8) -> Self {
+        self.transpositions = Some(TranspositionTable::new(bits));
+        self
+    }
+
+    /// Only uses the given moves, instead of every [neighbor](Puzzle::neighbors).
+    pub fn with_generators(mut self, generators: impl IntoIterator<Item = P::Move>) -> Self {
+        self.generators = Some(generators.into_iter().collect());
+        self
+    }
+
+    /// Gives up on solutions longer than `max_depth` moves.
+    pub fn with_max_depth(mut self, max_depth: u8) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Finds one of the shortest solutions, or `None` if there are none with
+    /// at most the maximum depth.
+    pub fn solve(self, initial: P) -> Option<Alg<P::Move>> {
+        self.solutions(initial).next()
+    }
+
+    /// Every solution of the shortest length.
+    pub fn solutions(self, initial: P) -> Solutions<P, H, G> {
+        Solutions::new(self, initial, true)
+    }
+
+    /// Every solution up to the maximum depth, shortest first.
+    ///
+    /// Solutions don't go through a goal state before the end.
+    pub fn all_solutions(self, initial: P) -> Solutions<P, H, G> {
+        Solutions::new(self, initial, false)
+    }
+}
+
+/// Iterator over the solutions of an [`IdaStar`] search.
+///
+/// Each iteration of the search only finds solutions longer than the bound of
+/// the previous one, so they are never repeated. With an admissible heuristic
+/// the bound never skips a length that has solutions, so they come shortest
+/// first (otherwise, solutions of the same iteration can come in any order).
+pub struct Solutions<P: Puzzle, H, G> {
+    search: IdaStar<P, H, G>,
+    initial: P,
+    optimal_only: bool,
+    bound: u8,
+    /// Solutions shorter than this were found by a previous iteration.
+    min_depth: u8,
+    /// The smallest estimate that exceeded the bound in this iteration, which
+    /// is the bound of the next one.
+    next_bound: Option<u8>,
+    found: bool,
+    started: bool,
+    path: Vec<P::Move>,
+    /// The successors of each state of the path, reused between nodes.
+    frames: Vec<Frame<P>>,
+    /// Number of frames in use.
+    active: usize,
+}
+
+struct Frame<P: Puzzle> {
+    successors: Vec<(P::Move, P)>,
+    next: usize,
+}
+
+enum Node {
+    Solution,
+    Pruned,
+    Expanded,
+}
+
+impl<P, H, G> Solutions<P, H, G>
+where
+    P: Puzzle,
+    H: FnMut(P) -> u8,
+    G: FnMut(P) -> bool,
+{
+    fn new(mut search: IdaStar<P, H, G>, initial: P, optimal_only: bool) -> Self {
+        let bound = (search.heuristic)(initial);
+        Self {
+            search,
+            initial,
+            optimal_only,
+            bound,
+            min_depth: 0,
+            next_bound: None,
+            found: false,
+            started: false,
+            path: Vec::new(),
+            frames: Vec::new(),
+            active: 0,
+        }
+    }
+
+    fn visit(&mut self, state: P, depth: u8) -> Node {
+        let estimate = depth.saturating_add((self.search.heuristic)(state));
+        if estimate > self.bound {
+            self.exceeded(estimate);
+            return Node::Pruned;
+        }
+
+        if (self.search.goal)(state) {
+            return if depth >= self.min_depth {
+                Node::Solution
+            } else {
+                Node::Pruned
+            };
+        }
+
+        // Every successor would be over the bound.
+        if depth == self.bound {
+            self.exceeded(depth.saturating_add(1));
+            return Node::Pruned;
+        }
+
+        if let Some(table) = &mut self.search.transpositions
+            && !table.visit(state, depth)
+        {
+            return Node::Pruned;
+        }
+
+        let depth = depth as usize;
+        if self.frames.len() == depth {
+            self.frames.push(Frame {
+                successors: Vec::new(),
+                next: 0,
+            });
+        }
+
+        let previous = self.path.last().copied();
+        let is_useful = |mov| previous.is_none_or(|previous| !P::is_redundant(previous, mov));
+        let frame = &mut self.frames[depth];
+        frame.successors.clear();
+        match &self.search.generators {
+            Some(generators) => frame.successors.extend(
+                generators
+                    .iter()
+                    .filter(|&&mov| is_useful(mov))
+                    .map(|&mov| (mov, state.apply(mov))),
+            ),
+            None => frame
+                .successors
+                .extend(state.neighbors().filter(|&(mov, _)| is_useful(mov))),
+        }
+        frame.next = 0;
+
+        Node::Expanded
+    }
+
+    fn exceeded(&mut self, estimate: u8) {
+        self.next_bound = Some(
+            self.next_bound
+                .map_or(estimate, |bound| bound.min(estimate)),
+        );
+    }
+
+    /// Starts the next iteration, returning whether there is one.
+    fn start_iteration(&mut self) -> bool {
+        if self.started {
+            if self.found && self.optimal_only {
+                return false;
+            }
+
+            match self.next_bound {
+                Some(bound) if bound <= self.search.max_depth => {
+                    self.min_depth = self.bound + 1;
+                    self.bound = bound;
+                }
+                _ => return false,
+            }
+        } else if self.bound > self.search.max_depth {
+            return false;
+        }
+
+        self.started = true;
+        self.next_bound = None;
+        if let Some(table) = &mut self.search.transpositions {
+            table.clear();
+        }
+
+        true
+    }
+}
+
+impl<P, H, G> Iterator for Solutions<P, H, G>
+where
+    P: Puzzle,
+    H: FnMut(P) -> u8,
+    G: FnMut(P) -> bool,
+{
+    type Item = Alg<P::Move>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.active == 0 {
+                if !self.start_iteration() {
+                    // Make sure we keep returning `None`.
+                    self.optimal_only = true;
+                    self.found = true;
+                    return None;
+                }
+
+                match self.visit(self.initial, 0) {
+                    Node::Solution => {
+                        self.found = true;
+                        return Some(Alg { moves: Vec::new() });
+                    }
+                    Node::Pruned => {}
+                    Node::Expanded => self.active = 1,
+                }
+
+                continue;
+            }
+
+            let frame = &mut self.frames[self.active - 1];
+            let Some(&(mov, state)) = frame.successors.get(frame.next) else {
+                self.active -= 1;
+                self.path.pop();
+                continue;
+            };
+            frame.next += 1;
+
+            self.path.push(mov);
+            match self.visit(state, self.active as u8) {
+                Node::Solution => {
+                    self.found = true;
+                    let solution = Alg {
+                        moves: self.path.clone(),
+                    };
+                    self.path.pop();
+                    return Some(solution);
+                }
+                Node::Pruned => {
+                    self.path.pop();
+                }
+                Node::Expanded => self.active += 1,
+            }
+        }
+    }
+}
+
+/// A lossy cache of the depth at which states were reached, indexed by the
+/// hash of the state.
+struct TranspositionTable<P> {
+    entries: Vec<Option<(P, u8)>>,
+    mask: u64,
+}
+
+impl<P: Puzzle> TranspositionTable<P> {
+    fn new(bits: u8) -> Self {
+        Self {
+            entries: vec![None; 1 << bits],
+            mask: (1 << bits) - 1,
+        }
+    }
+
+    fn clear(&mut self) {
+        self.entries.fill(None);
+    }
+
+    /// Records that `state` was reached at `depth`, returning whether it
+    /// wasn't already reached with as many moves or fewer.
+    fn visit(&mut self, state: P, depth: u8) -> bool {
+        let mut hasher = DefaultHasher::new();
+        state.hash(&mut hasher);
+        let entry = &mut self.entries[(hasher.finish() & self.mask) as usize];
+
+        if let Some((seen, seen_depth)) = *entry
+            && seen == state
+            && seen_depth <= depth
+        {
+            return false;
+        }
+
+        *entry = Some((state, depth));
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fmt;
+
+    /// A position in a 5x5 grid.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    struct Grid(i8, i8);
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+    enum Step {
+        Left,
+        Right,
+        Down,
+        Up,
+    }
+
+    impl crate::Move for Step {}
+
+    impl fmt::Display for Step {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            fmt::Debug::fmt(self, f)
+        }
+    }
+
+    impl Puzzle for Grid {
+        type Move = Step;
+
+        const SOLVED: Self = Self(0, 0);
+
+        fn apply(self, mov: Step) -> Self {
+            let Self(x, y) = self;
+            let next = match mov {
+                Step::Left => Self(x - 1, y),
+                Step::Right => Self(x + 1, y),
+                Step::Down => Self(x, y - 1),
+                Step::Up => Self(x, y + 1),
+            };
+
+            if (0..5).contains(&next.0) && (0..5).contains(&next.1) {
+                next
+            } else {
+                self
+            }
+        }
+
+        fn random_state(rng: &mut impl rand::Rng) -> Self {
+            Self(rng.random_range(0..5), rng.random_range(0..5))
+        }
+
+        fn neighbors(self) -> impl Iterator<Item = (Step, Self)> {
+            [Step::Left, Step::Right, Step::Down, Step::Up]
+                .into_iter()
+                .map(move |mov| (mov, self.apply(mov)))
+                .filter(move |&(_, next)| next != self)
+        }
+
+        fn is_redundant(previous: Step, next: Step) -> bool {
+            matches!(
+                (previous, next),
+                (Step::Left, Step::Right)
+                    | (Step::Right, Step::Left)
+                    | (Step::Down, Step::Up)
+                    | (Step::Up, Step::Down)
+            )
+        }
+
+        fn render(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "({}, {})", self.0, self.1)
+        }
+    }
+
+    fn manhattan(Grid(x, y): Grid) -> u8 {
+        (x.abs() + y.abs()) as u8
+    }
+
+    #[test]
+    fn finds_every_optimal_solution() {
+        let search = IdaStar::new(manhattan, Grid::is_solved);
+        let solutions: Vec<_> = search.solutions(Grid(2, 1)).collect();
+        assert_eq!(solutions.len(), 3);
+        for solution in &solutions {
+            assert_eq!(solution.len(), 3);
+            assert!(Grid(2, 1).apply_alg(solution.iter().copied()).is_solved());
+        }
+    }
+
+    #[test]
+    fn solved_state_has_empty_solution() {
+        let search = IdaStar::new(manhattan, Grid::is_solved);
+        assert_eq!(search.solve(Grid::SOLVED), Some(Alg { moves: Vec::new() }));
+    }
+
+    #[test]
+    fn all_solutions_come_shortest_first() {
+        let search = IdaStar::new(manhattan, Grid::is_solved).with_max_depth(5);
+        let solutions: Vec<_> = search.all_solutions(Grid(1, 1)).collect();
+        assert!(
+            solutions
+                .windows(2)
+                .all(|pair| pair[0].len() <= pair[1].len())
+        );
+        assert_eq!(
+            solutions
+                .iter()
+                .filter(|solution| solution.len() == 2)
+                .count(),
+            2
+        );
+        assert!(solutions.iter().any(|solution| solution.len() == 4));
+        assert!(solutions.iter().all(|solution| solution.len() <= 5));
+
+        let mut unique = solutions.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), solutions.len());
+    }
+
+    #[test]
+    fn transposition_table_keeps_optimal_length() {
+        let search = IdaStar::new(|_| 0, Grid::is_solved).with_transposition_table(4);
+        let solution = search.solve(Grid(4, 3)).unwrap();
+        assert_eq!(solution.len(), 7);
+    }
+
+    #[test]
+    fn only_uses_generators() {
+        let search = IdaStar::new(|_| 0, Grid::is_solved).with_generators([Step::Left]);
+        let solution = search.solve(Grid(3, 0)).unwrap();
+        assert_eq!(solution.moves, [Step::Left; 3]);
+
+        let search = IdaStar::new(|_| 0, Grid::is_solved)
+            .with_generators([Step::Left])
+            .with_max_depth(10);
+        assert_eq!(search.solve(Grid(3, 1)), None);
+    }
+
+    #[test]
+    fn gives_up_after_max_depth() {
+        let search = IdaStar::new(manhattan, Grid::is_solved).with_max_depth(6);
+        assert_eq!(search.solve(Grid(4, 3)), None);
+    }
+}
@@ -2,7 +2,7 @@ use norcina_core::types::{Axis, Direction};
 use std::fmt::{self, Write as _};
 
 // TODO: Implement everything with transmuting.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Face {
     /// Right
     R = 0,
@@ -76,7 +76,7 @@ impl Face {
         [Face::R, Face::U, Face::F, Face::L, Face::D, Face::B].into_iter()
     }
 
-    fn char(self) -> char {
+    pub(crate) fn char(self) -> char {
         match self {
             Self::R => 'R',
             Self::U => 'U',
@@ -1,11 +1,11 @@
-use std::{fmt, ops};
+use std::{fmt, ops, str::FromStr};
 
 use norcina_core::types::{Axis, Direction};
 
 use crate::face::Face;
 
 #[repr(u8)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Amount {
     Single = 1,
     Double = 2,
@@ -57,7 +57,7 @@ impl ops::Mul<Direction> for Amount {
 }
 
 #[repr(transparent)]
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Move {
     /// Packed field: `---aafff`
     data: u8,
@@ -87,6 +87,11 @@ impl Move {
         Face::iter().flat_map(|face| Amount::iter().map(move |amount| Move::new(face, amount)))
     }
 
+    /// The move as an alg of length one.
+    pub const fn as_slice(&self) -> &[Move] {
+        std::slice::from_ref(self)
+    }
+
     pub const ALL: [Move; 18] = {
         use moves::*;
         [
@@ -131,6 +136,41 @@ impl norcina_core::mov::RandomMove for Move {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseMoveError(String);
+
+impl fmt::Display for ParseMoveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid cube move {:?}", self.0)
+    }
+}
+
+impl std::error::Error for ParseMoveError {}
+
+impl FromStr for Move {
+    type Err = ParseMoveError;
+
+    /// Parses a face followed by nothing, `2` or `'` (e.g., `R`, `U2` or `F'`).
+    /// `2'` is also accepted, as a double move.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseMoveError(s.to_string());
+        let s = s.trim();
+        let first = s.chars().next().ok_or_else(err)?;
+        let face = Face::iter()
+            .find(|face| face.char() == first)
+            .ok_or_else(err)?;
+
+        let amount = match &s[1..] {
+            "" => Amount::Single,
+            "2" | "2'" => Amount::Double,
+            "'" => Amount::Reverse,
+            _ => return Err(err()),
+        };
+
+        Ok(Self::new(face, amount))
+    }
+}
+
 pub mod moves {
     macro_rules! generate_moves {
         ([$($face:tt $amount:tt $name:ident),*]) => {
@@ -168,16 +208,162 @@ pub mod moves {
     ]);
 }
 
+/// Builds an alg as a `[Move; N]` array, evaluated at compile time.
+///
+/// Each element is one of:
+/// - A move, written with `P` instead of `'` (e.g., `R`, `U2` or `FP`).
+/// - A constant alg (an array of moves), possibly with a path, which gets spliced in.
+/// - A group in parentheses, optionally followed by `P` to invert it or by a
+///   number to repeat it (e.g., `(R U)3` or `(SUNE)P`).
+///
+/// Note that `'` can't be used inside of the macro, since Rust tries to parse
+/// it as a char literal.
+///
+/// Since the alg is built in a constant, the elements have to be constants
+/// too (and not local variables).
+///
+/// ```
+/// # use norcina_cube_n::{alg, mov::moves::*};
+/// const SEXY: [norcina_cube_n::mov::Move; 4] = alg!(R U RP UP);
+/// assert_eq!(alg!(F SEXY FP), [F, R, U, RP, UP, FP]);
+/// assert_eq!(alg!((SEXY)P), [U, R, UP, RP]);
+/// assert_eq!(alg!((R U)2 D2), [R, U, R, U, D2]);
+/// ```
 #[macro_export]
 macro_rules! alg {
-    (@ $mov:tt) => { $mov };
+    (@parts [$($parts:expr),*]) => { [$($parts),*] };
+
+    (@parts [$($parts:expr),*] ($($group:tt)*) P $($rest:tt)*) => {
+        $crate::alg!(@parts [$($parts,)* $crate::alg!(@group $($group)*).inverted()] $($rest)*)
+    };
+
+    (@parts [$($parts:expr),*] ($($group:tt)*) $times:literal $($rest:tt)*) => {
+        $crate::alg!(@parts [$($parts,)* $crate::alg!(@group $($group)*).repeated($times)] $($rest)*)
+    };
+
+    (@parts [$($parts:expr),*] ($($group:tt)*) $($rest:tt)*) => {
+        $crate::alg!(@parts [$($parts,)* $crate::alg!(@group $($group)*)] $($rest)*)
+    };
+
+    (@parts [$($parts:expr),*] $segment:ident :: $($rest:tt)*) => {
+        $crate::alg!(@path [$($parts),*] [$segment] $($rest)*)
+    };
+
+    (@parts [$($parts:expr),*] $alg:ident $($rest:tt)*) => {
+        $crate::alg!(@parts [$($parts,)* $crate::mov::alg_macro::Part::new($alg.as_slice())] $($rest)*)
+    };
+
+    (@path [$($parts:expr),*] [$($path:tt)*] $segment:ident :: $($rest:tt)*) => {
+        $crate::alg!(@path [$($parts),*] [$($path)* :: $segment] $($rest)*)
+    };
+
+    (@path [$($parts:expr),*] [$($path:tt)*] $alg:ident $($rest:tt)*) => {
+        $crate::alg!(@parts [$($parts,)* $crate::mov::alg_macro::Part::new($($path)*::$alg.as_slice())] $($rest)*)
+    };
 
-    ($($mov:tt)*) => {{
+    (@group $($alg:tt)*) => {
+        $crate::mov::alg_macro::Part::new($crate::alg!($($alg)*).as_slice())
+    };
+
+    ($($alg:tt)*) => {{
+        #[allow(unused_imports)]
         use $crate::mov::moves::*;
-        [$(alg!(@ $mov)),*]
+
+        const PARTS: &[$crate::mov::alg_macro::Part] = &$crate::alg!(@parts [] $($alg)*);
+        const ALG: [$crate::mov::Move; $crate::mov::alg_macro::len(PARTS)] =
+            $crate::mov::alg_macro::concat(PARTS);
+        ALG
     }};
 }
 
+/// Const functions used by [`alg!`].
+#[doc(hidden)]
+pub mod alg_macro {
+    use super::Move;
+
+    /// A sequence of moves, which might be inverted and repeated.
+    #[derive(Debug, Clone, Copy)]
+    pub struct Part<'a> {
+        moves: &'a [Move],
+        inverted: bool,
+        times: usize,
+    }
+
+    impl<'a> Part<'a> {
+        pub const fn new(moves: &'a [Move]) -> Self {
+            Self {
+                moves,
+                inverted: false,
+                times: 1,
+            }
+        }
+
+        pub const fn inverted(self) -> Self {
+            Self {
+                inverted: !self.inverted,
+                ..self
+            }
+        }
+
+        pub const fn repeated(self, times: usize) -> Self {
+            Self {
+                times: self.times * times,
+                ..self
+            }
+        }
+
+        const fn len(&self) -> usize {
+            self.moves.len() * self.times
+        }
+
+        const fn get(&self, i: usize) -> Move {
+            let n = self.moves.len();
+            if self.inverted {
+                let mov = self.moves[n - 1 - i % n];
+                Move::new(mov.face(), mov.amount().reverse())
+            } else {
+                self.moves[i % n]
+            }
+        }
+    }
+
+    /// The total number of moves of the parts.
+    pub const fn len(parts: &[Part]) -> usize {
+        let mut len = 0;
+        let mut i = 0;
+        while i < parts.len() {
+            len += parts[i].len();
+            i += 1;
+        }
+
+        len
+    }
+
+    /// Puts every move of the parts in an array, one after the other.
+    ///
+    /// # Panics
+    ///
+    /// If `N` is not the [`len`] of the parts.
+    pub const fn concat<const N: usize>(parts: &[Part]) -> [Move; N] {
+        assert!(len(parts) == N, "Alg has the wrong length");
+
+        let mut out = [super::moves::R; N];
+        let mut k = 0;
+        let mut i = 0;
+        while i < parts.len() {
+            let mut j = 0;
+            while j < parts[i].len() {
+                out[k] = parts[i].get(j);
+                k += 1;
+                j += 1;
+            }
+            i += 1;
+        }
+
+        out
+    }
+}
+
 #[cfg(feature = "quickcheck")]
 mod quickcheck_impl {
     use super::*;
@@ -118,7 +118,7 @@ impl Corner {
         }
 
         // TODO: Is this actually uniform?
-        out[7].data += ((-(orientation_sum as i8) % 3) << 3) as u8;
+        out[7].data += ((3 - orientation_sum % 3) % 3) << 3;
         out
     }
 
@@ -175,7 +175,7 @@ impl CornerPosition {
 
         let index = faces
             .into_iter()
-            .map(|face| (face.direction().u8() << face.axis().u8()))
+            .map(|face| face.direction().u8() << face.axis().u8())
             .sum();
 
         CornerPosition { data: index }
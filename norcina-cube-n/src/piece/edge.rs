@@ -72,7 +72,7 @@ impl Edge {
 
     #[inline]
     pub fn set_oriented(&mut self, is_oriented: bool) {
-        self.data = self.data ^ 0b01111 + ((is_oriented as u8 ^ 0b1) << 4);
+        self.data = (self.data & 0b01111) | ((is_oriented as u8 ^ 0b1) << 4);
     }
 
     /// Returns a possible set of 12 edges.
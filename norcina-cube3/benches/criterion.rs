@@ -10,7 +10,7 @@ use rand::{SeedableRng, rngs::SmallRng};
 use std::hint::black_box;
 
 fn benchmark_solve(c: &mut Criterion) {
-    let mut group = c.benchmark_group(format!("Solve 3x3"));
+    let mut group = c.benchmark_group("Solve 3x3");
     group.plot_config(PlotConfiguration::default().summary_scale(AxisScale::Logarithmic));
 
     for len in [1, 3, 5, 6, 7, 8, 9] {
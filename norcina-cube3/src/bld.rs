@@ -0,0 +1,461 @@
+//! Memo for blindfolded solving.
+//!
+//! Every sticker (other than the centers) gets a letter, and a scramble is
+//! memorized as the sequence of stickers that the buffer piece has to be
+//! shot to, in pairs of letters.
+
+use std::fmt;
+
+use norcina_core::types::Axis;
+use norcina_cube_n::{
+    Face,
+    piece::{
+        corner::{self, CornerPosition},
+        edge::{self, EdgePosition},
+    },
+};
+
+use crate::Cube;
+
+use Face::*;
+
+/// The corner stickers in [Speffz](LetterScheme::SPEFFZ) order, each given by
+/// the face it's on followed by the other two faces of the corner.
+const CORNER_STICKERS: [[Face; 3]; 24] = [
+    [U, B, L],
+    [U, B, R],
+    [U, F, R],
+    [U, F, L],
+    [L, U, B],
+    [L, U, F],
+    [L, D, F],
+    [L, D, B],
+    [F, U, L],
+    [F, U, R],
+    [F, D, R],
+    [F, D, L],
+    [R, U, F],
+    [R, U, B],
+    [R, D, B],
+    [R, D, F],
+    [B, U, R],
+    [B, U, L],
+    [B, D, L],
+    [B, D, R],
+    [D, F, L],
+    [D, F, R],
+    [D, B, R],
+    [D, B, L],
+];
+
+/// The edge stickers in [Speffz](LetterScheme::SPEFFZ) order, each given by
+/// the face it's on followed by the other face of the edge.
+const EDGE_STICKERS: [[Face; 2]; 24] = [
+    [U, B],
+    [U, R],
+    [U, F],
+    [U, L],
+    [L, U],
+    [L, F],
+    [L, D],
+    [L, B],
+    [F, U],
+    [F, R],
+    [F, D],
+    [F, L],
+    [R, U],
+    [R, B],
+    [R, D],
+    [R, F],
+    [B, U],
+    [B, L],
+    [B, D],
+    [B, R],
+    [D, F],
+    [D, R],
+    [D, B],
+    [D, L],
+];
+
+/// A sticker of a corner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CornerSticker {
+    pub position: CornerPosition,
+    pub face: Face,
+}
+
+impl CornerSticker {
+    /// The sticker on the first face, of the corner of the three faces.
+    pub fn new(faces: [Face; 3]) -> Self {
+        Self {
+            position: CornerPosition::from_faces(faces),
+            face: faces[0],
+        }
+    }
+
+    fn index(self) -> usize {
+        (0..24)
+            .find(|&i| Self::from_index(i) == self)
+            .expect("Every sticker is listed")
+    }
+
+    fn from_index(index: usize) -> Self {
+        Self::new(CORNER_STICKERS[index])
+    }
+}
+
+/// A sticker of an edge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EdgeSticker {
+    pub position: EdgePosition,
+    pub face: Face,
+}
+
+impl EdgeSticker {
+    /// The sticker on the first face, of the edge of the two faces.
+    pub fn new(faces: [Face; 2]) -> Self {
+        Self {
+            position: EdgePosition::from_faces(faces),
+            face: faces[0],
+        }
+    }
+
+    fn index(self) -> usize {
+        (0..24)
+            .find(|&i| Self::from_index(i) == self)
+            .expect("Every sticker is listed")
+    }
+
+    fn from_index(index: usize) -> Self {
+        Self::new(EDGE_STICKERS[index])
+    }
+}
+
+/// The letter of each sticker.
+///
+/// Letters are given in the order of the stickers of [`LetterScheme::SPEFFZ`]:
+/// faces U, L, F, R, B and D, and in each face clockwise starting from the
+/// top left (or from the back for U and from the front for D).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LetterScheme {
+    pub corners: [char; 24],
+    pub edges: [char; 24],
+}
+
+impl LetterScheme {
+    const ALPHABET: [char; 24] = [
+        'A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J', 'K', 'L', 'M', 'N', 'O', 'P', 'Q', 'R',
+        'S', 'T', 'U', 'V', 'W', 'X',
+    ];
+
+    /// The most common scheme, with letters from `A` to `X`.
+    pub const SPEFFZ: Self = Self {
+        corners: Self::ALPHABET,
+        edges: Self::ALPHABET,
+    };
+
+    pub fn corner(&self, sticker: CornerSticker) -> char {
+        self.corners[sticker.index()]
+    }
+
+    pub fn edge(&self, sticker: EdgeSticker) -> char {
+        self.edges[sticker.index()]
+    }
+}
+
+impl Default for LetterScheme {
+    fn default() -> Self {
+        Self::SPEFFZ
+    }
+}
+
+/// The memo of a scramble.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Memo {
+    /// The edge targets, including the ones that start and end cycle breaks.
+    pub edges: Vec<char>,
+    /// The corner targets, including the ones that start and end cycle breaks.
+    pub corners: Vec<char>,
+    /// The edges that are in their position but flipped, by the letter of the
+    /// sticker where their U or D sticker is (F or B for the E slice).
+    pub flipped_edges: Vec<char>,
+    /// The corners that are in their position but twisted, by the letter of
+    /// the sticker where their U or D sticker is.
+    pub twisted_corners: Vec<char>,
+}
+
+impl Memo {
+    /// Whether there is an odd number of targets (of both edges and corners),
+    /// so an extra algorithm is needed.
+    pub fn has_parity(&self) -> bool {
+        self.corners.len() % 2 == 1
+    }
+
+    /// The edge targets in pairs of letters.
+    pub fn edge_pairs(&self) -> Vec<String> {
+        letter_pairs(&self.edges)
+    }
+
+    /// The corner targets in pairs of letters.
+    pub fn corner_pairs(&self) -> Vec<String> {
+        letter_pairs(&self.corners)
+    }
+}
+
+fn letter_pairs(letters: &[char]) -> Vec<String> {
+    letters
+        .chunks(2)
+        .map(|pair| pair.iter().collect())
+        .collect()
+}
+
+impl fmt::Display for Memo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Edges: {}", self.edge_pairs().join(" "))?;
+        writeln!(f, "Corners: {}", self.corner_pairs().join(" "))?;
+        if !self.flipped_edges.is_empty() {
+            writeln!(f, "Flips: {}", String::from_iter(&self.flipped_edges))?;
+        }
+        if !self.twisted_corners.is_empty() {
+            writeln!(f, "Twists: {}", String::from_iter(&self.twisted_corners))?;
+        }
+        if self.has_parity() {
+            writeln!(f, "Parity")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// How to memorize: the letters and the buffers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Memoizer {
+    pub scheme: LetterScheme,
+    pub corner_buffer: CornerSticker,
+    pub edge_buffer: EdgeSticker,
+}
+
+impl Default for Memoizer {
+    /// Speffz, with UFR and UF buffers.
+    fn default() -> Self {
+        Self {
+            scheme: LetterScheme::SPEFFZ,
+            corner_buffer: CornerSticker::new([U, F, R]),
+            edge_buffer: EdgeSticker::new([U, F]),
+        }
+    }
+}
+
+impl Memoizer {
+    pub fn memo(&self, cube: Cube) -> Memo {
+        // Which sticker (by its solved place) is at each place.
+        let corner_stickers: [usize; 24] = std::array::from_fn(|i| {
+            let place = CornerSticker::from_index(i);
+            let piece = place.position.pick(cube.corners);
+            CornerSticker {
+                position: piece.position(),
+                face: corner::sticker(piece, place.position, place.face),
+            }
+            .index()
+        });
+
+        let edge_stickers: [usize; 24] = std::array::from_fn(|i| {
+            let place = EdgeSticker::from_index(i);
+            let piece = place.position.pick(&cube.edges);
+            EdgeSticker {
+                position: piece.position(),
+                face: edge::sticker(piece, place.position, place.face),
+            }
+            .index()
+        });
+
+        let corner_pieces = std::array::from_fn(|i| CornerSticker::from_index(i).position.u8());
+        let edge_pieces = std::array::from_fn(|i| EdgeSticker::from_index(i).position.index());
+
+        // The U or D sticker of corners, and the one on the orientation axis
+        // of edges.
+        let corner_references =
+            std::array::from_fn(|i| CornerSticker::from_index(i).face.axis() == Axis::Y);
+        let edge_references = std::array::from_fn(|i| {
+            let sticker = EdgeSticker::from_index(i);
+            sticker.face.axis() == sticker.position.orientation_axis()
+        });
+
+        let corners = Trace::new(
+            corner_stickers,
+            corner_pieces,
+            corner_references,
+            self.corner_buffer.index(),
+        );
+        let edges = Trace::new(
+            edge_stickers,
+            edge_pieces,
+            edge_references,
+            self.edge_buffer.index(),
+        );
+
+        Memo {
+            edges: edges
+                .targets
+                .iter()
+                .map(|&i| self.scheme.edges[i])
+                .collect(),
+            corners: corners
+                .targets
+                .iter()
+                .map(|&i| self.scheme.corners[i])
+                .collect(),
+            flipped_edges: edges
+                .misoriented
+                .iter()
+                .map(|&i| self.scheme.edges[i])
+                .collect(),
+            twisted_corners: corners
+                .misoriented
+                .iter()
+                .map(|&i| self.scheme.corners[i])
+                .collect(),
+        }
+    }
+}
+
+/// The cycles of the stickers of one kind of piece.
+struct Trace {
+    targets: Vec<usize>,
+    /// For each piece that is in its position but not oriented, where its
+    /// reference sticker is.
+    misoriented: Vec<usize>,
+}
+
+impl Trace {
+    /// Follows the cycles starting from `buffer`, where `stickers` has the
+    /// sticker at each place, `pieces` has the piece of each sticker and
+    /// `reference` marks the sticker that tells the orientation of each piece.
+    fn new(stickers: [usize; 24], pieces: [u8; 24], reference: [bool; 24], buffer: usize) -> Self {
+        let piece = |sticker: usize| pieces[sticker] as usize;
+        let in_place = |sticker: usize| piece(stickers[sticker]) == piece(sticker);
+
+        let mut visited = [false; 12];
+        visited[piece(buffer)] = true;
+
+        let mut targets = Vec::new();
+        let mut current = buffer;
+        let mut cycle = piece(buffer);
+        loop {
+            let target = stickers[current];
+            if piece(target) != cycle {
+                visited[piece(target)] = true;
+                targets.push(target);
+                current = target;
+                continue;
+            }
+
+            // Closes a cycle break (the buffer cycle is closed by itself).
+            if cycle != piece(buffer) {
+                targets.push(target);
+            }
+
+            // Cycle break into the first piece that is left.
+            let Some(start) = (0..24).find(|&i| !visited[piece(i)] && !in_place(i)) else {
+                break;
+            };
+
+            visited[piece(start)] = true;
+            targets.push(start);
+            current = start;
+            cycle = piece(start);
+        }
+
+        let misoriented = (0..24)
+            .filter(|&i| reference[i] && !visited[piece(i)] && in_place(i) && stickers[i] != i)
+            .map(|i| {
+                (0..24)
+                    .find(|&place| stickers[place] == i)
+                    .expect("Every sticker is somewhere")
+            })
+            .collect();
+
+        Self {
+            targets,
+            misoriented,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algs::pll;
+    use norcina_core::types::Orientation3;
+    use norcina_cube_n::piece::corner::Corner;
+    use rand::{SeedableRng, rngs::StdRng};
+
+    #[test]
+    fn solved_has_empty_memo() {
+        let memo = Memoizer::default().memo(Cube::SOLVED);
+        assert!(memo.edges.is_empty() && memo.corners.is_empty());
+        assert!(memo.flipped_edges.is_empty() && memo.twisted_corners.is_empty());
+        assert!(!memo.has_parity());
+    }
+
+    #[test]
+    fn t_perm() {
+        let memo = Memoizer::default().memo(Cube::SOLVED.mov(pll::T));
+
+        // UFR buffer goes to UBR.
+        assert_eq!(memo.corners, ['B']);
+        // UF is solved, so there's a cycle break from UR to UL and back.
+        assert_eq!(memo.edges, ['B', 'D', 'B']);
+        assert_eq!(memo.edge_pairs(), ["BD", "B"]);
+        assert!(memo.has_parity());
+    }
+
+    #[test]
+    fn flips_and_twists() {
+        let mut cube = Cube::SOLVED;
+        let ub = EdgePosition::from_faces([U, B]).index() as usize;
+        let df = EdgePosition::from_faces([D, F]).index() as usize;
+        cube.edges[ub].set_oriented(false);
+        cube.edges[df].set_oriented(false);
+
+        let ubl = CornerPosition::from_faces([U, B, L]).u8() as usize;
+        let dfr = CornerPosition::from_faces([D, F, R]).u8() as usize;
+        cube.corners[ubl].set_orientation(Orientation3::from_u8(1));
+        cube.corners[dfr].set_orientation(Orientation3::from_u8(2));
+
+        let memo = Memoizer::default().memo(cube);
+        assert!(memo.edges.is_empty() && memo.corners.is_empty());
+        assert_eq!(memo.flipped_edges, ['Q', 'K']);
+        // Clockwise UBL has its U sticker on L, counterclockwise DFR has its
+        // D sticker on R.
+        assert_eq!(memo.twisted_corners, ['E', 'P']);
+    }
+
+    #[test]
+    fn parity_matches_permutation() {
+        let mut rng = StdRng::seed_from_u64(38);
+        for _ in 0..100 {
+            let cube = Cube::random_with_rng(&mut rng);
+            let memo = Memoizer::default().memo(cube);
+
+            assert_eq!(memo.corners.len() % 2, memo.edges.len() % 2);
+            assert_eq!(
+                memo.has_parity(),
+                Corner::count_swaps(cube.corners) % 2 == 1
+            );
+        }
+    }
+
+    #[test]
+    fn other_buffers() {
+        let memoizer = Memoizer {
+            edge_buffer: EdgeSticker::new([U, R]),
+            corner_buffer: CornerSticker::new([U, B, L]),
+            ..Memoizer::default()
+        };
+
+        let memo = memoizer.memo(Cube::SOLVED.mov(pll::T));
+        assert_eq!(memo.edges, ['D']);
+        assert_eq!(memo.corners, ['B', 'C', 'B']);
+    }
+}
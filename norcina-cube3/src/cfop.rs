@@ -0,0 +1,437 @@
+//! Solvers for each step of CFOP: cross, F2L (including XCross), OLL and PLL.
+//!
+//! Every step can be done on any of the six colours. The colour of a piece is
+//! the face it belongs to, so the cross "colour" is the face where the cross
+//! ends up (e.g. [`Face::D`] for the usual white-on-bottom cross).
+//!
+//! Each [`StepSolver`] has pruning tables for the pieces that the step has to
+//! solve, which are then used to find the optimal solutions with IDA*.
+//!
+//! The last layer can also be solved with the algorithms of [`algs::oll`] and
+//! [`algs::pll`], by recognizing the case with [`recognize_oll`] and
+//! [`recognize_pll`].
+
+use norcina_core::{Alg, search::IdaStar};
+use norcina_cube_n::{
+    Face,
+    mov::moves::{U, U2, UP},
+    piece::{
+        corner::{self, Corner, CornerPosition},
+        edge::{self, Edge, EdgePosition},
+    },
+};
+
+use crate::{
+    Cube, Move,
+    algs::{self, Case},
+    search::{SearchSolution, piece_table::PieceTable},
+};
+
+/// Finds the optimal solutions of a CFOP step.
+#[derive(Debug)]
+pub struct StepSolver {
+    /// Positions whose pieces have to be solved.
+    corners: Vec<usize>,
+    edges: Vec<usize>,
+    /// Face whose pieces have to be oriented towards it, for OLL.
+    oriented: Option<Face>,
+    corner_tables: Vec<PieceTable<Corner>>,
+    edge_tables: Vec<PieceTable<Edge>>,
+}
+
+impl StepSolver {
+    fn new(corners: Vec<usize>, edges: Vec<usize>, oriented: Option<Face>) -> Self {
+        Self {
+            corner_tables: PieceTable::generate_chunks((0..8).collect(), &corners, &Move::ALL),
+            edge_tables: PieceTable::generate_chunks((0..12).collect(), &edges, &Move::ALL),
+            corners,
+            edges,
+            oriented,
+        }
+    }
+
+    /// The four edges of `face`.
+    pub fn cross(face: Face) -> Self {
+        Self::new(Vec::new(), cross_edges(face), None)
+    }
+
+    /// The cross along with the pair of one of the slots.
+    pub fn xcross(face: Face, slot: EdgePosition) -> Self {
+        Self::f2l(face, [slot])
+    }
+
+    /// The cross along with the pairs of the given slots. The slots are the
+    /// positions of the edges of the pairs, which are between the cross face
+    /// and its opposite.
+    ///
+    /// # Panics
+    ///
+    /// If any of the slots touches the cross face or its opposite.
+    pub fn f2l(face: Face, slots: impl IntoIterator<Item = EdgePosition>) -> Self {
+        let mut corners = Vec::new();
+        let mut edges = cross_edges(face);
+        for slot in slots {
+            corners.push(slot_corner(face, slot).u8() as usize);
+            edges.push(slot.index() as usize);
+        }
+
+        Self::new(corners, edges, None)
+    }
+
+    /// All of F2L, with the pieces of the last layer oriented.
+    pub fn oll(face: Face) -> Self {
+        let f2l = Self::f2l(face, slots(face));
+        Self {
+            oriented: Some(face.opposite()),
+            ..f2l
+        }
+    }
+
+    /// The whole cube.
+    pub fn pll(face: Face) -> Self {
+        Self::f2l(face, slots(face)).with_last_layer(face)
+    }
+
+    fn with_last_layer(self, face: Face) -> Self {
+        let last_layer = face.opposite();
+        let mut corners = self.corners;
+        corners.extend(
+            CornerPosition::ALL
+                .into_iter()
+                .filter(|position| position.contains_face(last_layer))
+                .map(|position| position.u8() as usize),
+        );
+
+        let mut edges = self.edges;
+        edges.extend(cross_edges(last_layer));
+
+        Self::new(corners, edges, self.oriented)
+    }
+
+    /// Whether the step is done.
+    pub fn is_solved(&self, cube: Cube) -> bool {
+        let corners_are_solved = || {
+            self.corners
+                .iter()
+                .all(|&i| cube.corners[i] == Corner::solved(i as u8))
+        };
+
+        let edges_are_solved = || {
+            self.edges
+                .iter()
+                .all(|&i| cube.edges[i] == Edge::solved(i as u8))
+        };
+
+        let is_oriented = || match self.oriented {
+            Some(face) => is_face_oriented(cube, face),
+            None => true,
+        };
+
+        corners_are_solved() && edges_are_solved() && is_oriented()
+    }
+
+    /// A lower bound of the number of moves to do the step.
+    pub fn distance_lower_bound(&self, cube: Cube) -> u8 {
+        let corners = self.corner_tables.iter().map(|table| table.distance(cube));
+        let edges = self.edge_tables.iter().map(|table| table.distance(cube));
+        corners
+            .chain(edges)
+            .map(|distance| distance.expect("Every state can be solved with every move"))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Every optimal solution of the step.
+    pub fn solutions(&self, cube: Cube) -> impl Iterator<Item = SearchSolution> + '_ {
+        IdaStar::new(
+            |cube| self.distance_lower_bound(cube),
+            |cube| self.is_solved(cube),
+        )
+        .solutions(cube)
+        .map(move |alg| SearchSolution::from_alg(cube, alg))
+    }
+
+    /// One of the optimal solutions of the step.
+    pub fn solve(&self, cube: Cube) -> SearchSolution {
+        self.solutions(cube)
+            .next()
+            .expect("Every step can be solved")
+    }
+}
+
+/// The optimal cross on each of the six colours.
+pub fn crosses(cube: Cube) -> [(Face, SearchSolution); 6] {
+    [Face::R, Face::U, Face::F, Face::L, Face::D, Face::B]
+        .map(|face| (face, StepSolver::cross(face).solve(cube)))
+}
+
+/// The slot whose pair can be inserted with the fewest moves (keeping the
+/// cross and the pairs that are already solved), along with how to insert it.
+///
+/// Returns `None` if every slot is solved.
+pub fn best_f2l_pair(cube: Cube, face: Face) -> Option<(EdgePosition, SearchSolution)> {
+    let (solved, unsolved): (Vec<_>, Vec<_>) = slots(face)
+        .into_iter()
+        .partition(|&slot| is_slot_solved(cube, face, slot));
+
+    unsolved
+        .into_iter()
+        .map(|slot| {
+            let solver = StepSolver::f2l(face, solved.iter().copied().chain([slot]));
+            (slot, solver.solve(cube))
+        })
+        .min_by_key(|(_, solution)| solution.states.len())
+}
+
+/// The slots of the F2L pairs under the cross on `face`, as the positions of
+/// their edges.
+pub fn slots(face: Face) -> [EdgePosition; 4] {
+    let mut slots = EdgePosition::ALL
+        .into_iter()
+        .filter(|position| position.normal() == face.axis());
+    std::array::from_fn(|_| slots.next().expect("There are four slots"))
+}
+
+/// Whether both pieces of the pair of `slot` are solved.
+pub fn is_slot_solved(cube: Cube, face: Face, slot: EdgePosition) -> bool {
+    let corner = slot_corner(face, slot);
+    corner.pick(cube.corners) == Corner::solved(corner.u8())
+        && slot.pick(&cube.edges) == Edge::solved(slot.index())
+}
+
+/// Whether every sticker of the colour of `face` is on that face (ignoring
+/// the rest of the pieces).
+pub fn is_face_oriented(cube: Cube, face: Face) -> bool {
+    let corners = cube
+        .corners()
+        .filter(|(position, _)| position.contains_face(face))
+        .all(|(position, piece)| corner::sticker(piece, position, face) == face);
+
+    let edges = cube
+        .edges()
+        .filter(|(position, _)| position.contains_face(face))
+        .all(|(position, piece)| edge::sticker(piece, position, face) == face);
+
+    corners && edges
+}
+
+/// The ways to adjust the U face: nothing, `U`, `U2` or `U'`.
+const AUFS: [Option<Move>; 4] = [None, Some(U), Some(U2), Some(UP)];
+
+/// A case of the last layer, along with how to turn the U face before and
+/// after doing its algorithm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Recognition {
+    pub pre_auf: Option<Move>,
+    pub case: Case,
+    pub post_auf: Option<Move>,
+}
+
+impl Recognition {
+    /// The moves that solve the step: the pre-AUF, the algorithm of the case
+    /// and the post-AUF.
+    pub fn alg(&self) -> Alg<Move> {
+        let moves = self.pre_auf.into_iter();
+        let moves = moves.chain(self.case.alg.iter().copied());
+        Alg {
+            moves: moves.chain(self.post_auf).collect(),
+        }
+    }
+}
+
+/// Whether the cross on D and the four pairs under it are solved.
+pub fn is_f2l_solved(cube: Cube) -> bool {
+    cross_edges(Face::D)
+        .into_iter()
+        .all(|i| cube.edges[i] == Edge::solved(i as u8))
+        && slots(Face::D)
+            .into_iter()
+            .all(|slot| is_slot_solved(cube, Face::D, slot))
+}
+
+/// Finds the OLL case of `cube`, which has to have F2L solved on D.
+///
+/// Returns `None` if F2L is not solved or the U face is already oriented.
+/// The post-AUF of the recognition is always `None`, since it doesn't matter
+/// for OLL.
+pub fn recognize_oll(cube: Cube) -> Option<Recognition> {
+    if !is_f2l_solved(cube) || is_face_oriented(cube, Face::U) {
+        return None;
+    }
+
+    AUFS.into_iter().find_map(|pre_auf| {
+        let cube = cube.mov(pre_auf);
+        algs::oll::ALL
+            .into_iter()
+            .find(|case| is_face_oriented(cube.mov(case.alg.iter().copied()), Face::U))
+            .map(|case| Recognition {
+                pre_auf,
+                case,
+                post_auf: None,
+            })
+    })
+}
+
+/// Finds the PLL case of `cube`, which has to have F2L solved on D and the U
+/// face oriented.
+///
+/// Returns `None` if the cube is not in such a state or if it only needs an
+/// AUF to be solved.
+pub fn recognize_pll(cube: Cube) -> Option<Recognition> {
+    if !is_f2l_solved(cube) || !is_face_oriented(cube, Face::U) {
+        return None;
+    }
+
+    if AUFS.into_iter().any(|auf| cube.mov(auf).is_solved()) {
+        return None;
+    }
+
+    AUFS.into_iter().find_map(|pre_auf| {
+        algs::pll::ALL.into_iter().find_map(|case| {
+            let cube = cube.mov(pre_auf).mov(case.alg.iter().copied());
+            let post_auf = AUFS
+                .into_iter()
+                .find(|&post_auf| cube.mov(post_auf).is_solved())?;
+
+            Some(Recognition {
+                pre_auf,
+                case,
+                post_auf,
+            })
+        })
+    })
+}
+
+fn cross_edges(face: Face) -> Vec<usize> {
+    EdgePosition::ALL
+        .into_iter()
+        .filter(|position| position.contains_face(face))
+        .map(|position| position.index() as usize)
+        .collect()
+}
+
+fn slot_corner(face: Face, slot: EdgePosition) -> CornerPosition {
+    assert_eq!(
+        slot.normal(),
+        face.axis(),
+        "Slot {slot:?} is not between {face:?} and its opposite"
+    );
+
+    let [a, b] = slot.faces();
+    CornerPosition::from_faces([face, a, b])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::search_bfs;
+    use norcina_cube_n::mov::moves::*;
+
+    const SCRAMBLE: [Move; 8] = [R, U2, F, LP, D, B2, R2, UP];
+
+    #[test]
+    fn cross_is_optimal() {
+        let cube = Cube::SOLVED.mov([R, FP, L2, D]);
+        let solver = StepSolver::cross(Face::U);
+        let solution = solver.solve(cube);
+        let bfs = search_bfs(cube, |cube| solver.is_solved(cube));
+
+        assert!(solver.is_solved(solution.final_state()));
+        assert_eq!(solution.states.len(), bfs.states.len());
+    }
+
+    #[test]
+    fn crosses_on_every_colour() {
+        let cube = Cube::SOLVED.mov(SCRAMBLE);
+        for (face, solution) in crosses(cube) {
+            assert!(StepSolver::cross(face).is_solved(solution.final_state()));
+            assert!(solution.states.len() <= 9);
+        }
+    }
+
+    #[test]
+    fn xcross_solves_cross_and_pair() {
+        let cube = Cube::SOLVED.mov(SCRAMBLE);
+        let slot = EdgePosition::from_faces([Face::F, Face::R]);
+        let solution = StepSolver::xcross(Face::D, slot).solve(cube);
+
+        let state = solution.final_state();
+        assert!(StepSolver::cross(Face::D).is_solved(state));
+        assert!(is_slot_solved(state, Face::D, slot));
+    }
+
+    #[test]
+    fn finds_best_pair_insertion() {
+        let cube = Cube::SOLVED.mov([R, U, RP]);
+        let (slot, solution) = best_f2l_pair(cube, Face::D).unwrap();
+
+        assert_eq!(slot, EdgePosition::from_faces([Face::F, Face::R]));
+        assert_eq!(solution.alg().moves, [R, UP, RP]);
+        assert!(best_f2l_pair(Cube::SOLVED, Face::D).is_none());
+    }
+
+    #[test]
+    fn oll_orients_last_layer() {
+        // Sune
+        let cube = Cube::SOLVED.mov([R, U, RP, U, R, U2, RP]);
+        let solver = StepSolver::oll(Face::D);
+        let solution = solver.solve(cube);
+
+        assert!(!solver.is_solved(cube));
+        assert!(is_face_oriented(solution.final_state(), Face::U));
+        assert_eq!(solution.states.len() - 1, 7);
+    }
+
+    #[test]
+    fn pll_solves_everything() {
+        let cube = Cube::SOLVED.mov([R2, U, R, U, RP, UP, RP, UP, RP, U, RP]);
+        let solution = StepSolver::pll(Face::D).solve(cube);
+        assert!(solution.final_state().is_solved());
+        assert!(solution.states.len() - 1 <= 11);
+    }
+
+    /// The state that `case` solves, turned by `auf`.
+    fn case_state(case: Case, auf: Option<Move>) -> Cube {
+        let alg = Alg {
+            moves: case.alg.to_vec(),
+        };
+        Cube::SOLVED.mov(alg.reversed()).mov(auf)
+    }
+
+    #[test]
+    fn recognizes_every_oll() {
+        for case in algs::oll::ALL {
+            for auf in AUFS {
+                let cube = case_state(case, auf);
+                let recognition = recognize_oll(cube)
+                    .unwrap_or_else(|| panic!("{} should be recognized", case.name));
+
+                assert_eq!(recognition.case, case);
+                assert!(is_f2l_solved(cube.mov(recognition.alg())));
+                assert!(is_face_oriented(cube.mov(recognition.alg()), Face::U));
+            }
+        }
+    }
+
+    #[test]
+    fn recognizes_every_pll() {
+        for case in algs::pll::ALL {
+            for auf in AUFS {
+                let cube = case_state(case, auf);
+                let recognition = recognize_pll(cube)
+                    .unwrap_or_else(|| panic!("{} should be recognized", case.name));
+
+                assert_eq!(recognition.case, case);
+                assert!(cube.mov(recognition.alg()).is_solved());
+            }
+        }
+    }
+
+    #[test]
+    fn nothing_to_recognize() {
+        assert_eq!(recognize_oll(Cube::SOLVED), None);
+        assert_eq!(recognize_pll(Cube::SOLVED.mov([U])), None);
+        assert_eq!(recognize_oll(Cube::SOLVED.mov([R])), None);
+        assert_eq!(recognize_pll(Cube::SOLVED.mov(algs::oll::SUNE)), None);
+    }
+}
@@ -0,0 +1,463 @@
+//! Coordinates: numbers that describe one aspect of a cube (like the
+//! orientation of the corners), as used by Kociemba's algorithm.
+//!
+//! Moves can be applied to coordinates directly with a [`MoveTable`], which is
+//! much faster than going through a [`Cube`], and the distance to solved of
+//! every value can be found with [`MoveTable::distances`] to build pruning
+//! tables.
+
+use std::{fmt, marker::PhantomData};
+
+use norcina_core::{
+    math::{
+        choose, combination_rank, combination_unrank, fac, permutation_rank, permutation_unrank,
+    },
+    types::{Axis, Orientation3},
+};
+use norcina_cube_n::piece::{corner::Corner, edge::Edge};
+
+use crate::{Cube, Move};
+
+/// The moves that keep a cube in G1 = <U, D, R2, L2, F2, B2>, the subgroup of
+/// the second phase of Kociemba's algorithm.
+pub const G1_MOVES: [Move; 10] = {
+    use norcina_cube_n::mov::moves::*;
+    [U, U2, UP, D, D2, DP, R2, L2, F2, B2]
+};
+
+/// One aspect of a cube, as a number between 0 and [`Coordinate::SIZE`].
+pub trait Coordinate: Copy + Eq + fmt::Debug {
+    /// The number of different values.
+    const SIZE: usize;
+
+    /// The moves for which the coordinate stays meaningful.
+    const MOVES: &'static [Move];
+
+    fn from_cube(cube: Cube) -> Self;
+
+    /// A cube with this coordinate. Everything else is as solved as possible,
+    /// but the cube is not necessarily solvable.
+    fn to_cube_partial(self) -> Cube;
+
+    fn index(self) -> usize;
+
+    /// # Panics
+    ///
+    /// If `index` is not less than [`Coordinate::SIZE`].
+    fn from_index(index: usize) -> Self;
+
+    fn solved() -> Self {
+        Self::from_cube(Cube::SOLVED)
+    }
+}
+
+/// The positions of the edges of the U and D layers (those whose normal is not
+/// the y axis).
+const UD_EDGES: [usize; 8] = [0, 1, 2, 3, 8, 9, 10, 11];
+/// The positions of the edges of the E slice (those whose normal is the y
+/// axis).
+const SLICE_EDGES: [usize; 4] = [4, 5, 6, 7];
+
+/// The orientation of the corners, 3^7 values (the last corner's orientation
+/// is given by the rest).
+#[doc(alias = "CO")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CornerOrientation(u16);
+
+impl Coordinate for CornerOrientation {
+    const SIZE: usize = 3usize.pow(7);
+    const MOVES: &'static [Move] = &Move::ALL;
+
+    fn from_cube(cube: Cube) -> Self {
+        let index = cube.corners[..7].iter().fold(0, |index, corner| {
+            index * 3 + corner.orientation().u8() as u16
+        });
+
+        Self(index)
+    }
+
+    fn to_cube_partial(self) -> Cube {
+        let mut cube = Cube::SOLVED;
+        let mut index = self.0;
+        let mut orientation_sum = 0;
+        for corner in cube.corners[..7].iter_mut().rev() {
+            let orientation = Orientation3::from_u8((index % 3) as u8);
+            corner.set_orientation(orientation);
+            orientation_sum += orientation.u8();
+            index /= 3;
+        }
+
+        cube.corners[7].set_orientation(Orientation3::from_i8_mod3(-(orientation_sum as i8)));
+        cube
+    }
+
+    fn index(self) -> usize {
+        self.0 as usize
+    }
+
+    fn from_index(index: usize) -> Self {
+        assert!(index < Self::SIZE);
+        Self(index as u16)
+    }
+}
+
+/// The orientation of the edges, 2^11 values (the last edge's orientation is
+/// given by the rest).
+#[doc(alias = "EO")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EdgeOrientation(u16);
+
+impl Coordinate for EdgeOrientation {
+    const SIZE: usize = 2usize.pow(11);
+    const MOVES: &'static [Move] = &Move::ALL;
+
+    fn from_cube(cube: Cube) -> Self {
+        let index = cube.edges[..11]
+            .iter()
+            .fold(0, |index, edge| index * 2 + !edge.is_oriented() as u16);
+
+        Self(index)
+    }
+
+    fn to_cube_partial(self) -> Cube {
+        let mut cube = Cube::SOLVED;
+        let mut index = self.0;
+        let mut flipped = false;
+        for edge in cube.edges[..11].iter_mut().rev() {
+            let is_oriented = index.is_multiple_of(2);
+            edge.set_oriented(is_oriented);
+            flipped ^= !is_oriented;
+            index /= 2;
+        }
+
+        cube.edges[11].set_oriented(!flipped);
+        cube
+    }
+
+    fn index(self) -> usize {
+        self.0 as usize
+    }
+
+    fn from_index(index: usize) -> Self {
+        assert!(index < Self::SIZE);
+        Self(index as u16)
+    }
+}
+
+/// Which positions have the edges of the E slice, but not in which order,
+/// `choose(12, 4)` values.
+#[doc(alias = "UDSlice")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct UdSlice(u16);
+
+impl Coordinate for UdSlice {
+    const SIZE: usize = choose(12, 4);
+    const MOVES: &'static [Move] = &Move::ALL;
+
+    fn from_cube(cube: Cube) -> Self {
+        Self(combination_rank(cube.edges.map(|edge| edge.normal() == Axis::Y)) as u16)
+    }
+
+    fn to_cube_partial(self) -> Cube {
+        let mut cube = Cube::SOLVED;
+        let mut slice_edges = SLICE_EDGES.into_iter();
+        let mut ud_edges = UD_EDGES.into_iter();
+        for (edge, is_slice) in cube
+            .edges
+            .iter_mut()
+            .zip(combination_unrank::<12>(self.0 as usize, 4))
+        {
+            let piece = if is_slice {
+                slice_edges.next()
+            } else {
+                ud_edges.next()
+            };
+            *edge = Edge::solved(piece.expect("There are enough edges") as u8);
+        }
+
+        cube
+    }
+
+    fn index(self) -> usize {
+        self.0 as usize
+    }
+
+    fn from_index(index: usize) -> Self {
+        assert!(index < Self::SIZE);
+        Self(index as u16)
+    }
+}
+
+/// The permutation of the corners, 8! values.
+#[doc(alias = "CP")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CornerPermutation(u16);
+
+impl Coordinate for CornerPermutation {
+    const SIZE: usize = fac(8);
+    const MOVES: &'static [Move] = &Move::ALL;
+
+    fn from_cube(cube: Cube) -> Self {
+        Self(permutation_rank(&cube.corners.map(|corner| corner.position().u8())) as u16)
+    }
+
+    fn to_cube_partial(self) -> Cube {
+        Cube {
+            corners: permutation_unrank::<8>(self.0 as usize).map(Corner::solved),
+            ..Cube::SOLVED
+        }
+    }
+
+    fn index(self) -> usize {
+        self.0 as usize
+    }
+
+    fn from_index(index: usize) -> Self {
+        assert!(index < Self::SIZE);
+        Self(index as u16)
+    }
+}
+
+/// The permutation of the edges of the U and D layers, 8! values. Only
+/// meaningful in G1, where those edges stay in those layers.
+#[doc(alias = "EP")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EdgePermutation(u16);
+
+impl Coordinate for EdgePermutation {
+    const SIZE: usize = fac(8);
+    const MOVES: &'static [Move] = &G1_MOVES;
+
+    fn from_cube(cube: Cube) -> Self {
+        let slots = UD_EDGES.map(|position| {
+            let home = cube.edges[position].position().index() as usize;
+            UD_EDGES
+                .iter()
+                .position(|&ud| ud == home)
+                .expect("Cube should be in G1") as u8
+        });
+
+        Self(permutation_rank(&slots) as u16)
+    }
+
+    fn to_cube_partial(self) -> Cube {
+        let mut cube = Cube::SOLVED;
+        for (position, slot) in UD_EDGES
+            .into_iter()
+            .zip(permutation_unrank::<8>(self.0 as usize))
+        {
+            cube.edges[position] = Edge::solved(UD_EDGES[slot as usize] as u8);
+        }
+
+        cube
+    }
+
+    fn index(self) -> usize {
+        self.0 as usize
+    }
+
+    fn from_index(index: usize) -> Self {
+        assert!(index < Self::SIZE);
+        Self(index as u16)
+    }
+}
+
+/// The permutation of the edges of the E slice, 4! values. Only meaningful in
+/// G1, where those edges stay in the slice.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SlicePermutation(u8);
+
+impl Coordinate for SlicePermutation {
+    const SIZE: usize = fac(4);
+    const MOVES: &'static [Move] = &G1_MOVES;
+
+    fn from_cube(cube: Cube) -> Self {
+        let slots = SLICE_EDGES.map(|position| {
+            let home = cube.edges[position].position().index() as usize;
+            SLICE_EDGES
+                .iter()
+                .position(|&slice| slice == home)
+                .expect("Cube should be in G1") as u8
+        });
+
+        Self(permutation_rank(&slots) as u8)
+    }
+
+    fn to_cube_partial(self) -> Cube {
+        let mut cube = Cube::SOLVED;
+        for (position, slot) in SLICE_EDGES
+            .into_iter()
+            .zip(permutation_unrank::<4>(self.0 as usize))
+        {
+            cube.edges[position] = Edge::solved(SLICE_EDGES[slot as usize] as u8);
+        }
+
+        cube
+    }
+
+    fn index(self) -> usize {
+        self.0 as usize
+    }
+
+    fn from_index(index: usize) -> Self {
+        assert!(index < Self::SIZE);
+        Self(index as u8)
+    }
+}
+
+/// The result of applying each of [`Coordinate::MOVES`] to each value of a
+/// coordinate.
+pub struct MoveTable<C> {
+    table: Vec<u32>,
+    _coordinate: PhantomData<C>,
+}
+
+impl<C> fmt::Debug for MoveTable<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MoveTable")
+            .field("len", &self.table.len())
+            .finish()
+    }
+}
+
+impl<C: Coordinate> MoveTable<C> {
+    pub fn new() -> Self {
+        let mut table = Vec::with_capacity(C::SIZE * C::MOVES.len());
+        for index in 0..C::SIZE {
+            let cube = C::from_index(index).to_cube_partial();
+            for &mov in C::MOVES {
+                table.push(C::from_cube(cube.mov_single(mov)).index() as u32);
+            }
+        }
+
+        Self {
+            table,
+            _coordinate: PhantomData,
+        }
+    }
+
+    /// # Panics
+    ///
+    /// If `mov` is not one of [`Coordinate::MOVES`].
+    pub fn apply(&self, coordinate: C, mov: Move) -> C {
+        let column = C::MOVES
+            .iter()
+            .position(|&m| m == mov)
+            .expect("Move should keep the coordinate meaningful");
+
+        C::from_index(self.table[coordinate.index() * C::MOVES.len() + column] as usize)
+    }
+
+    /// The number of moves (of [`Coordinate::MOVES`]) to solve each value,
+    /// by index.
+    pub fn distances(&self) -> Vec<u8> {
+        self.distances_with(C::MOVES)
+    }
+
+    /// The number of moves to solve each value by index, using only `moves`.
+    ///
+    /// # Panics
+    ///
+    /// If some of the `moves` are not in [`Coordinate::MOVES`].
+    pub fn distances_with(&self, moves: &[Move]) -> Vec<u8> {
+        let columns: Vec<usize> = moves
+            .iter()
+            .map(|mov| {
+                C::MOVES
+                    .iter()
+                    .position(|m| m == mov)
+                    .expect("Move should keep the coordinate meaningful")
+            })
+            .collect();
+
+        let mut distances = vec![u8::MAX; C::SIZE];
+        let mut frontier = vec![C::solved().index()];
+        distances[frontier[0]] = 0;
+
+        for depth in 1.. {
+            let mut next = Vec::new();
+            for index in frontier {
+                for &column in &columns {
+                    let moved = self.table[index * C::MOVES.len() + column] as usize;
+                    if distances[moved] == u8::MAX {
+                        distances[moved] = depth;
+                        next.push(moved);
+                    }
+                }
+            }
+
+            if next.is_empty() {
+                break;
+            }
+            frontier = next;
+        }
+
+        distances
+    }
+}
+
+impl<C: Coordinate> Default for MoveTable<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use norcina_cube_n::mov::moves::*;
+    use quickcheck::quickcheck;
+
+    fn check_coordinate<C: Coordinate>(scramble: &[Move]) {
+        let table = MoveTable::<C>::new();
+        let mut cube = Cube::SOLVED;
+        for &mov in scramble {
+            let before = C::from_cube(cube);
+            cube = cube.mov_single(mov);
+            assert_eq!(table.apply(before, mov), C::from_cube(cube));
+        }
+
+        let distances = table.distances();
+        assert_eq!(distances[C::solved().index()], 0);
+        assert!(distances.iter().all(|&distance| distance != u8::MAX));
+    }
+
+    fn index_round_trips<C: Coordinate>(index: usize) -> bool {
+        let index = index % C::SIZE;
+        C::from_index(index).index() == index
+            && C::from_cube(C::from_index(index).to_cube_partial()).index() == index
+    }
+
+    quickcheck! {
+        fn index_round_trips_co(index: usize) -> bool { index_round_trips::<CornerOrientation>(index) }
+        fn index_round_trips_eo(index: usize) -> bool { index_round_trips::<EdgeOrientation>(index) }
+        fn index_round_trips_ud_slice(index: usize) -> bool { index_round_trips::<UdSlice>(index) }
+        fn index_round_trips_cp(index: usize) -> bool { index_round_trips::<CornerPermutation>(index) }
+        fn index_round_trips_ep(index: usize) -> bool { index_round_trips::<EdgePermutation>(index) }
+        fn index_round_trips_slice(index: usize) -> bool { index_round_trips::<SlicePermutation>(index) }
+    }
+
+    #[test]
+    fn phase1_coordinates() {
+        let scramble = [R, U, FP, L2, D, B, R2, UP, F];
+        check_coordinate::<CornerOrientation>(&scramble);
+        check_coordinate::<EdgeOrientation>(&scramble);
+        check_coordinate::<UdSlice>(&scramble);
+        check_coordinate::<CornerPermutation>(&scramble);
+    }
+
+    #[test]
+    fn phase2_coordinates() {
+        let scramble = [U, R2, D, F2, UP, L2, B2, D2];
+        check_coordinate::<EdgePermutation>(&scramble);
+        check_coordinate::<SlicePermutation>(&scramble);
+    }
+
+    #[test]
+    fn solved_coordinates() {
+        assert_eq!(CornerOrientation::solved().index(), 0);
+        assert_eq!(EdgeOrientation::solved().index(), 0);
+        assert_eq!(CornerPermutation::solved().index(), 0);
+        assert_eq!(EdgePermutation::solved().index(), 0);
+    }
+}
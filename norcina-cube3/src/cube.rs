@@ -232,6 +232,38 @@ impl fmt::Display for Cube {
     }
 }
 
+impl norcina_core::Puzzle for Cube {
+    type Move = Move;
+
+    const SOLVED: Self = Self::SOLVED;
+
+    fn apply(self, mov: Move) -> Self {
+        self.mov_single(mov)
+    }
+
+    fn is_solved(self) -> bool {
+        self.is_solved()
+    }
+
+    fn random_state(rng: &mut impl rand::Rng) -> Self {
+        Self::random_with_rng(rng)
+    }
+
+    fn neighbors(self) -> impl Iterator<Item = (Move, Self)> {
+        self.neighbors()
+    }
+
+    /// Moves of the same face can be merged, and moves of opposite faces
+    /// commute, so they are only allowed in one order.
+    fn is_redundant(previous: Move, next: Move) -> bool {
+        previous.axis() == next.axis() && next.face().u8() <= previous.face().u8()
+    }
+
+    fn render(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, DEFAULT_COLOR_SCHEME)
+    }
+}
+
 #[cfg(all(test, feature = "quickcheck"))]
 mod tests {
     use quickcheck::{Arbitrary, Gen};
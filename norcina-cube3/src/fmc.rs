@@ -0,0 +1,583 @@
+//! Tools for fewest moves: NISS, skeletons and insertions.
+//!
+//! A usual FMC solution is made of a skeleton, that solves most of the cube
+//! and leaves a few pieces (say, a corner 3-cycle), and insertions of
+//! commutators somewhere in the middle of the skeleton that solve the rest
+//! while cancelling as many moves as possible. [`InsertionFinder`] finds
+//! them greedily, one 3-cycle at a time (see [`InsertionFinder::solve`] for
+//! what that can't solve).
+
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt,
+};
+
+use norcina_core::mov::InvertibleMove as _;
+use norcina_cube_n::mov::Amount;
+
+use crate::{Alg, Cube, Move};
+
+/// Merges consecutive moves of the same face, including the ones that are
+/// only separated by moves of the opposite face (like `R L R`), since they
+/// commute.
+pub fn cancel(moves: impl IntoIterator<Item = Move>) -> Alg {
+    let mut output: Vec<Move> = Vec::new();
+    for mov in moves {
+        // Only the last two moves can be of the same axis.
+        let same_face = output
+            .iter()
+            .rev()
+            .take(2)
+            .take_while(|last| last.axis() == mov.axis())
+            .position(|last| last.face() == mov.face())
+            .map(|back| output.len() - 1 - back);
+
+        let Some(i) = same_face else {
+            output.push(mov);
+            continue;
+        };
+
+        match (output[i].amount().u8() + mov.amount().u8()) % 4 {
+            0 => {
+                output.remove(i);
+            }
+            amount => output[i] = Move::new(mov.face(), Amount::from_u8(amount)),
+        }
+    }
+
+    Alg { moves: output }
+}
+
+/// Which scramble a move is done on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Side {
+    /// The scramble itself.
+    Normal,
+    /// The inverse of the scramble.
+    Inverse,
+}
+
+/// A partial solution made of moves on the normal scramble and moves on the
+/// inverse scramble ("normal inverse scramble switch", or NISS).
+///
+/// Moves done on the inverse go at the end of the solution, inverted and in
+/// reverse order.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NissSolution {
+    pub normal: Alg,
+    pub inverse: Alg,
+}
+
+impl NissSolution {
+    pub fn new() -> Self {
+        Self {
+            normal: Alg { moves: Vec::new() },
+            inverse: Alg { moves: Vec::new() },
+        }
+    }
+
+    pub fn push(&mut self, side: Side, mov: Move) {
+        self.side_mut(side).moves.push(mov);
+    }
+
+    pub fn extend(&mut self, side: Side, moves: impl IntoIterator<Item = Move>) {
+        self.side_mut(side).moves.extend(moves);
+    }
+
+    fn side_mut(&mut self, side: Side) -> &mut Alg {
+        match side {
+            Side::Normal => &mut self.normal,
+            Side::Inverse => &mut self.inverse,
+        }
+    }
+
+    /// The state that is seen on `side`: the scramble (or its inverse)
+    /// followed by the moves of that side, and preceded by the inverse of
+    /// the moves of the other side. Solving it with moves on `side` solves
+    /// the scramble with [`NissSolution::skeleton`].
+    pub fn cube(&self, scramble: &[Move], side: Side) -> Cube {
+        match side {
+            Side::Normal => Cube::SOLVED
+                .mov(self.inverse.clone().reversed())
+                .mov(scramble.iter().copied())
+                .mov(self.normal.iter().copied()),
+            Side::Inverse => Cube::SOLVED
+                .mov(self.normal.clone().reversed())
+                .mov(inverse_scramble(scramble))
+                .mov(self.inverse.iter().copied()),
+        }
+    }
+
+    /// The moves on both sides as a single alg on the normal scramble, with
+    /// cancellations.
+    pub fn skeleton(&self) -> Alg {
+        cancel(
+            self.normal
+                .iter()
+                .copied()
+                .chain(self.inverse.clone().reversed()),
+        )
+    }
+}
+
+impl fmt::Display for NissSolution {
+    /// Normal moves, followed by the inverse moves in parentheses, like
+    /// `R U (F' D)`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.normal)?;
+        if !self.inverse.is_empty() {
+            if !self.normal.is_empty() {
+                write!(f, " ")?;
+            }
+            write!(f, "({})", self.inverse)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for NissSolution {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The inverse of a scramble, to solve it with NISS.
+pub fn inverse_scramble(scramble: &[Move]) -> Alg {
+    Alg {
+        moves: scramble.to_vec(),
+    }
+    .reversed()
+}
+
+/// A cycle of pieces that are not solved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Cycle {
+    pub length: u8,
+    /// Whether going around the cycle twists (or flips) the pieces. A single
+    /// twisted corner is a misoriented cycle of length 1.
+    pub misoriented: bool,
+}
+
+/// What is left to solve after a skeleton.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Leftover {
+    pub corner_cycles: Vec<Cycle>,
+    pub edge_cycles: Vec<Cycle>,
+}
+
+impl Leftover {
+    pub fn new(cube: Cube) -> Self {
+        Self {
+            corner_cycles: cycles(
+                &cube.corners.map(|corner| {
+                    (
+                        corner.position().u8() as usize,
+                        corner.orientation().u8() as usize,
+                    )
+                }),
+                3,
+            ),
+            edge_cycles: cycles(
+                &cube.edges.map(|edge| {
+                    (
+                        edge.position().index() as usize,
+                        !edge.is_oriented() as usize,
+                    )
+                }),
+                2,
+            ),
+        }
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.corner_cycles.is_empty() && self.edge_cycles.is_empty()
+    }
+
+    /// The number of pieces that are not solved.
+    pub fn unsolved_pieces(&self) -> usize {
+        self.corner_cycles
+            .iter()
+            .chain(&self.edge_cycles)
+            .map(|cycle| cycle.length as usize)
+            .sum()
+    }
+
+    /// Whether only three corners are left, and they can be solved with a
+    /// pure commutator.
+    pub fn is_corner_3_cycle(&self) -> bool {
+        self.edge_cycles.is_empty()
+            && self.corner_cycles
+                == [Cycle {
+                    length: 3,
+                    misoriented: false,
+                }]
+    }
+
+    /// Whether only three edges are left, and they can be solved with a pure
+    /// commutator.
+    pub fn is_edge_3_cycle(&self) -> bool {
+        self.corner_cycles.is_empty()
+            && self.edge_cycles
+                == [Cycle {
+                    length: 3,
+                    misoriented: false,
+                }]
+    }
+}
+
+/// The cycles of some pieces, given by the home and the orientation of the
+/// piece at each position.
+fn cycles(pieces: &[(usize, usize)], orientations: usize) -> Vec<Cycle> {
+    let mut visited = vec![false; pieces.len()];
+    let mut cycles = Vec::new();
+    for start in 0..pieces.len() {
+        let mut position = start;
+        let mut length = 0;
+        let mut orientation = 0;
+        while !visited[position] {
+            visited[position] = true;
+            length += 1;
+            orientation += pieces[position].1;
+            position = pieces[position].0;
+        }
+
+        if length > 1 || orientation % orientations != 0 {
+            cycles.push(Cycle {
+                length,
+                misoriented: orientation % orientations != 0,
+            });
+        }
+    }
+
+    cycles
+}
+
+impl fmt::Display for Leftover {
+    /// The cycles of the corners and then the edges, like `3c` or `2c2c 3e`.
+    /// Misoriented cycles are followed by `*`, so a twisted corner is `1c*`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_solved() {
+            return write!(f, "solved");
+        }
+
+        write_cycles(f, &self.corner_cycles, 'c')?;
+        if !self.corner_cycles.is_empty() && !self.edge_cycles.is_empty() {
+            write!(f, " ")?;
+        }
+        write_cycles(f, &self.edge_cycles, 'e')
+    }
+}
+
+fn write_cycles(f: &mut fmt::Formatter<'_>, cycles: &[Cycle], letter: char) -> fmt::Result {
+    for cycle in cycles {
+        write!(f, "{}{letter}", cycle.length)?;
+        if cycle.misoriented {
+            write!(f, "*")?;
+        }
+    }
+
+    Ok(())
+}
+
+/// A commutator inserted in a skeleton.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Insertion {
+    /// The number of moves of the skeleton before the commutator.
+    pub position: usize,
+    pub alg: Alg,
+}
+
+/// The result of inserting commutators in a skeleton.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct InsertionSolution {
+    /// The insertions, in order. The position of each one is in the solution
+    /// with the previous ones already inserted (and cancelled).
+    pub insertions: Vec<Insertion>,
+    /// The final solution, with cancellations.
+    pub solution: Alg,
+}
+
+/// Finds insertions of 3-cycles in skeletons.
+#[derive(Debug)]
+pub struct InsertionFinder {
+    commutators: Vec<Alg>,
+}
+
+impl InsertionFinder {
+    /// Generates every pure commutator `[A, B]` that is a 3-cycle of corners
+    /// or edges, where `A` is one move and `B` is one move conjugated by up to
+    /// two moves, and then conjugates them (as in `[S: [A, B]]`) until there
+    /// is one for every 3-cycle. Only the shortest one of each 3-cycle is
+    /// kept.
+    pub fn new() -> Self {
+        let mut setups: Vec<Vec<Move>> = vec![Vec::new()];
+        setups.extend(Move::iter().map(|mov| vec![mov]));
+        for first in Move::iter() {
+            for second in Move::iter().filter(|second| second.face() != first.face()) {
+                setups.push(vec![first, second]);
+            }
+        }
+
+        let mut commutators: HashMap<Cube, Alg> = HashMap::new();
+        for setup in &setups {
+            for interchange in Move::iter() {
+                let b: Vec<Move> = setup
+                    .iter()
+                    .copied()
+                    .chain([interchange])
+                    .chain(setup.iter().rev().map(|mov| mov.inverse()))
+                    .collect();
+
+                for a in Move::iter() {
+                    for alg in [commutator(&[a], &b), commutator(&b, &[a])] {
+                        let cube = Cube::SOLVED.mov(alg.iter().copied());
+                        let leftover = Leftover::new(cube);
+                        if !leftover.is_corner_3_cycle() && !leftover.is_edge_3_cycle() {
+                            continue;
+                        }
+
+                        let shortest = commutators.entry(cube).or_insert_with(|| alg.clone());
+                        if alg.len() < shortest.len() {
+                            *shortest = alg;
+                        }
+                    }
+                }
+            }
+        }
+
+        // Conjugate them until every 3-cycle has an alg.
+        let mut queue: VecDeque<Alg> = commutators.values().cloned().collect();
+        while let Some(alg) = queue.pop_front() {
+            for setup in Move::iter() {
+                let conjugated = cancel(
+                    [setup]
+                        .into_iter()
+                        .chain(alg.iter().copied())
+                        .chain([setup.inverse()]),
+                );
+
+                let cube = Cube::SOLVED.mov(conjugated.iter().copied());
+                let shortest = commutators.get(&cube);
+                if shortest.is_none_or(|shortest| conjugated.len() < shortest.len()) {
+                    commutators.insert(cube, conjugated.clone());
+                    queue.push_back(conjugated);
+                }
+            }
+        }
+
+        let mut commutators: Vec<Alg> = commutators.into_values().collect();
+        commutators.sort_by_cached_key(|alg| (alg.len(), alg.to_string()));
+        Self { commutators }
+    }
+
+    /// Every commutator that is tried.
+    pub fn commutators(&self) -> &[Alg] {
+        &self.commutators
+    }
+
+    /// Inserts commutators into `skeleton` until `scramble` is solved.
+    ///
+    /// Every commutator is tried at every position, but the search is
+    /// greedy: each time, it keeps the insertion that solves the most pieces
+    /// and, among those, the one that gives the shortest solution after
+    /// cancellations. So the result is the shortest one when a single
+    /// insertion is enough (like for a corner 3-cycle), but with more than
+    /// one insertion a worse first one could lead to a shorter final
+    /// solution, and that is not tried.
+    ///
+    /// Returns `None` if at some point no insertion solves more pieces. That
+    /// is the case for leftovers that need an insertion that doesn't solve
+    /// any piece by itself, like two twisted corners or two flipped edges,
+    /// and for odd permutations (like a T perm), which 3-cycles can't solve
+    /// at all.
+    pub fn solve(&self, scramble: &[Move], skeleton: &[Move]) -> Option<InsertionSolution> {
+        let scrambled = Cube::SOLVED.mov(scramble.iter().copied());
+        let mut solution = cancel(skeleton.iter().copied());
+        let mut insertions = Vec::new();
+        let mut unsolved = Leftover::new(scrambled.mov(solution.iter().copied())).unsolved_pieces();
+
+        while unsolved > 0 {
+            let mut best: Option<(usize, Insertion, Alg)> = None;
+            let mut before = scrambled;
+            for position in 0..=solution.len() {
+                for commutator in &self.commutators {
+                    let cube = before
+                        .mov(commutator.iter().copied())
+                        .mov(solution[position..].iter().copied());
+                    let left = Leftover::new(cube).unsolved_pieces();
+                    if left >= unsolved {
+                        continue;
+                    }
+
+                    let inserted = cancel(
+                        solution[..position]
+                            .iter()
+                            .chain(commutator.iter())
+                            .chain(&solution[position..])
+                            .copied(),
+                    );
+
+                    let is_better = best.as_ref().is_none_or(|(best_left, _, best_alg)| {
+                        (left, inserted.len()) < (*best_left, best_alg.len())
+                    });
+
+                    if is_better {
+                        let insertion = Insertion {
+                            position,
+                            alg: commutator.clone(),
+                        };
+                        best = Some((left, insertion, inserted));
+                    }
+                }
+
+                if let Some(&mov) = solution.get(position) {
+                    before = before.mov_single(mov);
+                }
+            }
+
+            let (left, insertion, inserted) = best?;
+            unsolved = left;
+            insertions.push(insertion);
+            solution = inserted;
+        }
+
+        Some(InsertionSolution {
+            insertions,
+            solution,
+        })
+    }
+}
+
+impl Default for InsertionFinder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// `a b a' b'`.
+fn commutator(a: &[Move], b: &[Move]) -> Alg {
+    let inverse = |moves: &[Move]| {
+        moves
+            .iter()
+            .rev()
+            .map(|mov| mov.inverse())
+            .collect::<Vec<_>>()
+    };
+    cancel(
+        a.iter()
+            .chain(b)
+            .copied()
+            .chain(inverse(a))
+            .chain(inverse(b)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algs::pll;
+    use norcina_core::types::Orientation3;
+    use norcina_cube_n::mov::moves::*;
+
+    #[test]
+    fn cancels_same_and_opposite_faces() {
+        assert_eq!(cancel([R, R]).moves, [R2]);
+        assert_eq!(cancel([R, RP]).moves, []);
+        assert_eq!(cancel([R, L, R]).moves, [R2, L]);
+        assert_eq!(cancel([U, R, L, RP, D]).moves, [U, L, D]);
+        assert_eq!(cancel([R, U, UP, RP]).moves, []);
+    }
+
+    #[test]
+    fn niss_sides_meet() {
+        let scramble = [R, U, F, D2, L];
+        let mut niss = NissSolution::new();
+        niss.extend(Side::Normal, [LP, D2]);
+        niss.extend(Side::Inverse, [R, U]);
+
+        assert_eq!(niss.to_string(), "L' D2 (R  U )");
+        // Each side sees the moves done on the other one.
+        assert_eq!(niss.cube(&scramble, Side::Normal), Cube::SOLVED.mov([F]));
+        assert_eq!(niss.cube(&scramble, Side::Inverse), Cube::SOLVED.mov([FP]));
+        assert_eq!(niss.skeleton().moves, [LP, D2, UP, RP]);
+
+        // Solving the state shown on the inverse solves the scramble.
+        let mut on_inverse = niss.clone();
+        on_inverse.push(Side::Inverse, F);
+        assert!(on_inverse.cube(&scramble, Side::Inverse).is_solved());
+        assert!(
+            Cube::SOLVED
+                .mov(scramble)
+                .mov(on_inverse.skeleton())
+                .is_solved()
+        );
+
+        niss.push(Side::Normal, FP);
+        assert!(niss.cube(&scramble, Side::Normal).is_solved());
+        assert!(Cube::SOLVED.mov(scramble).mov(niss.skeleton()).is_solved());
+    }
+
+    #[test]
+    fn leftover_of_a_perm() {
+        let leftover = Leftover::new(Cube::SOLVED.mov(pll::A_A));
+        assert!(leftover.is_corner_3_cycle());
+        assert_eq!(leftover.to_string(), "3c");
+
+        let leftover = Leftover::new(Cube::SOLVED.mov(pll::T));
+        assert_eq!(leftover.to_string(), "2c 2e");
+        assert_eq!(leftover.unsolved_pieces(), 4);
+
+        assert_eq!(Leftover::new(Cube::SOLVED.mov([F])).to_string(), "4c 4e");
+
+        let mut cube = Cube::SOLVED;
+        cube.corners[0].set_orientation(Orientation3::from_u8(1));
+        cube.corners[1].set_orientation(Orientation3::from_u8(2));
+        assert_eq!(Leftover::new(cube).to_string(), "1c*1c*");
+    }
+
+    #[test]
+    fn inserts_3_cycles() {
+        let finder = InsertionFinder::new();
+        // Every corner and edge 3-cycle.
+        assert_eq!(finder.commutators().len(), 112 * 9 + 440 * 4);
+        for commutator in finder.commutators() {
+            let leftover = Leftover::new(Cube::SOLVED.mov(commutator.iter().copied()));
+            assert!(leftover.is_corner_3_cycle() || leftover.is_edge_3_cycle());
+        }
+
+        // A skeleton that leaves a corner 3-cycle and an edge 3-cycle.
+        let scramble = [R, U, F2, D, LP, B];
+        let skeleton: Vec<Move> = inverse_scramble(&scramble)
+            .iter()
+            .copied()
+            .chain(pll::A_A.iter().copied())
+            .chain(pll::U_A.iter().copied())
+            .collect();
+
+        let solution = finder.solve(&scramble, &skeleton).unwrap();
+        assert_eq!(solution.insertions.len(), 2);
+        assert!(
+            Cube::SOLVED
+                .mov(scramble)
+                .mov(solution.solution)
+                .is_solved()
+        );
+    }
+
+    #[test]
+    fn gives_up_on_twisted_corners() {
+        let twist = [RP, DP, R, D, RP, DP, R, D];
+        let untwist = [DP, RP, D, R, DP, RP, D, R];
+        let scramble: Vec<Move> = twist
+            .into_iter()
+            .chain([U])
+            .chain(untwist)
+            .chain([UP])
+            .collect();
+
+        let leftover = Leftover::new(Cube::SOLVED.mov(scramble.iter().copied()));
+        assert_eq!(leftover.to_string(), "1c*1c*");
+        assert_eq!(InsertionFinder::new().solve(&scramble, &[]), None);
+    }
+}
@@ -7,6 +7,18 @@ pub use mov::algs;
 
 pub use norcina_cube_n::mov::Move;
 
+pub mod coord;
+
 pub mod search;
 
+pub mod cfop;
+
+pub mod roux;
+
+pub mod zz;
+
+pub mod bld;
+
+pub mod fmc;
+
 pub type Alg = norcina_core::Alg<Move>;
@@ -1,59 +1,335 @@
 pub mod algs {
-    // macro_rules! declare_alg {
-    //     ($name:tt = $($alg:tt)*) => {
-    //         declare_alg!(@inner
-    //             (name=$name)
-    //             (length=0)
-    //             (acc=[])
-    //             (rest=[$($alg),*])
-    //         );
-    //     };
-
-    //     (@inner (name=$name:ident) (length=$length:expr) (acc=[$($alg:tt),*]) (rest=[ ])) => {
-    //         use $crate::mov::moves::*;
-    //         pub const $name: [Move; $length] = [$($alg),*];
-    //     };
-
-    //     (@inner
-    //         (name=$name:tt)
-    //         (length=$length:expr)
-    //         (acc=[$($acc:tt)*])
-    //         (rest=[$alg_head:tt $(, $($alg_tail:tt)+)? ])
-    //     ) => {
-    //         declare_alg!(@inner
-    //             (name=$name)
-    //             (length=$length + 1)
-    //             (acc=[$($acc)* $alg_head])
-    //             (rest=[ $($($alg_tail),+)? ])
-    //         );
-    //     };
-    // }
-
     use norcina_cube_n::alg;
 
     use crate::Move;
 
-    // declare_alg!(SIMPLE = R);
-    // declare_alg!(@inner (name=SEXY) (length=0) (acc=[]) (rest=[R, U]));
-    // declare_alg!(@inner (name=SEXY) (length=0 + 1) (acc=[R]) (rest=[U]));
-    // declare_alg!(@inner (name=SEXY) (length=0 + 1 + 1) (acc=[R, U]) (rest=[ ]));
+    /// A named algorithm that solves a case of a step.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Case {
+        pub name: &'static str,
+        pub alg: &'static [Move],
+    }
 
-    // declare_alg!(SEXY = R U);
-    // declare_alg!(SEXY = R U RP UP);
-    // pub const SEXY: [Move; 4] = alg![R U RP UP];
+    impl Case {
+        pub const fn new(name: &'static str, alg: &'static [Move]) -> Self {
+            Self { name, alg }
+        }
+    }
+
+    pub const SEXY: [Move; 4] = alg!(R U RP UP);
     pub const SLEDGEHAMMER: [Move; 4] = alg!(RP F R FP);
+    pub const SUNE: [Move; 7] = alg!(R U RP U R U2 RP);
+    pub const ANTISUNE: [Move; 7] = alg!((SUNE)P);
 
+    /// The 57 cases of OLL, by their standard number.
+    pub mod oll {
+        use super::*;
+
+        pub const OLL_1: [Move; 11] = alg!(R U2 R2 F R FP U2 RP F R FP);
+        /// `F R U R' U' F' f R U R' U' f'`
+        pub const OLL_2: [Move; 12] = alg!(F SEXY FP B U L UP LP BP);
+        /// `f R U R' U' f' U' F R U R' U' F'`
+        pub const OLL_3: [Move; 13] = alg!(B (U L UP LP) BP UP F SEXY FP);
+        /// `f R U R' U' f' U F R U R' U' F'`
+        pub const OLL_4: [Move; 13] = alg!(B (U L UP LP) BP U F SEXY FP);
+        /// `r' U2 R U R' U r`
+        pub const OLL_5: [Move; 7] = alg!(LP B2 R B RP B L);
+        /// `r U2 R' U' R U' r'`
+        pub const OLL_6: [Move; 7] = alg!(L F2 RP FP R FP LP);
+        /// `r U R' U R U2 r'`
+        pub const OLL_7: [Move; 7] = alg!(L F RP F R F2 LP);
+        /// `r' U' R U' R' U2 r`
+        pub const OLL_8: [Move; 7] = alg!(LP BP R BP RP B2 L);
+        pub const OLL_9: [Move; 11] = alg!(SEXY RP F R2 U RP UP FP);
+        pub const OLL_10: [Move; 11] = alg!(R U RP U RP F R FP R U2 RP);
+        /// `r U R' U R' F R F' R U2 r'`
+        pub const OLL_11: [Move; 11] = alg!(L F RP F RP D R DP R F2 LP);
+        /// `M' R' U' R U' R' U2 R U' M`
+        pub const OLL_12: [Move; 12] = alg!(RP L RP FP R FP RP F2 R FP R LP);
+        pub const OLL_13: [Move; 11] = alg!(F U R UP R2 FP R U R UP RP);
+        pub const OLL_14: [Move; 10] = alg!(RP F R U RP FP R F UP FP);
+        /// `r' U' r R' U' R U r' U r`
+        pub const OLL_15: [Move; 10] = alg!(LP BP L RP UP R U LP B L);
+        /// `r U r' R U R' U' r U' r'`
+        pub const OLL_16: [Move; 10] = alg!(L F LP R U RP UP L FP LP);
+        pub const OLL_17: [Move; 13] = alg!(R U RP U RP F R FP U2 SLEDGEHAMMER);
+        /// `r U R' U R U2 r2 U' R U' R' U2 r`
+        pub const OLL_18: [Move; 13] = alg!(L F RP F R F2 L2 BP R BP RP B2 L);
+        /// `M U R U R' U' M' R' F R F'`
+        pub const OLL_19: [Move; 13] = alg!(R LP B R B RP BP RP L RP F R FP);
+        /// `M U R U R' U' M2 U R U' r'`
+        pub const OLL_20: [Move; 13] = alg!(R LP B R B RP BP R2 L2 F R FP LP);
+        pub const OLL_21: [Move; 11] = alg!(R U2 RP UP R U RP UP R UP RP);
+        pub const OLL_22: [Move; 9] = alg!(R U2 R2 UP R2 UP R2 U2 R);
+        pub const OLL_23: [Move; 9] = alg!(R2 DP R U2 RP D R U2 R);
+        /// `r U R' U' r' F R F'`
+        pub const OLL_24: [Move; 8] = alg!(L F RP FP LP F R FP);
+        /// `F' r U R' U' r' F R`
+        pub const OLL_25: [Move; 8] = alg!(FP L F RP FP LP F R);
+        pub const OLL_26: [Move; 7] = alg!(ANTISUNE);
+        pub const OLL_27: [Move; 7] = alg!(SUNE);
+        /// `r U R' U' M U R U' R'`
+        pub const OLL_28: [Move; 10] = alg!(L F RP FP R LP U R UP RP);
+        pub const OLL_29: [Move; 13] = alg!(R U RP UP R UP RP FP UP F R U RP);
+        pub const OLL_30: [Move; 11] = alg!(F RP F R2 UP RP UP R U RP F2);
+        pub const OLL_31: [Move; 9] = alg!(RP UP F U R UP RP FP R);
+        pub const OLL_32: [Move; 9] = alg!(L U FP UP LP U L F LP);
+        pub const OLL_33: [Move; 8] = alg!(SEXY SLEDGEHAMMER);
+        pub const OLL_34: [Move; 11] = alg!(R U R2 UP RP F R U R UP FP);
+        pub const OLL_35: [Move; 9] = alg!(R U2 R2 F R FP R U2 RP);
+        pub const OLL_36: [Move; 12] = alg!(LP UP L UP LP U L U L FP LP F);
+        pub const OLL_37: [Move; 8] = alg!((SLEDGEHAMMER)P U R UP RP);
+        pub const OLL_38: [Move; 12] = alg!(R U RP U R UP RP UP SLEDGEHAMMER);
+        pub const OLL_39: [Move; 9] = alg!(L FP LP UP L U F UP LP);
+        pub const OLL_40: [Move; 9] = alg!(RP F R U RP UP FP U R);
+        pub const OLL_41: [Move; 13] = alg!(R U RP U R U2 RP F SEXY FP);
+        pub const OLL_42: [Move; 13] = alg!(RP UP R UP RP U2 R F SEXY FP);
+        pub const OLL_43: [Move; 6] = alg!(FP UP LP U L F);
+        pub const OLL_44: [Move; 6] = alg!(F (U R UP RP) FP);
+        pub const OLL_45: [Move; 6] = alg!(F SEXY FP);
+        pub const OLL_46: [Move; 8] = alg!(RP UP RP F R FP U R);
+        pub const OLL_47: [Move; 12] = alg!(RP UP (RP F R FP)2 U R);
+        pub const OLL_48: [Move; 10] = alg!(F (SEXY)2 FP);
+        /// `r U' r2 U r2 U r2 U' r`
+        pub const OLL_49: [Move; 9] = alg!(L FP L2 B L2 F L2 BP L);
+        /// `r' U r2 U' r2 U' r2 U r'`
+        pub const OLL_50: [Move; 9] = alg!(LP B L2 FP L2 BP L2 F LP);
+        pub const OLL_51: [Move; 10] = alg!(F (U R UP RP)2 FP);
+        pub const OLL_52: [Move; 10] = alg!(R U RP U R UP B UP BP RP);
+        /// `l' U2 L U L' U' L U L' U l`
+        pub const OLL_53: [Move; 11] = alg!(RP F2 L F LP FP L F LP F R);
+        /// `r U2 R' U' R U R' U' R U' r'`
+        pub const OLL_54: [Move; 11] = alg!(L F2 RP FP R F RP FP R FP LP);
+        pub const OLL_55: [Move; 15] = alg!(RP F R U R UP R2 FP R2 UP RP U R U RP);
+        /// `r' U' r U' R' U R U' R' U R r' U r`
+        pub const OLL_56: [Move; 14] = alg!(LP BP L UP RP U R UP RP U R LP B L);
+        /// `R U R' U' M' U R U' r'`
+        pub const OLL_57: [Move; 10] = alg!(R U RP UP RP L F R FP LP);
+
+        pub const H: [Move; 11] = OLL_21;
+        pub const PI: [Move; 9] = OLL_22;
+        pub const HEADLIGHTS: [Move; 9] = OLL_23;
+        pub const CHAMELEON: [Move; 8] = OLL_24;
+        pub const BOWTIE: [Move; 8] = OLL_25;
+        pub use super::{ANTISUNE, SUNE};
+
+        pub const ALL: [Case; 57] = [
+            Case::new("OLL 1", &OLL_1),
+            Case::new("OLL 2", &OLL_2),
+            Case::new("OLL 3", &OLL_3),
+            Case::new("OLL 4", &OLL_4),
+            Case::new("OLL 5", &OLL_5),
+            Case::new("OLL 6", &OLL_6),
+            Case::new("OLL 7", &OLL_7),
+            Case::new("OLL 8", &OLL_8),
+            Case::new("OLL 9", &OLL_9),
+            Case::new("OLL 10", &OLL_10),
+            Case::new("OLL 11", &OLL_11),
+            Case::new("OLL 12", &OLL_12),
+            Case::new("OLL 13", &OLL_13),
+            Case::new("OLL 14", &OLL_14),
+            Case::new("OLL 15", &OLL_15),
+            Case::new("OLL 16", &OLL_16),
+            Case::new("OLL 17", &OLL_17),
+            Case::new("OLL 18", &OLL_18),
+            Case::new("OLL 19", &OLL_19),
+            Case::new("OLL 20", &OLL_20),
+            Case::new("OLL 21", &OLL_21),
+            Case::new("OLL 22", &OLL_22),
+            Case::new("OLL 23", &OLL_23),
+            Case::new("OLL 24", &OLL_24),
+            Case::new("OLL 25", &OLL_25),
+            Case::new("OLL 26", &OLL_26),
+            Case::new("OLL 27", &OLL_27),
+            Case::new("OLL 28", &OLL_28),
+            Case::new("OLL 29", &OLL_29),
+            Case::new("OLL 30", &OLL_30),
+            Case::new("OLL 31", &OLL_31),
+            Case::new("OLL 32", &OLL_32),
+            Case::new("OLL 33", &OLL_33),
+            Case::new("OLL 34", &OLL_34),
+            Case::new("OLL 35", &OLL_35),
+            Case::new("OLL 36", &OLL_36),
+            Case::new("OLL 37", &OLL_37),
+            Case::new("OLL 38", &OLL_38),
+            Case::new("OLL 39", &OLL_39),
+            Case::new("OLL 40", &OLL_40),
+            Case::new("OLL 41", &OLL_41),
+            Case::new("OLL 42", &OLL_42),
+            Case::new("OLL 43", &OLL_43),
+            Case::new("OLL 44", &OLL_44),
+            Case::new("OLL 45", &OLL_45),
+            Case::new("OLL 46", &OLL_46),
+            Case::new("OLL 47", &OLL_47),
+            Case::new("OLL 48", &OLL_48),
+            Case::new("OLL 49", &OLL_49),
+            Case::new("OLL 50", &OLL_50),
+            Case::new("OLL 51", &OLL_51),
+            Case::new("OLL 52", &OLL_52),
+            Case::new("OLL 53", &OLL_53),
+            Case::new("OLL 54", &OLL_54),
+            Case::new("OLL 55", &OLL_55),
+            Case::new("OLL 56", &OLL_56),
+            Case::new("OLL 57", &OLL_57),
+        ];
+    }
+
+    /// The 21 cases of PLL.
     pub mod pll {
         use super::*;
-        pub const T: [Move; 14] = alg!(R U RP UP RP F R2 UP RP UP R U RP FP);
+
+        /// `x R' U R' D2 R U' R' D2 R2 x'`
+        pub const A_A: [Move; 9] = alg!(RP F RP B2 R FP RP B2 R2);
+        /// `x R2 D2 R U R' D2 R U' R x'`
+        pub const A_B: [Move; 9] = alg!(R2 B2 R F RP B2 R FP R);
+        /// `x' R U' R' D R U R' D' R U R' D R U' R' D' x`
+        pub const E: [Move; 16] = alg!(R BP RP F R B RP FP R B RP F R BP RP FP);
+        pub const F: [Move; 18] = alg!(RP UP FP R U RP UP RP F R2 UP RP UP R U RP U R);
+        pub const G_A: [Move; 15] = alg!(R2 U RP U RP UP R UP R2 UP D RP U R DP);
+        pub const G_B: [Move; 15] = alg!(RP UP R U DP R2 U RP U R UP R UP R2 D);
+        pub const G_C: [Move; 15] = alg!(R2 UP R UP R U RP U R2 U DP R UP RP D);
+        pub const G_D: [Move; 15] = alg!(R U RP UP D R2 UP R UP RP U RP U R2 DP);
+        /// `M2 U M2 U2 M2 U M2`
+        pub const H: [Move; 11] = alg!(R2 L2 D R2 L2 U2 R2 L2 D R2 L2);
+        pub const J_A: [Move; 10] = alg!(RP U LP U2 R UP RP U2 R L);
         pub const J: [Move; 13] = alg!(R U RP F R U RP UP RP FP R2 UP RP);
+        pub const J_B: [Move; 13] = alg!(R U RP FP R U RP UP RP F R2 UP RP);
+        pub const N_A: [Move; 21] = alg!(R U RP U R U RP FP R U RP UP RP F R2 UP RP U2 R UP RP);
+        pub const N_B: [Move; 17] = alg!(RP U R UP RP FP UP F R U RP F RP FP R UP R);
+        pub const R_A: [Move; 15] = alg!(R UP RP UP R U R D RP UP R DP RP U2 RP);
+        pub const R_B: [Move; 13] = alg!(R2 F R U R UP RP FP R U2 RP U2 R);
+        pub const T: [Move; 14] = alg!(SEXY RP F R2 UP RP UP R U RP FP);
         pub const U: [Move; 11] = U_A;
         pub const U_A: [Move; 11] = alg!(R2 UP RP UP R U R U R UP R);
-        pub const U_B: [Move; 11] = alg!(RP U RP UP RP UP RP U R U R2);
+        pub const U_B: [Move; 11] = alg!((U_A)P);
+        /// `R' U R' U' y R' F' R2 U' R' U R' F R F`
+        pub const V: [Move; 14] = alg!(RP U RP UP BP RP B2 UP BP U BP R B R);
+        pub const Y: [Move; 17] = alg!(F R UP RP UP R U RP FP SEXY SLEDGEHAMMER);
+        /// `M' U M2 U M2 U M' U2 M2`
+        pub const Z: [Move; 14] = alg!(RP L F R2 L2 B R2 L2 F RP L D2 R2 L2);
+
+        pub const ALL: [Case; 21] = [
+            Case::new("Aa", &A_A),
+            Case::new("Ab", &A_B),
+            Case::new("E", &E),
+            Case::new("F", &F),
+            Case::new("Ga", &G_A),
+            Case::new("Gb", &G_B),
+            Case::new("Gc", &G_C),
+            Case::new("Gd", &G_D),
+            Case::new("H", &H),
+            Case::new("Ja", &J_A),
+            Case::new("Jb", &J_B),
+            Case::new("Na", &N_A),
+            Case::new("Nb", &N_B),
+            Case::new("Ra", &R_A),
+            Case::new("Rb", &R_B),
+            Case::new("T", &T),
+            Case::new("Ua", &U_A),
+            Case::new("Ub", &U_B),
+            Case::new("V", &V),
+            Case::new("Y", &Y),
+            Case::new("Z", &Z),
+        ];
+    }
+
+    /// The 42 cases of CMLL, by the orientation of the corners (O, H, Pi, U, T,
+    /// S, AS and L), each with one of its shortest `<R, U, F>` algorithms.
+    ///
+    /// The cases of each set are numbered by the length of their algorithm.
+    pub mod cmll {
+        use super::*;
+
+        pub const O_ADJACENT: [Move; 12] = alg!(RP U F U FP U2 R F RP U R FP);
+        pub const O_DIAGONAL: [Move; 11] = alg!(R2 U2 F2 UP R2 U2 F2 U R2 U2 F2);
+        pub const H_1: [Move; 9] = alg!(F U2 FP U2 FP U2 FP U2 F);
+        pub const H_2: [Move; 11] = alg!(F R2 F2 U2 F R2 FP U2 F2 R2 FP);
+        pub const H_3: [Move; 11] = alg!(R FP U2 F U2 F RP U2 F U2 FP);
+        pub const H_4: [Move; 12] = alg!(R U RP FP U F RP F R F2 U F);
+        pub const PI_1: [Move; 10] = alg!(RP U F U2 F UP F2 UP FP R);
+        pub const PI_2: [Move; 10] = alg!(RP UP R UP RP U FP U F R);
+        pub const PI_3: [Move; 11] = alg!(R U RP U R2 F2 RP U2 R F2 R2);
+        pub const PI_4: [Move; 11] = alg!(RP F R U F UP R U RP UP FP);
+        pub const PI_5: [Move; 12] = alg!(RP F R U RP U F2 UP F2 UP FP R);
+        pub const PI_6: [Move; 12] = alg!(RP UP R UP RP U2 F R U2 F U2 FP);
+        pub const U_1: [Move; 6] = alg!(F R U RP UP FP);
+        pub const U_2: [Move; 9] = alg!(R2 UP R F2 RP U R F2 R);
+        pub const U_3: [Move; 10] = alg!(F U2 FP R F RP U2 R FP RP);
+        pub const U_4: [Move; 11] = alg!(RP FP R FP U2 RP F R F U2 F);
+        pub const U_5: [Move; 12] = alg!(R U RP U2 F U RP F R2 UP RP FP);
+        pub const U_6: [Move; 12] = alg!(R2 U F2 U F U R UP RP F UP R2);
+        pub const T_1: [Move; 7] = alg!(RP F R U2 F U2 FP);
+        pub const T_2: [Move; 9] = alg!(F RP UP R FP RP U FP R);
+        pub const T_3: [Move; 9] = alg!(R U RP FP UP F R UP RP);
+        pub const T_4: [Move; 10] = alg!(RP FP R FP UP F2 UP FP U2 F);
+        pub const T_5: [Move; 11] = alg!(F RP F R2 UP RP UP R U RP F2);
+        pub const T_6: [Move; 11] = alg!(RP F R U R2 UP R FP RP U R2);
+        pub const S_1: [Move; 7] = alg!(SUNE);
+        pub const S_2: [Move; 8] = alg!(F RP FP R U2 R U2 RP);
+        pub const S_3: [Move; 11] = alg!(F R2 F2 U2 F R FP U2 F2 R2 FP);
+        pub const S_4: [Move; 11] = alg!(F U R UP R2 F R F2 RP F2 R);
+        pub const S_5: [Move; 11] = alg!(FP U2 F U2 R U2 RP UP FP UP F);
+        pub const S_6: [Move; 11] = alg!(RP F UP FP U F2 R U2 RP F R);
+        pub const AS_1: [Move; 8] = alg!(R U R2 F R F2 U F);
+        pub const AS_2: [Move; 10] = alg!(R U2 RP UP FP UP F R UP RP);
+        pub const AS_3: [Move; 10] = alg!(RP F R U RP F UP FP U R);
+        pub const AS_4: [Move; 11] = alg!(RP FP R U2 RP F2 UP F U FP R);
+        pub const AS_5: [Move; 11] = alg!(RP UP FP U F UP R UP RP U2 R);
+        pub const AS_6: [Move; 13] = alg!(RP UP FP U F2 R U F2 RP FP R UP FP);
+        pub const L_1: [Move; 9] = alg!(FP U2 F2 RP FP R FP U2 F);
+        pub const L_2: [Move; 9] = alg!(RP F R U RP UP FP U R);
+        pub const L_3: [Move; 10] = alg!(F U RP F R F2 UP RP FP R);
+        pub const L_4: [Move; 10] = alg!(RP UP R FP RP F U FP R F);
+        pub const L_5: [Move; 12] = alg!(R U R2 UP RP F R FP U F R FP);
+        pub const L_6: [Move; 13] = alg!(R F RP U R FP R UP R FP RP U R2);
+
+        pub const ALL: [Case; 42] = [
+            Case::new("O Adjacent", &O_ADJACENT),
+            Case::new("O Diagonal", &O_DIAGONAL),
+            Case::new("H 1", &H_1),
+            Case::new("H 2", &H_2),
+            Case::new("H 3", &H_3),
+            Case::new("H 4", &H_4),
+            Case::new("Pi 1", &PI_1),
+            Case::new("Pi 2", &PI_2),
+            Case::new("Pi 3", &PI_3),
+            Case::new("Pi 4", &PI_4),
+            Case::new("Pi 5", &PI_5),
+            Case::new("Pi 6", &PI_6),
+            Case::new("U 1", &U_1),
+            Case::new("U 2", &U_2),
+            Case::new("U 3", &U_3),
+            Case::new("U 4", &U_4),
+            Case::new("U 5", &U_5),
+            Case::new("U 6", &U_6),
+            Case::new("T 1", &T_1),
+            Case::new("T 2", &T_2),
+            Case::new("T 3", &T_3),
+            Case::new("T 4", &T_4),
+            Case::new("T 5", &T_5),
+            Case::new("T 6", &T_6),
+            Case::new("S 1", &S_1),
+            Case::new("S 2", &S_2),
+            Case::new("S 3", &S_3),
+            Case::new("S 4", &S_4),
+            Case::new("S 5", &S_5),
+            Case::new("S 6", &S_6),
+            Case::new("AS 1", &AS_1),
+            Case::new("AS 2", &AS_2),
+            Case::new("AS 3", &AS_3),
+            Case::new("AS 4", &AS_4),
+            Case::new("AS 5", &AS_5),
+            Case::new("AS 6", &AS_6),
+            Case::new("L 1", &L_1),
+            Case::new("L 2", &L_2),
+            Case::new("L 3", &L_3),
+            Case::new("L 4", &L_4),
+            Case::new("L 5", &L_5),
+            Case::new("L 6", &L_6),
+        ];
     }
 
     pub const CHECKER: [Move; 6] = alg!(R2 L2 U2 D2 F2 B2);
 
-    // TODO: Concat or extend algs
-    // pub const J_AUF: [Move; 14] = [J, alg!(UP)].concat();
+    pub const J_AUF: [Move; 14] = alg!(pll::J UP);
 }
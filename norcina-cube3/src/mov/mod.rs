@@ -64,4 +64,16 @@ mod tests {
                 .is_solved()
         )
     }
+
+    #[test]
+    fn alg_macro_splices_inverts_and_repeats() {
+        use algs::{SEXY, pll};
+
+        assert_eq!(algs::J_AUF[..13], pll::J);
+        assert_eq!(alg!(SEXY (SEXY)P), alg!(R U RP UP U R UP RP));
+        assert_eq!(alg!((pll::T)P (R2 (U)P)2).len(), 18);
+        assert!(Cube::SOLVED.mov(alg!((R U)105)).is_solved());
+        assert!(Cube::SOLVED.mov(alg!((SEXY)6)).is_solved());
+        assert!(Cube::SOLVED.mov(pll::T).mov(alg!((pll::T)P)).is_solved());
+    }
 }
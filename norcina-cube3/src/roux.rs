@@ -0,0 +1,735 @@
+//! Solvers for each step of Roux: first block, second block, CMLL and LSE.
+//!
+//! Roux uses slice moves, which also move the centers. [`Cube`] doesn't keep
+//! track of the centers (its frame is given by the pieces), so the steps work
+//! on a [`RouxCube`], which also knows where the centers are.
+//!
+//! The blocks are 1x2x3 blocks on the side faces. The first block can be on
+//! any side and with any bottom colour (see [`ColourNeutrality`]), and the
+//! second block goes on the opposite side. CMLL and LSE are done in the usual
+//! orientation, with the blocks on L and R and D at the bottom.
+
+mod lse;
+
+use std::fmt;
+
+use norcina_core::{Puzzle, mov::InvertibleMove, search::IdaStar, types::Axis};
+use norcina_cube_n::{
+    Face,
+    mov::{
+        Amount,
+        moves::{U, U2, UP},
+    },
+    piece::{
+        corner::{Corner, CornerPosition},
+        edge::{self, Edge, EdgePosition},
+    },
+};
+
+use crate::{
+    Cube, Move,
+    algs::{self, Case},
+    cfop::Recognition,
+    search::{SearchSolution, piece_table::PieceTable},
+};
+
+pub use lse::LseTable;
+
+/// A move used in Roux: a face turn, a slice turn or a wide turn.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RouxMove {
+    Face(Move),
+    /// `M`, `E` or `S`, which turn like `L`, `D` and `F` respectively.
+    Slice(Axis, Amount),
+    /// A face along with the slice next to it, like `r`.
+    Wide(Face, Amount),
+}
+
+impl RouxMove {
+    pub const M: Self = Self::Slice(Axis::X, Amount::Single);
+    pub const M2: Self = Self::Slice(Axis::X, Amount::Double);
+    pub const MP: Self = Self::Slice(Axis::X, Amount::Reverse);
+
+    /// Every face, slice and wide turn.
+    pub fn iter() -> impl Iterator<Item = Self> {
+        let faces = Move::iter().map(Self::Face);
+        let slices = [Axis::X, Axis::Y, Axis::Z]
+            .into_iter()
+            .flat_map(|axis| Amount::iter().map(move |amount| Self::Slice(axis, amount)));
+        let wide = Face::iter()
+            .flat_map(|face| Amount::iter().map(move |amount| Self::Wide(face, amount)));
+
+        faces.chain(slices).chain(wide)
+    }
+
+    /// Every power of a face turn.
+    pub fn faces(face: Face) -> impl Iterator<Item = Self> {
+        Amount::iter().map(move |amount| Self::Face(Move::new(face, amount)))
+    }
+
+    /// Every power of a slice turn.
+    pub fn slices(axis: Axis) -> impl Iterator<Item = Self> {
+        Amount::iter().map(move |amount| Self::Slice(axis, amount))
+    }
+
+    /// Every power of a wide turn.
+    pub fn wides(face: Face) -> impl Iterator<Item = Self> {
+        Amount::iter().map(move |amount| Self::Wide(face, amount))
+    }
+
+    pub fn axis(self) -> Axis {
+        match self {
+            Self::Face(mov) => mov.axis(),
+            Self::Slice(axis, _) => axis,
+            Self::Wide(face, _) => face.axis(),
+        }
+    }
+
+    /// Orders the moves of each axis, for [`Puzzle::is_redundant`].
+    fn order(self) -> u8 {
+        match self {
+            Self::Face(mov) => mov.face().u8(),
+            Self::Slice(..) => 8,
+            Self::Wide(face, _) => 9 + face.u8(),
+        }
+    }
+}
+
+impl From<Move> for RouxMove {
+    fn from(mov: Move) -> Self {
+        Self::Face(mov)
+    }
+}
+
+impl fmt::Debug for RouxMove {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self}")
+    }
+}
+
+impl fmt::Display for RouxMove {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let amount_str = |amount| match amount {
+            Amount::Single => " ",
+            Amount::Double => "2",
+            Amount::Reverse => "'",
+        };
+
+        match *self {
+            Self::Face(mov) => write!(f, "{mov}"),
+            Self::Slice(axis, amount) => {
+                let slice = match axis {
+                    Axis::X => 'M',
+                    Axis::Y => 'E',
+                    Axis::Z => 'S',
+                };
+                write!(f, "{slice}{}", amount_str(amount))
+            }
+            Self::Wide(face, amount) => {
+                write!(
+                    f,
+                    "{}{}",
+                    face.to_string().to_lowercase(),
+                    amount_str(amount)
+                )
+            }
+        }
+    }
+}
+
+impl norcina_core::Move for RouxMove {}
+impl InvertibleMove for RouxMove {
+    fn inverse(&self) -> Self {
+        match *self {
+            Self::Face(mov) => Self::Face(mov.inverse()),
+            Self::Slice(axis, amount) => Self::Slice(axis, amount.reverse()),
+            Self::Wide(face, amount) => Self::Wide(face, amount.reverse()),
+        }
+    }
+}
+
+/// A cube along with the position of its centers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RouxCube {
+    pub cube: Cube,
+    /// The faces where the U and F centers are.
+    pub centers: [Face; 2],
+}
+
+impl RouxCube {
+    pub const SOLVED: Self = Self {
+        cube: Cube::SOLVED,
+        centers: [Face::U, Face::F],
+    };
+
+    pub fn mov(self, alg: impl IntoIterator<Item = impl Into<RouxMove>>) -> Self {
+        self.apply_alg(alg.into_iter().map(Into::into))
+    }
+
+    /// The face where the center of `face` is.
+    pub fn center(self, face: Face) -> Face {
+        let [up, front] = self.centers;
+        match face {
+            Face::U => up,
+            Face::D => up.opposite(),
+            Face::F => front,
+            Face::B => front.opposite(),
+            Face::R => up.cross(front),
+            Face::L => front.cross(up),
+        }
+    }
+
+    fn mov_slice(self, axis: Axis, amount: Amount) -> Self {
+        let around = slice_face(axis);
+        let mut cube = self.cube;
+        let mut centers = self.centers;
+        for _ in 0..amount.u8() {
+            let edges = cube.edges;
+            for position in EdgePosition::ALL {
+                if position.normal() != axis {
+                    continue;
+                }
+
+                let [a, b] = position.faces();
+                let (new_a, new_b) = (turn(a, around), turn(b, around));
+                let target = EdgePosition::from_faces([new_a, new_b]);
+
+                let piece = position.pick(&edges);
+                let sticker = edge::sticker(piece, position, a);
+                let mut moved = piece;
+                if edge::sticker(moved, target, new_a) != sticker {
+                    moved.set_oriented(!moved.is_oriented());
+                }
+                cube.edges[target.index() as usize] = moved;
+            }
+
+            centers = centers.map(|center| turn(center, around));
+        }
+
+        Self { cube, centers }
+    }
+}
+
+/// The face that turns in the same direction as the slice of `axis`.
+fn slice_face(axis: Axis) -> Face {
+    match axis {
+        Axis::X => Face::L,
+        Axis::Y => Face::D,
+        Axis::Z => Face::F,
+    }
+}
+
+/// Where a clockwise turn of `around` takes `face`.
+fn turn(face: Face, around: Face) -> Face {
+    if face.axis() == around.axis() {
+        face
+    } else {
+        face.cross(around)
+    }
+}
+
+impl Puzzle for RouxCube {
+    type Move = RouxMove;
+
+    const SOLVED: Self = Self::SOLVED;
+
+    fn apply(self, mov: RouxMove) -> Self {
+        match mov {
+            RouxMove::Face(mov) => Self {
+                cube: self.cube.mov_single(mov),
+                ..self
+            },
+            RouxMove::Slice(axis, amount) => self.mov_slice(axis, amount),
+            RouxMove::Wide(face, amount) => {
+                let slice_amount = if slice_face(face.axis()) == face {
+                    amount
+                } else {
+                    amount.reverse()
+                };
+
+                let turned = Self {
+                    cube: self.cube.mov_single(Move::new(face, amount)),
+                    ..self
+                };
+                turned.mov_slice(face.axis(), slice_amount)
+            }
+        }
+    }
+
+    /// A random state of the pieces, with the centers solved.
+    fn random_state(rng: &mut impl rand::Rng) -> Self {
+        Self {
+            cube: Cube::random_with_rng(rng),
+            ..Self::SOLVED
+        }
+    }
+
+    fn neighbors(self) -> impl Iterator<Item = (RouxMove, Self)> {
+        RouxMove::iter().map(move |mov| (mov, self.apply(mov)))
+    }
+
+    /// Every move of the same axis commutes, so they are only allowed in one
+    /// order.
+    fn is_redundant(previous: RouxMove, next: RouxMove) -> bool {
+        previous.axis() == next.axis() && next.order() <= previous.order()
+    }
+
+    /// Renders the pieces (the centers are shown as if they were solved).
+    fn render(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.cube.render(f)
+    }
+}
+
+impl From<Cube> for RouxCube {
+    fn from(cube: Cube) -> Self {
+        Self {
+            cube,
+            ..Self::SOLVED
+        }
+    }
+}
+
+/// A 1x2x3 block, on the `side` face and with the `bottom` face below it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Block {
+    pub side: Face,
+    pub bottom: Face,
+}
+
+impl Block {
+    /// The usual first block, on L with D at the bottom.
+    pub const LEFT: Self = Self {
+        side: Face::L,
+        bottom: Face::D,
+    };
+
+    /// # Panics
+    ///
+    /// If `side` and `bottom` are not perpendicular.
+    pub fn new(side: Face, bottom: Face) -> Self {
+        assert_ne!(
+            side.axis(),
+            bottom.axis(),
+            "Block needs perpendicular faces"
+        );
+        Self { side, bottom }
+    }
+
+    /// The block on the opposite side, where the second block goes.
+    pub fn opposite(self) -> Self {
+        Self {
+            side: self.side.opposite(),
+            ..self
+        }
+    }
+
+    /// The face above the blocks.
+    pub fn top(self) -> Face {
+        self.bottom.opposite()
+    }
+
+    pub fn corners(self) -> [CornerPosition; 2] {
+        let [front, back] = self.ends();
+        [
+            CornerPosition::from_faces([self.side, self.bottom, front]),
+            CornerPosition::from_faces([self.side, self.bottom, back]),
+        ]
+    }
+
+    pub fn edges(self) -> [EdgePosition; 3] {
+        let [front, back] = self.ends();
+        [
+            EdgePosition::from_faces([self.side, self.bottom]),
+            EdgePosition::from_faces([self.side, front]),
+            EdgePosition::from_faces([self.side, back]),
+        ]
+    }
+
+    /// The two faces at the ends of the block.
+    fn ends(self) -> [Face; 2] {
+        let front = self.side.cross(self.bottom);
+        [front, front.opposite()]
+    }
+
+    pub fn is_solved(self, cube: Cube) -> bool {
+        self.corners()
+            .into_iter()
+            .all(|position| position.pick(cube.corners) == Corner::solved(position.u8()))
+            && self
+                .edges()
+                .into_iter()
+                .all(|position| position.pick(&cube.edges) == Edge::solved(position.index()))
+    }
+}
+
+/// Which first blocks to consider.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColourNeutrality {
+    /// Only [`Block::LEFT`].
+    Fixed,
+    /// Blocks with D or U at the bottom, on any side.
+    Dual,
+    /// Every block.
+    Full,
+}
+
+impl ColourNeutrality {
+    pub fn blocks(self) -> Vec<Block> {
+        let bottoms = match self {
+            Self::Fixed => return vec![Block::LEFT],
+            Self::Dual => vec![Face::D, Face::U],
+            Self::Full => Face::iter().collect(),
+        };
+
+        bottoms
+            .into_iter()
+            .flat_map(|bottom| {
+                Face::iter()
+                    .filter(move |side| side.axis() != bottom.axis())
+                    .map(move |side| Block::new(side, bottom))
+            })
+            .collect()
+    }
+}
+
+/// Finds the optimal solutions of a block.
+#[derive(Debug)]
+pub struct BlockSolver {
+    blocks: Vec<Block>,
+    generators: Vec<RouxMove>,
+    corner_tables: Vec<PieceTable<Corner>>,
+    edge_tables: Vec<PieceTable<Edge>>,
+}
+
+impl BlockSolver {
+    fn new(blocks: Vec<Block>, generators: Vec<RouxMove>) -> Self {
+        let effects: Vec<Cube> = generators
+            .iter()
+            .map(|&mov| RouxCube::SOLVED.apply(mov).cube)
+            .collect();
+
+        let corners: Vec<usize> = blocks
+            .iter()
+            .flat_map(|block| block.corners())
+            .map(|position| position.u8() as usize)
+            .collect();
+        let edges: Vec<usize> = blocks
+            .iter()
+            .flat_map(|block| block.edges())
+            .map(|position| position.index() as usize)
+            .collect();
+
+        Self {
+            corner_tables: PieceTable::generate_chunks_with_effects(
+                (0..8).collect(),
+                &corners,
+                &effects,
+            ),
+            edge_tables: PieceTable::generate_chunks_with_effects(
+                (0..12).collect(),
+                &edges,
+                &effects,
+            ),
+            blocks,
+            generators,
+        }
+    }
+
+    /// The first block, with any face, slice parallel to the block, or wide
+    /// turn of the opposite side.
+    pub fn first_block(block: Block) -> Self {
+        let generators = Move::iter()
+            .map(RouxMove::Face)
+            .chain(RouxMove::slices(block.side.axis()))
+            .chain(RouxMove::wides(block.side.opposite()))
+            .collect();
+
+        Self::new(vec![block], generators)
+    }
+
+    /// The second block, opposite to `first_block` (which has to stay
+    /// solved), with the usual moves of the opposite side, the top, the slice
+    /// and the wide turn (`<R, U, M, r>` for [`Block::LEFT`]).
+    pub fn second_block(first_block: Block) -> Self {
+        let side = first_block.side.opposite();
+        let generators = RouxMove::faces(side)
+            .chain(RouxMove::faces(first_block.top()))
+            .chain(RouxMove::slices(side.axis()))
+            .chain(RouxMove::wides(side))
+            .collect();
+
+        Self::new(vec![first_block, first_block.opposite()], generators)
+    }
+
+    /// Whether every block is solved.
+    pub fn is_solved(&self, cube: RouxCube) -> bool {
+        self.blocks.iter().all(|block| block.is_solved(cube.cube))
+    }
+
+    /// A lower bound of the number of moves to solve the blocks.
+    pub fn distance_lower_bound(&self, cube: RouxCube) -> u8 {
+        let corners = self
+            .corner_tables
+            .iter()
+            .map(|table| table.distance(cube.cube));
+        let edges = self
+            .edge_tables
+            .iter()
+            .map(|table| table.distance(cube.cube));
+        corners
+            .chain(edges)
+            .try_fold(0, |max, distance| Some(max.max(distance?)))
+            .unwrap_or(u8::MAX)
+    }
+
+    /// Every optimal solution.
+    pub fn solutions(&self, cube: RouxCube) -> impl Iterator<Item = SearchSolution<RouxCube>> + '_ {
+        IdaStar::new(
+            |cube| self.distance_lower_bound(cube),
+            |cube| self.is_solved(cube),
+        )
+        .with_generators(self.generators.iter().copied())
+        .solutions(cube)
+        .map(move |alg| SearchSolution::from_alg(cube, alg))
+    }
+
+    /// One of the optimal solutions.
+    ///
+    /// # Panics
+    ///
+    /// If the blocks can't be solved with the moves of the solver (e.g. the
+    /// first block is not solved for the second block).
+    pub fn solve(&self, cube: RouxCube) -> SearchSolution<RouxCube> {
+        assert!(
+            self.distance_lower_bound(cube) != u8::MAX,
+            "Blocks can't be solved with {:?}",
+            self.generators
+        );
+
+        self.solutions(cube).next().expect("Blocks can be solved")
+    }
+}
+
+/// The optimal first block for each of the blocks of `neutrality`, shortest
+/// first.
+pub fn first_blocks(
+    cube: RouxCube,
+    neutrality: ColourNeutrality,
+) -> Vec<(Block, SearchSolution<RouxCube>)> {
+    let mut blocks: Vec<_> = neutrality
+        .blocks()
+        .into_iter()
+        .map(|block| (block, BlockSolver::first_block(block).solve(cube)))
+        .collect();
+
+    blocks.sort_by_key(|(_, solution)| solution.states.len());
+    blocks
+}
+
+/// Whether both blocks are solved, with L and R as sides and D at the bottom.
+pub fn is_f2b_solved(cube: RouxCube) -> bool {
+    Block::LEFT.is_solved(cube.cube) && Block::LEFT.opposite().is_solved(cube.cube)
+}
+
+/// Whether the corners are solved, up to an AUF.
+pub fn is_cmll_solved(cube: RouxCube) -> bool {
+    AUFS.into_iter()
+        .any(|auf| cube.cube.mov(auf).corners == Cube::SOLVED.corners)
+}
+
+/// The ways to adjust the U face: nothing, `U`, `U2` or `U'`.
+const AUFS: [Option<Move>; 4] = [None, Some(U), Some(U2), Some(UP)];
+
+/// Finds the CMLL case of `cube`, which has to have both blocks solved.
+///
+/// Returns `None` if the blocks are not solved or the corners are already
+/// solved (up to AUF).
+pub fn recognize_cmll(cube: RouxCube) -> Option<Recognition> {
+    if !is_f2b_solved(cube) || is_cmll_solved(cube) {
+        return None;
+    }
+
+    AUFS.into_iter().find_map(|pre_auf| {
+        algs::cmll::ALL.into_iter().find_map(|case: Case| {
+            let corners = cube.cube.mov(pre_auf).mov(case.alg.iter().copied());
+            let post_auf = AUFS
+                .into_iter()
+                .find(|&post_auf| corners.mov(post_auf).corners == Cube::SOLVED.corners)?;
+
+            Some(Recognition {
+                pre_auf,
+                case,
+                post_auf,
+            })
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use norcina_cube_n::{
+        mov::moves::*,
+        piece::{corner, edge},
+    };
+
+    /// Whether every sticker matches the center of its face, so the cube is
+    /// solved up to a rotation.
+    fn matches_centers(cube: RouxCube) -> bool {
+        let color = |face| {
+            Face::iter()
+                .find(|&center| cube.center(center) == face)
+                .unwrap()
+        };
+
+        let corners = cube.cube.corners().all(|(position, piece)| {
+            Face::iter()
+                .filter(|&face| position.contains_face(face))
+                .all(|face| corner::sticker(piece, position, face) == color(face))
+        });
+        let edges = cube.cube.edges().all(|(position, piece)| {
+            Face::iter()
+                .filter(|&face| position.contains_face(face))
+                .all(|face| edge::sticker(piece, position, face) == color(face))
+        });
+
+        corners && edges
+    }
+
+    #[test]
+    fn slice_and_wide_moves_rotate_the_cube() {
+        let x = RouxCube::SOLVED.mov([RouxMove::Face(R), RouxMove::MP, RouxMove::Face(LP)]);
+        assert!(matches_centers(x));
+        assert_ne!(x, RouxCube::SOLVED);
+        assert_eq!(x.center(Face::U), Face::B);
+
+        let wide =
+            RouxCube::SOLVED.mov([RouxMove::Wide(Face::R, Amount::Single), RouxMove::Face(LP)]);
+        assert_eq!(wide, x);
+
+        let y = [
+            RouxMove::Face(U),
+            RouxMove::Slice(Axis::Y, Amount::Reverse),
+            RouxMove::Face(DP),
+        ];
+        let z = [
+            RouxMove::Face(F),
+            RouxMove::Slice(Axis::Z, Amount::Single),
+            RouxMove::Face(BP),
+        ];
+        assert!(matches_centers(RouxCube::SOLVED.mov(y)));
+        assert!(matches_centers(RouxCube::SOLVED.mov(z)));
+
+        let m4 = RouxCube::SOLVED.mov([RouxMove::M; 4]);
+        assert_eq!(m4, RouxCube::SOLVED);
+    }
+
+    #[test]
+    fn first_block_is_optimal() {
+        let cube = RouxCube::SOLVED.mov([RouxMove::MP, RouxMove::Face(L), RouxMove::Face(D2)]);
+        let solver = BlockSolver::first_block(Block::LEFT);
+        let solution = solver.solve(cube);
+
+        assert!(solver.is_solved(solution.final_state()));
+        assert_eq!(solution.states.len() - 1, 2);
+    }
+
+    #[test]
+    fn first_blocks_with_colour_neutrality() {
+        let cube = RouxCube::from(Cube::SOLVED.mov([R, U2, F, LP, D, B2]));
+        let blocks = first_blocks(cube, ColourNeutrality::Dual);
+
+        assert_eq!(blocks.len(), 8);
+        assert!(blocks.is_sorted_by_key(|(_, solution)| solution.states.len()));
+        for (block, solution) in blocks {
+            assert!(block.is_solved(solution.final_state().cube));
+        }
+
+        assert_eq!(ColourNeutrality::Fixed.blocks(), [Block::LEFT]);
+        assert_eq!(ColourNeutrality::Full.blocks().len(), 24);
+    }
+
+    #[test]
+    fn second_block_keeps_first_block() {
+        let scramble = [
+            RouxMove::Face(R),
+            RouxMove::Face(U),
+            RouxMove::Wide(Face::R, Amount::Reverse),
+            RouxMove::Face(U2),
+            RouxMove::M,
+            RouxMove::Face(RP),
+        ];
+        let cube = RouxCube::SOLVED.mov(scramble);
+        let solver = BlockSolver::second_block(Block::LEFT);
+        let solution = solver.solve(cube);
+
+        assert!(is_f2b_solved(solution.final_state()));
+        assert!(solution.states.len() - 1 <= scramble.len());
+        assert!(
+            solution
+                .alg()
+                .iter()
+                .all(|mov| [Face::R, Face::U].contains(&mov_face(*mov)))
+        );
+    }
+
+    /// The face of the move, or R for slice moves (which are parallel to it).
+    fn mov_face(mov: RouxMove) -> Face {
+        match mov {
+            RouxMove::Face(mov) => mov.face(),
+            RouxMove::Slice(..) => Face::R,
+            RouxMove::Wide(face, _) => face,
+        }
+    }
+
+    #[test]
+    fn recognizes_every_cmll() {
+        for case in algs::cmll::ALL {
+            let inverse = norcina_core::Alg {
+                moves: case.alg.to_vec(),
+            }
+            .reversed();
+
+            for auf in AUFS {
+                let cube = RouxCube::from(Cube::SOLVED.mov(inverse.clone()).mov(auf));
+                let recognition = recognize_cmll(cube)
+                    .unwrap_or_else(|| panic!("{} should be recognized", case.name));
+
+                assert_eq!(recognition.case, case);
+                let solved = cube.mov(recognition.alg());
+                assert!(is_f2b_solved(solved));
+                assert_eq!(solved.cube.corners, Cube::SOLVED.corners);
+            }
+        }
+
+        assert_eq!(recognize_cmll(RouxCube::SOLVED.mov([U])), None);
+    }
+
+    #[test]
+    fn solves_lse() {
+        let table = LseTable::new();
+        assert_eq!(table.distance(RouxCube::SOLVED), Some(0));
+        assert_eq!(table.distance(RouxCube::SOLVED.mov([R])), None);
+
+        let scramble = [
+            RouxMove::M,
+            RouxMove::Face(U),
+            RouxMove::M2,
+            RouxMove::Face(UP),
+            RouxMove::MP,
+            RouxMove::Face(U2),
+            RouxMove::M,
+            RouxMove::Face(U),
+        ];
+        let cube = RouxCube::SOLVED.mov(scramble);
+        let distance = table.distance(cube).unwrap();
+        let solutions: Vec<_> = table.solutions(cube).unwrap().collect();
+
+        assert!(distance as usize <= scramble.len());
+        assert!(!solutions.is_empty());
+        for solution in solutions {
+            assert!(solution.final_state().is_solved());
+            assert_eq!(solution.states.len() - 1, distance as usize);
+        }
+    }
+}
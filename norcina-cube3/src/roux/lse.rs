@@ -0,0 +1,220 @@
+//! The last six edges, solved with `<M, U>`.
+//!
+//! There are few enough states that the distance of every one of them fits in
+//! a table, so the solutions come straight out of it.
+
+use std::collections::VecDeque;
+
+use norcina_core::{Puzzle as _, search::IdaStar, types::Axis};
+use norcina_cube_n::{Face, mov::moves::U, piece::edge::EdgePosition};
+
+use super::{RouxCube, RouxMove, is_cmll_solved, is_f2b_solved};
+use crate::{Cube, search::SearchSolution};
+
+/// Number of arrangements of the six edges.
+const PERMUTATIONS: usize = 720;
+const ORIENTATIONS: usize = 1 << 6;
+/// Positions of the M centers times positions of the U layer.
+const TURNS: usize = 4 * 4;
+const SIZE: usize = PERMUTATIONS * ORIENTATIONS * TURNS;
+
+/// The state of LSE: where each of the six edges is (and whether it's
+/// flipped), how much the M slice is turned and how much the U layer is
+/// turned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct LseState {
+    edges: [usize; 6],
+    flips: [bool; 6],
+    center: usize,
+    auf: usize,
+}
+
+impl LseState {
+    const SOLVED: Self = Self {
+        edges: [0, 1, 2, 3, 4, 5],
+        flips: [false; 6],
+        center: 0,
+        auf: 0,
+    };
+
+    /// Reads the state of `cube`, or `None` if it's not in LSE.
+    fn from_cube(cube: RouxCube) -> Option<Self> {
+        if !is_f2b_solved(cube) || !is_cmll_solved(cube) || cube.center(Face::L) != Face::L {
+            return None;
+        }
+
+        let slots = slots();
+        let mut state = Self::SOLVED;
+        for (slot, position) in slots.iter().enumerate() {
+            let edge = position.pick(&cube.cube.edges);
+            state.edges[slot] = slots
+                .iter()
+                .position(|&home| home == edge.position())
+                .expect("Blocks are solved, so the edge is one of the six");
+            state.flips[slot] = !edge.is_oriented();
+        }
+
+        state.center = [Face::U, Face::F, Face::D, Face::B]
+            .into_iter()
+            .position(|face| cube.center(Face::U) == face)
+            .expect("M slice only moves the U center to the M faces");
+
+        state.auf = (0..4)
+            .position(|turns| {
+                Cube::SOLVED.mov(std::iter::repeat_n(U, turns)).corners == cube.cube.corners
+            })
+            .expect("CMLL is solved");
+
+        Some(state)
+    }
+
+    fn index(&self) -> usize {
+        let mut permutation = 0;
+        for i in 0..6 {
+            let smaller_after = self.edges[i + 1..]
+                .iter()
+                .filter(|&&edge| edge < self.edges[i])
+                .count();
+            permutation = permutation * (6 - i) + smaller_after;
+        }
+
+        let orientation = self
+            .flips
+            .iter()
+            .fold(0, |bits, &flip| (bits << 1) | flip as usize);
+
+        ((permutation * ORIENTATIONS + orientation) * 4 + self.center) * 4 + self.auf
+    }
+
+    fn from_index(mut index: usize) -> Self {
+        let mut state = Self::SOLVED;
+        state.auf = index % 4;
+        index /= 4;
+        state.center = index % 4;
+        index /= 4;
+
+        for flip in state.flips.iter_mut().rev() {
+            *flip = index % 2 == 1;
+            index /= 2;
+        }
+
+        let mut digits = [0; 6];
+        for (i, digit) in digits.iter_mut().enumerate().rev() {
+            *digit = index % (6 - i);
+            index /= 6 - i;
+        }
+
+        let mut unused: Vec<usize> = (0..6).collect();
+        for (edge, digit) in state.edges.iter_mut().zip(digits) {
+            *edge = unused.remove(digit);
+        }
+
+        state
+    }
+
+    /// Applies a move, given by the state it takes the solved state to.
+    fn then(self, effect: &Self) -> Self {
+        let mut state = Self {
+            center: (self.center + effect.center) % 4,
+            auf: (self.auf + effect.auf) % 4,
+            ..self
+        };
+
+        // `effect` has the edge that comes from each slot.
+        for (to, &from) in effect.edges.iter().enumerate() {
+            state.edges[to] = self.edges[from];
+            state.flips[to] = self.flips[from] ^ effect.flips[to];
+        }
+
+        state
+    }
+}
+
+/// The positions of the six edges: the U layer and the other two of the M
+/// slice.
+fn slots() -> Vec<EdgePosition> {
+    EdgePosition::ALL
+        .into_iter()
+        .filter(|position| position.contains_face(Face::U) || position.normal() == Axis::X)
+        .collect()
+}
+
+/// The distance of every LSE state to solved, using `<M, U>`.
+#[derive(Debug)]
+pub struct LseTable {
+    distances: Vec<u8>,
+}
+
+impl LseTable {
+    /// Fills in the table with a breadth first search from the solved state.
+    pub fn new() -> Self {
+        let effects: Vec<LseState> = Self::generators()
+            .map(|mov| {
+                LseState::from_cube(RouxCube::SOLVED.apply(mov))
+                    .expect("<M, U> keeps the cube in LSE")
+            })
+            .collect();
+
+        let mut distances = vec![u8::MAX; SIZE];
+        let solved = LseState::SOLVED.index();
+        distances[solved] = 0;
+        let mut queue = VecDeque::from([solved]);
+
+        while let Some(index) = queue.pop_front() {
+            let state = LseState::from_index(index);
+            for effect in &effects {
+                let next = state.then(effect).index();
+                if distances[next] == u8::MAX {
+                    distances[next] = distances[index] + 1;
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        Self { distances }
+    }
+
+    /// Every power of `M` and `U`.
+    pub fn generators() -> impl Iterator<Item = RouxMove> {
+        RouxMove::slices(Axis::X).chain(RouxMove::faces(Face::U))
+    }
+
+    /// The number of moves of the optimal solution, or `None` if the cube is
+    /// not in LSE (that is, with the blocks and CMLL solved) or it can't be
+    /// solved with `<M, U>`.
+    pub fn distance(&self, cube: RouxCube) -> Option<u8> {
+        let distance = self.distances[LseState::from_cube(cube)?.index()];
+        (distance != u8::MAX).then_some(distance)
+    }
+
+    /// Every optimal solution, or `None` if the cube can't be solved with
+    /// `<M, U>`.
+    pub fn solutions(
+        &self,
+        cube: RouxCube,
+    ) -> Option<impl Iterator<Item = SearchSolution<RouxCube>> + '_> {
+        self.distance(cube)?;
+
+        let solutions = IdaStar::new(
+            |cube| self.distance(cube).unwrap_or(u8::MAX),
+            RouxCube::is_solved,
+        )
+        .with_generators(Self::generators())
+        .solutions(cube)
+        .map(move |alg| SearchSolution::from_alg(cube, alg));
+
+        Some(solutions)
+    }
+
+    /// One of the optimal solutions, or `None` if the cube can't be solved
+    /// with `<M, U>`.
+    pub fn solve(&self, cube: RouxCube) -> Option<SearchSolution<RouxCube>> {
+        self.solutions(cube)?.next()
+    }
+}
+
+impl Default for LseTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -5,16 +5,15 @@
 //! - Prunte table in more detail: <https://cube20.org/src/phase1prune.pdf>
 //! - Prune table reference implementation: <https://qiita.com/7y2n/items/55abb991a45ade2afa28>
 
-use super::SearchSolution;
+use std::{borrow::Borrow, collections::HashSet, rc::Rc};
+
+use super::{SearchSolution, SolutionFilter, canonicalize};
 use crate::{Cube, Move, search::search_idastar};
-use norcina_core::types::Axis;
+use norcina_core::{Alg, search::IdaStar, types::Axis};
 use norcina_cube_n::piece::edge::EdgePosition;
 
 /// Moves that stay in G1.
-pub const G1_MOVES: [Move; 10] = {
-    use norcina_cube_n::mov::moves::*;
-    [U, U2, UP, D, D2, DP, R2, L2, F2, B2]
-};
+pub use crate::coord::G1_MOVES;
 
 pub fn solve(cube: Cube) -> SearchSolution {
     let prune_table = PruneTable::load_or_generate();
@@ -22,12 +21,97 @@ pub fn solve(cube: Cube) -> SearchSolution {
 }
 
 pub fn solve_with_table(cube: Cube, prune_table: &PruneTable) -> SearchSolution {
-    let phase1_sol = solve_to_g1(cube, &prune_table);
+    let phase1_sol = solve_to_g1(cube, prune_table);
     debug_assert!(is_in_g1(phase1_sol.final_state()));
-    let phase2_sol = solve_from_g1(phase1_sol.final_state(), &prune_table);
+    let phase2_sol = solve_from_g1(phase1_sol.final_state(), prune_table);
     phase1_sol.concat(phase2_sol)
 }
 
+/// The longest phase 1 solutions that [`solutions`] goes through.
+const MAX_PHASE1_LEN: u8 = 12;
+
+/// Every state in G1 can be solved in this many moves.
+const MAX_PHASE2_LEN: u8 = 18;
+
+/// Phase 1 solutions never end with a move of G1 (otherwise the state before
+/// that move would already be in G1), so joining both phases cancels at most
+/// the last two moves of phase 1, like in `R L` followed by `R2 L2`.
+const MAX_CANCELLATION: u8 = 2;
+
+/// Finds more and more solutions, each one a phase 1 solution followed by the
+/// shortest phase 2 after it, in order of length (after cancelling the moves
+/// where the phases meet). So `.take(k)` gives the `k` shortest ones, although
+/// the shortest solutions of the cube might not be made of two such phases.
+///
+/// Solutions that are equal as move sequences (see [`canonicalize`]) are only
+/// returned once.
+pub fn solutions(cube: Cube) -> impl Iterator<Item = SearchSolution> {
+    solutions_filtered(cube, SolutionFilter::default())
+}
+
+/// Like [`solutions`], but only returns the solutions that `filter` accepts.
+pub fn solutions_filtered(
+    cube: Cube,
+    filter: SolutionFilter,
+) -> impl Iterator<Item = SearchSolution> {
+    solutions_with_table(cube, Rc::new(PruneTable::load_or_generate()), filter)
+}
+
+/// Like [`solutions_filtered`], with an already loaded prune table.
+///
+/// For each length, this goes through every phase 1 solution that could lead
+/// to a solution of that length, so it takes longer and longer to find the
+/// next one.
+pub fn solutions_with_table(
+    cube: Cube,
+    prune_table: impl Borrow<PruneTable> + Clone,
+    filter: SolutionFilter,
+) -> impl Iterator<Item = SearchSolution> {
+    let phase1_generators: Vec<_> = Move::iter()
+        .filter(|&mov| filter.allows_move(mov))
+        .collect();
+    let phase2_generators: Vec<_> = G1_MOVES
+        .into_iter()
+        .filter(|&mov| filter.allows_move(mov))
+        .collect();
+
+    (0..=MAX_PHASE1_LEN + MAX_PHASE2_LEN).flat_map(move |len| {
+        let phase1_table = prune_table.clone();
+        let phase2_table = prune_table.clone();
+        let phase2_generators = phase2_generators.clone();
+        let filter = filter.clone();
+        let mut seen = HashSet::new();
+
+        // Before cancelling, a solution of `len` moves can be a bit longer.
+        let max_len = len + MAX_CANCELLATION;
+
+        IdaStar::new(
+            move |cube| phase1_table.borrow().phase1_distance_heuristic(cube),
+            is_in_g1,
+        )
+        .with_generators(phase1_generators.clone())
+        .with_max_depth(max_len.min(MAX_PHASE1_LEN))
+        .all_solutions(cube)
+        .filter_map(move |phase1| {
+            let phase2 = IdaStar::new(
+                |cube| phase2_table.borrow().phase2_distance_heuristic(cube),
+                Cube::is_solved,
+            )
+            .with_generators(phase2_generators.iter().copied())
+            .with_max_depth(max_len - phase1.len() as u8)
+            .solve(cube.mov(phase1.iter().copied()))?;
+
+            let alg = canonicalize(Alg {
+                moves: phase1.moves.into_iter().chain(phase2).collect(),
+            });
+
+            // Shorter ones were already returned with their own length.
+            (alg.len() == len as usize && filter.accepts(&alg) && seen.insert(alg.clone()))
+                .then(|| SearchSolution::from_alg(cube, alg))
+        })
+    })
+}
+
 /// Takes a scrambled cube and finds the closest algorithm to a state in the
 // "G1" subset. Any state in this subset can be solved using just U, D, R2, L2,
 // F2 and B2 moves.
@@ -42,11 +126,15 @@ pub fn solve_to_g1(cube: Cube, prune_table: &PruneTable) -> SearchSolution {
 /// Takes a cube in a G1 state and solves it.
 pub fn solve_from_g1(cube: Cube, prune_table: &PruneTable) -> SearchSolution {
     debug_assert!(is_in_g1(cube));
-    search_idastar(
-        cube,
+    let alg = IdaStar::new(
         |cube| prune_table.phase2_distance_heuristic(cube),
         Cube::is_solved,
     )
+    .with_generators(G1_MOVES)
+    .solve(cube)
+    .expect("Every state in G1 can be solved with G1 moves");
+
+    SearchSolution::from_alg(cube, alg)
 }
 
 /// A cube is in G1 if:
@@ -73,12 +161,64 @@ mod prune_table;
 mod tests {
     use super::*;
     use quickcheck::quickcheck;
+    use std::sync::LazyLock;
+
+    static PRUNE_TABLE: LazyLock<PruneTable> = LazyLock::new(PruneTable::generate);
 
     #[test]
     fn solved_cube_is_in_g1() {
         assert!(is_in_g1(Cube::SOLVED))
     }
 
+    #[test]
+    fn finds_several_distinct_solutions() {
+        use norcina_cube_n::mov::moves::*;
+
+        let cube = Cube::SOLVED.mov([R, U, F]);
+        let solutions: Vec<_> =
+            solutions_with_table(cube, &*PRUNE_TABLE, SolutionFilter::default())
+                .take(3)
+                .map(SearchSolution::alg)
+                .collect();
+
+        assert_eq!(solutions[0].moves, [FP, UP, RP]);
+        assert_eq!(solutions.len(), 3);
+        for (i, solution) in solutions.iter().enumerate() {
+            assert!(cube.mov(solution.iter().copied()).is_solved());
+            assert!(!solutions[..i].contains(solution));
+        }
+    }
+
+    #[test]
+    fn solutions_are_shortest_first() {
+        use norcina_cube_n::mov::moves::*;
+
+        let cube = Cube::SOLVED.mov([R, U, F]);
+        let lengths: Vec<_> = solutions_with_table(cube, &*PRUNE_TABLE, SolutionFilter::default())
+            .take(5)
+            .map(|solution| solution.alg().len())
+            .collect();
+
+        assert_eq!(lengths.len(), 5);
+        assert_eq!(lengths[0], 3);
+        assert!(lengths.is_sorted(), "{lengths:?}");
+    }
+
+    #[test]
+    fn solutions_respect_final_move_filter() {
+        use norcina_cube_n::mov::moves::*;
+
+        let cube = Cube::SOLVED.mov([R, U]);
+        let filter = SolutionFilter::default().with_final_moves([U2, UP, U]);
+        let solution = solutions_with_table(cube, &*PRUNE_TABLE, filter)
+            .next()
+            .unwrap()
+            .alg();
+
+        assert!(cube.mov(solution.iter().copied()).is_solved());
+        assert!([U2, UP, U].contains(solution.last().unwrap()));
+    }
+
     quickcheck! {
         fn moves_in_g1_stay_in_g1(moves: Vec<u8>) -> bool {
             let state = Cube::SOLVED.mov(moves.into_iter().map(|mov_idx| G1_MOVES[(mov_idx % 10) as usize]));
@@ -1,5 +1,5 @@
 use color_eyre::eyre::{self, WrapErr as _};
-use norcina_core::math::{comb, fac};
+use norcina_core::math::{choose, fac, permutation_rank, permutation_unrank};
 use std::{fs, io, path::PathBuf};
 
 use crate::{
@@ -96,34 +96,13 @@ impl TableHeuristic {
     }
 }
 
-const CORNER_PERMUTATIONS: u32 = fac(8);
+const CORNER_PERMUTATIONS: u32 = fac(8) as u32;
 const CORNER_ORIENTATIONS: u32 = 3u32.pow(7);
 const CORNER_STATES: u32 = CORNER_ORIENTATIONS * CORNER_PERMUTATIONS;
 
 /// Returns a unique number between 0 and 88_179_839 for each possible corner set.
 fn index_corners(corners: [Corner; 8]) -> u32 {
-    let permutation_index: u32 = {
-        let mut used_slots = [false; 8];
-        let mut modulo = 1;
-        let mut out = 0;
-        for (i, &corner) in corners.iter().enumerate() {
-            let index = corner.position().u8();
-            let chosen_before = used_slots
-                .iter()
-                .take(index as usize)
-                .filter(|&&u| u)
-                .count() as u8;
-
-            let choice = index - chosen_before;
-            out += choice as u32 * modulo as u32;
-            let number_of_choices = 8 - i;
-            modulo *= number_of_choices;
-            debug_assert!(used_slots[index as usize] == false);
-            used_slots[index as usize] = true;
-        }
-
-        out
-    };
+    let permutation_index = permutation_rank(&corners.map(|corner| corner.position().u8())) as u32;
 
     debug_assert!(permutation_index < CORNER_PERMUTATIONS);
 
@@ -179,8 +158,9 @@ fn corners_from_index(index: u32) -> [Corner; 8] {
 
     let mut out = Corner::SOLVED;
     let mut orientation_sum = 0u8;
-    for (i, permutation_index) in
-        indices_from_permutation_index::<8, 8>(permutation_index).enumerate()
+    for (i, permutation_index) in permutation_unrank::<8>(permutation_index as usize)
+        .into_iter()
+        .enumerate()
     {
         let orientation = if i < 7 {
             Axis::from_u8_mod3(((orientation_index / 3u32.pow(i as u32)) % 3) as u8)
@@ -196,7 +176,7 @@ fn corners_from_index(index: u32) -> [Corner; 8] {
     out
 }
 
-const HALF_EDGE_PERMUTATIONS: u32 = comb(12, 6);
+const HALF_EDGE_PERMUTATIONS: u32 = choose(12, 6) as u32;
 const HALF_EDGE_ORIENTATIONS: u32 = 2u32.pow(6);
 const HALF_EDGE_STATES: u32 = HALF_EDGE_PERMUTATIONS * HALF_EDGE_ORIENTATIONS;
 
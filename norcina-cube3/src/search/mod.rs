@@ -8,37 +8,46 @@ pub mod kociemba;
 #[cfg(feature = "kociemba")]
 pub use kociemba::solve as solve_kociemba;
 
-use crate::{Alg, Cube, Move};
+pub(crate) mod piece_table;
+pub(crate) mod stabilizer_chain;
+pub mod subgroup;
+pub use subgroup::Subgroup;
 
+use crate::{Cube, Move};
+use norcina_core::{Alg, Puzzle, search::IdaStar};
+use norcina_cube_n::mov::Amount;
+
+/// A path of states, each one move away from the previous one.
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct SearchSolution {
-    pub states: Vec<Cube>,
+pub struct SearchSolution<P = Cube> {
+    pub states: Vec<P>,
 }
 
-impl SearchSolution {
-    /// Finds the connecting move between two states.
-    fn connect(start: Cube, end: Cube) -> Option<Move> {
-        Move::iter().find(|&mov| start.mov_single(mov) == end)
+impl<P: Puzzle> SearchSolution<P> {
+    /// The states that `alg` goes through, starting at `initial_state`.
+    pub fn from_alg(initial_state: P, alg: Alg<P::Move>) -> Self {
+        let mut states = Vec::with_capacity(alg.len() + 1);
+        states.push(initial_state);
+        for mov in alg {
+            states.push(states.last().unwrap().apply(mov));
+        }
+
+        Self { states }
     }
 
-    pub fn moves(self) -> Vec<Move> {
-        self.states
-            .windows(2)
-            .map(|window| Self::connect(window[0], window[1]).unwrap())
-            .collect()
+    pub fn moves(self) -> Vec<P::Move> {
+        self.alg().moves
     }
 
-    pub fn alg(self) -> Alg {
-        Alg {
-            moves: self.moves(),
-        }
+    pub fn alg(self) -> Alg<P::Move> {
+        P::connect(&self.states)
     }
 
-    pub fn final_state(&self) -> Cube {
+    pub fn final_state(&self) -> P {
         *self.states.last().unwrap()
     }
 
-    fn concat(mut self, other: SearchSolution) -> SearchSolution {
+    fn concat(mut self, other: SearchSolution<P>) -> SearchSolution<P> {
         assert_eq!(self.final_state(), other.states[0]);
 
         // Skip the first state since the last from `self` is the first from `other`.
@@ -47,10 +56,13 @@ impl SearchSolution {
     }
 }
 
-pub fn search_bfs(initial_state: Cube, mut goal: impl FnMut(Cube) -> bool) -> SearchSolution {
+pub fn search_bfs<P: Puzzle>(
+    initial_state: P,
+    mut goal: impl FnMut(P) -> bool,
+) -> SearchSolution<P> {
     let states = pathfinding::directed::bfs::bfs(
         &initial_state,
-        |cube| cube.neighbors().map(|(_, state)| state),
+        |state| state.neighbors().map(|(_, state)| state),
         |state| goal(*state),
     )
     .expect("Search space won't be exhausted");
@@ -58,24 +70,123 @@ pub fn search_bfs(initial_state: Cube, mut goal: impl FnMut(Cube) -> bool) -> Se
     SearchSolution { states }
 }
 
-pub fn solve_bfs(state: Cube) -> SearchSolution {
-    search_bfs(state, Cube::is_solved)
+pub fn solve_bfs<P: Puzzle>(state: P) -> SearchSolution<P> {
+    search_bfs(state, P::is_solved)
 }
 
-pub fn search_idastar(
-    initial_state: Cube,
-    mut heuristic: impl FnMut(Cube) -> u8,
-    mut goal: impl FnMut(Cube) -> bool,
-) -> SearchSolution {
-    let (states, _cost) = pathfinding::directed::idastar::idastar(
-        &initial_state,
-        |cube| cube.neighbors().map(|(_mov, state)| (state, 1)),
-        |cube| heuristic(*cube),
-        |cube| goal(*cube),
-    )
-    .expect("Search space won't be exhausted.");
+pub fn search_idastar<P: Puzzle>(
+    initial_state: P,
+    heuristic: impl FnMut(P) -> u8,
+    goal: impl FnMut(P) -> bool,
+) -> SearchSolution<P> {
+    let alg = IdaStar::new(heuristic, goal)
+        .solve(initial_state)
+        .expect("Search space won't be exhausted.");
 
-    SearchSolution { states }
+    SearchSolution::from_alg(initial_state, alg)
+}
+
+/// Restrictions on the solutions found by [`solve_all_filtered`] (and
+/// [`kociemba::solutions_filtered`]).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SolutionFilter {
+    /// The only moves solutions can use, or every move if `None`.
+    pub generators: Option<Vec<Move>>,
+    /// The moves solutions can end with, or any move if `None`. The empty
+    /// solution (of an already solved cube) is always accepted.
+    pub final_moves: Option<Vec<Move>>,
+}
+
+impl SolutionFilter {
+    pub fn with_generators(mut self, generators: impl IntoIterator<Item = Move>) -> Self {
+        self.generators = Some(generators.into_iter().collect());
+        self
+    }
+
+    pub fn with_final_moves(mut self, final_moves: impl IntoIterator<Item = Move>) -> Self {
+        self.final_moves = Some(final_moves.into_iter().collect());
+        self
+    }
+
+    pub fn allows_move(&self, mov: Move) -> bool {
+        self.generators
+            .as_ref()
+            .is_none_or(|generators| generators.contains(&mov))
+    }
+
+    pub fn accepts(&self, alg: &Alg<Move>) -> bool {
+        let final_move_is_allowed = || match (&self.final_moves, alg.last()) {
+            (Some(final_moves), Some(last)) => final_moves.contains(last),
+            _ => true,
+        };
+
+        alg.iter().all(|&mov| self.allows_move(mov)) && final_move_is_allowed()
+    }
+}
+
+/// Every solution of `cube` with at most `max_len` moves, shortest first.
+///
+/// Solutions that only differ in the order of moves on opposite faces (like
+/// `R L` and `L R`) are only returned once, as are moves on the same face
+/// that could be merged (like `R R`).
+pub fn solve_all(cube: Cube, max_len: u8) -> impl Iterator<Item = SearchSolution> {
+    solve_all_filtered(cube, max_len, SolutionFilter::default())
+}
+
+/// Like [`solve_all`], but only returns the solutions that `filter` accepts.
+pub fn solve_all_filtered(
+    cube: Cube,
+    max_len: u8,
+    filter: SolutionFilter,
+) -> impl Iterator<Item = SearchSolution> {
+    let mut search = IdaStar::new(manhattan_lower_bound, Cube::is_solved).with_max_depth(max_len);
+    if let Some(generators) = &filter.generators {
+        search = search.with_generators(generators.iter().copied());
+    }
+
+    search
+        .all_solutions(cube)
+        .filter(move |alg| filter.accepts(alg))
+        .map(move |alg| SearchSolution::from_alg(cube, alg))
+}
+
+/// Merges consecutive moves on the same face and sorts the moves on opposite
+/// faces, so that algs that are equal as move sequences on a cube (like `R L
+/// R` and `L R2`) have the same canonical form.
+pub fn canonicalize(alg: Alg<Move>) -> Alg<Move> {
+    let mut moves: Vec<Move> = Vec::with_capacity(alg.len());
+    for mov in alg {
+        // Moves since the last one on a different axis, which all commute.
+        let run_start = moves
+            .iter()
+            .rposition(|previous| previous.axis() != mov.axis())
+            .map_or(0, |i| i + 1);
+
+        let same_face = moves[run_start..]
+            .iter()
+            .position(|previous| previous.face() == mov.face());
+        match same_face {
+            Some(i) => {
+                let previous = moves.remove(run_start + i);
+                let amount = (previous.amount().u8() + mov.amount().u8()) % 4;
+                if amount != 0 {
+                    moves.insert(
+                        run_start + i,
+                        Move::new(mov.face(), Amount::from_u8(amount)),
+                    );
+                }
+            }
+            None => {
+                let i = moves[run_start..]
+                    .iter()
+                    .position(|previous| previous.face().u8() > mov.face().u8())
+                    .map_or(moves.len(), |i| run_start + i);
+                moves.insert(i, mov);
+            }
+        }
+    }
+
+    Alg { moves }
 }
 
 pub fn solve_manhattan(state: Cube) -> SearchSolution {
@@ -95,3 +206,92 @@ pub fn manhattan_distance(state: Cube) -> u8 {
 
     c + e
 }
+
+/// A lower bound of the number of moves needed to solve `state`.
+///
+/// Unlike [`manhattan_distance`], this never overestimates, since each move
+/// gets at most four corners and four edges one move closer to their place.
+pub fn manhattan_lower_bound(state: Cube) -> u8 {
+    let c: u8 = state
+        .corners()
+        .map(|(position, state)| position.turn_distance(state.position()))
+        .sum();
+    let e: u8 = state
+        .edges()
+        .map(|(position, state)| position.turn_distance(state.position()))
+        .sum();
+
+    c.div_ceil(4).max(e.div_ceil(4))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use norcina_cube_n::mov::moves::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn bfs_finds_optimal_solution() {
+        let scramble = [R, U2, FP];
+        let solution = solve_bfs(Cube::SOLVED.mov(scramble));
+        assert!(solution.final_state().is_solved());
+        assert_eq!(solution.alg().moves, [F, U2, RP]);
+    }
+
+    #[test]
+    fn idastar_solves_with_manhattan_distance() {
+        let solution = solve_manhattan(Cube::SOLVED.mov([R, U2]));
+        assert!(solution.final_state().is_solved());
+        assert_eq!(solution.alg().moves, [U2, RP]);
+    }
+
+    #[test]
+    fn solve_all_skips_equivalent_solutions() {
+        let cube = Cube::SOLVED.mov([R, L]);
+        let solutions: Vec<_> = solve_all(cube, 4).map(SearchSolution::alg).collect();
+        assert_eq!(
+            solutions,
+            [Alg {
+                moves: vec![RP, LP]
+            }]
+        );
+    }
+
+    #[test]
+    fn solve_all_respects_filter() {
+        let cube = Cube::SOLVED.mov([R2, U2, R2, U2, R2, U2]);
+        let filter = SolutionFilter::default().with_generators([R2, U2]);
+        let solutions: HashSet<_> = solve_all_filtered(cube, 6, filter.clone())
+            .map(SearchSolution::alg)
+            .collect();
+        assert_eq!(
+            solutions,
+            HashSet::from([
+                Alg {
+                    moves: vec![R2, U2, R2, U2, R2, U2]
+                },
+                Alg {
+                    moves: vec![U2, R2, U2, R2, U2, R2]
+                },
+            ])
+        );
+
+        let filter = filter.with_final_moves([R2]);
+        let solutions: Vec<_> = solve_all_filtered(cube, 6, filter)
+            .map(SearchSolution::alg)
+            .collect();
+        assert_eq!(solutions.len(), 1);
+        assert_eq!(solutions[0].moves, [U2, R2, U2, R2, U2, R2]);
+    }
+
+    #[test]
+    fn canonicalize_merges_and_sorts_commuting_moves() {
+        let alg = |moves: &[Move]| Alg {
+            moves: moves.to_vec(),
+        };
+
+        assert_eq!(canonicalize(alg(&[L, R, L])), alg(&[R, L2]));
+        assert_eq!(canonicalize(alg(&[R, U, UP, RP])), alg(&[]));
+        assert_eq!(canonicalize(alg(&[D, U, F])), alg(&[U, D, F]));
+    }
+}
@@ -0,0 +1,286 @@
+//! Pruning tables for the positions and orientations of some of the pieces.
+
+use std::{collections::VecDeque, fmt, marker::PhantomData};
+
+use norcina_cube_n::{
+    Face,
+    piece::{
+        corner::{Corner, CornerPosition},
+        edge::{Edge, EdgePosition},
+    },
+};
+
+use crate::{Cube, Move};
+
+/// The biggest table (in number of entries) that is generated. Bigger chunks
+/// of pieces are split into smaller ones.
+const MAX_TABLE_SIZE: usize = 1 << 20;
+
+/// A corner or an edge.
+pub(crate) trait Piece: Copy {
+    const COUNT: usize;
+    const ORIENTATIONS: usize;
+
+    /// The position where the piece is solved.
+    fn home(self) -> usize;
+    fn orientation(self) -> usize;
+    fn moved_by(position: usize, face: Face) -> bool;
+    fn pieces(cube: &Cube) -> &[Self];
+}
+
+impl Piece for Corner {
+    const COUNT: usize = 8;
+    const ORIENTATIONS: usize = 3;
+
+    fn home(self) -> usize {
+        self.position().u8() as usize
+    }
+
+    fn orientation(self) -> usize {
+        self.orientation().u8() as usize
+    }
+
+    fn moved_by(position: usize, face: Face) -> bool {
+        CornerPosition::from_index(position as u8).contains_face(face)
+    }
+
+    fn pieces(cube: &Cube) -> &[Self] {
+        &cube.corners
+    }
+}
+
+impl Piece for Edge {
+    const COUNT: usize = 12;
+    const ORIENTATIONS: usize = 2;
+
+    fn home(self) -> usize {
+        self.position().index() as usize
+    }
+
+    fn orientation(self) -> usize {
+        !self.is_oriented() as usize
+    }
+
+    fn moved_by(position: usize, face: Face) -> bool {
+        EdgePosition::from_index(position as u8).contains_face(face)
+    }
+
+    fn pieces(cube: &Cube) -> &[Self] {
+        &cube.edges
+    }
+}
+
+/// The distance to solved of every arrangement of some pieces (`tracked`)
+/// among the positions that the moves can reach (`positions`).
+///
+/// An arrangement is indexed by the partial permutation of the slots (indices
+/// of `positions`) of the tracked pieces, followed by their orientations.
+pub(crate) struct PieceTable<T> {
+    positions: Vec<usize>,
+    tracked: Vec<usize>,
+    distances: Vec<u8>,
+    _piece: PhantomData<T>,
+}
+
+impl<T> fmt::Debug for PieceTable<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PieceTable")
+            .field("positions", &self.positions)
+            .field("tracked", &self.tracked)
+            .field("len", &self.distances.len())
+            .finish()
+    }
+}
+
+/// Where a move takes the piece at each slot, and how much it twists it.
+struct SlotMove {
+    slots: Vec<usize>,
+    twists: Vec<usize>,
+}
+
+impl<T: Piece> PieceTable<T> {
+    /// Tables for the pieces that `faces` move, which stay in their
+    /// positions.
+    pub(crate) fn generate_subgroup(faces: &[Face], moves: &[Move]) -> Vec<Self> {
+        let positions: Vec<usize> = (0..T::COUNT)
+            .filter(|&position| faces.iter().any(|&face| T::moved_by(position, face)))
+            .collect();
+
+        Self::generate_chunks(positions.clone(), &positions, moves)
+    }
+
+    /// Tables for the given pieces (by their solved position), split so that
+    /// they aren't too big.
+    pub(crate) fn generate_chunks(
+        positions: Vec<usize>,
+        tracked: &[usize],
+        moves: &[Move],
+    ) -> Vec<Self> {
+        let effects: Vec<Cube> = moves
+            .iter()
+            .map(|&mov| Cube::SOLVED.mov_single(mov))
+            .collect();
+
+        Self::generate_chunks_with_effects(positions, tracked, &effects)
+    }
+
+    /// Like [`PieceTable::generate_chunks`], but with each move given by the
+    /// state it takes the solved cube to. This allows moves that are not face
+    /// turns, like slice moves.
+    pub(crate) fn generate_chunks_with_effects(
+        positions: Vec<usize>,
+        tracked: &[usize],
+        effects: &[Cube],
+    ) -> Vec<Self> {
+        let mut tables = Vec::new();
+        let mut remaining = tracked;
+        while !remaining.is_empty() {
+            let mut len = 1;
+            while len < remaining.len() && Self::size(positions.len(), len + 1) <= MAX_TABLE_SIZE {
+                len += 1;
+            }
+
+            tables.push(Self::generate(
+                positions.clone(),
+                remaining[..len].to_vec(),
+                effects,
+            ));
+            remaining = &remaining[len..];
+        }
+
+        tables
+    }
+
+    fn size(positions: usize, tracked: usize) -> usize {
+        let arrangements: usize = (positions - tracked + 1..=positions).product();
+        arrangements * T::ORIENTATIONS.pow(tracked as u32)
+    }
+
+    /// Fills in the table with a breadth first search from the solved state.
+    fn generate(positions: Vec<usize>, tracked: Vec<usize>, effects: &[Cube]) -> Self {
+        let slot_moves: Vec<SlotMove> = effects
+            .iter()
+            .map(|&moved| {
+                let mut slot_move = SlotMove {
+                    slots: vec![0; positions.len()],
+                    twists: vec![0; positions.len()],
+                };
+
+                for (slot, &position) in positions.iter().enumerate() {
+                    let piece = T::pieces(&moved)[position];
+                    let from = positions
+                        .iter()
+                        .position(|&position| position == piece.home())
+                        .expect("Moves keep the pieces in their positions");
+                    slot_move.slots[from] = slot;
+                    slot_move.twists[from] = piece.orientation();
+                }
+
+                slot_move
+            })
+            .collect();
+
+        let mut table = Self {
+            distances: vec![u8::MAX; Self::size(positions.len(), tracked.len())],
+            positions,
+            tracked,
+            _piece: PhantomData,
+        };
+
+        let solved = table
+            .index(Cube::SOLVED)
+            .expect("Solved state is reachable");
+        table.distances[solved] = 0;
+        let mut queue = VecDeque::from([solved]);
+        let mut slots = vec![0; table.tracked.len()];
+        let mut orientations = vec![0; table.tracked.len()];
+
+        while let Some(index) = queue.pop_front() {
+            let distance = table.distances[index];
+            table.decode(index, &mut slots, &mut orientations);
+
+            for slot_move in &slot_moves {
+                let moved_slots = slots.iter().map(|&slot| slot_move.slots[slot]);
+                let moved_orientations =
+                    slots
+                        .iter()
+                        .zip(&orientations)
+                        .map(|(&slot, &orientation)| {
+                            (orientation + slot_move.twists[slot]) % T::ORIENTATIONS
+                        });
+
+                let next = table.encode(moved_slots.zip(moved_orientations));
+                if table.distances[next] == u8::MAX {
+                    table.distances[next] = distance + 1;
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        table
+    }
+
+    /// The number of moves needed to solve the tracked pieces, or `None`
+    /// if they can't be solved.
+    pub(crate) fn distance(&self, cube: Cube) -> Option<u8> {
+        let distance = self.distances[self.index(cube)?];
+        (distance != u8::MAX).then_some(distance)
+    }
+
+    /// The index of the arrangement of the tracked pieces in `cube`, or `None`
+    /// if some of them are outside the positions of the table.
+    fn index(&self, cube: Cube) -> Option<usize> {
+        let pieces = T::pieces(&cube);
+        let mut arrangement = Vec::with_capacity(self.tracked.len());
+        for &home in &self.tracked {
+            let (position, piece) = pieces
+                .iter()
+                .enumerate()
+                .find(|(_, piece)| piece.home() == home)
+                .expect("Every piece is somewhere");
+            let slot = self.positions.iter().position(|&p| p == position)?;
+            arrangement.push((slot, piece.orientation()));
+        }
+
+        Some(self.encode(arrangement))
+    }
+
+    fn encode(&self, arrangement: impl IntoIterator<Item = (usize, usize)>) -> usize {
+        let mut used = 0_u32;
+        let mut index = 0;
+        let mut orientations = 0;
+
+        for (i, (slot, orientation)) in arrangement.into_iter().enumerate() {
+            // The number of free slots before this one.
+            let digit = slot - (used & ((1 << slot) - 1)).count_ones() as usize;
+            used |= 1 << slot;
+            index = index * (self.positions.len() - i) + digit;
+            orientations = orientations * T::ORIENTATIONS + orientation;
+        }
+
+        index * T::ORIENTATIONS.pow(self.tracked.len() as u32) + orientations
+    }
+
+    fn decode(&self, mut index: usize, slots: &mut [usize], orientations: &mut [usize]) {
+        for orientation in orientations.iter_mut().rev() {
+            *orientation = index % T::ORIENTATIONS;
+            index /= T::ORIENTATIONS;
+        }
+
+        for (i, slot) in slots.iter_mut().enumerate().rev() {
+            let radix = self.positions.len() - i;
+            *slot = index % radix;
+            index /= radix;
+        }
+
+        // Turn the digits into the actual slots.
+        let mut used = 0_u32;
+        for slot in slots {
+            *slot = (0..self.positions.len())
+                .filter(|free| used & (1 << free) == 0)
+                .nth(*slot)
+                .expect("There are enough free slots");
+            used |= 1 << *slot;
+        }
+    }
+}
@@ -0,0 +1,224 @@
+//! Exact membership tests for the group generated by some moves, with the
+//! Schreier–Sims algorithm.
+//!
+//! A state is seen as a permutation of 48 points: each corner slot with each
+//! of its 3 twists, and each edge slot with each of its 2 flips.
+//!
+//! # Resources
+//! - Schreier–Sims algorithm: <https://en.wikipedia.org/wiki/Schreier%E2%80%93Sims_algorithm>
+
+use norcina_cube_n::piece::{corner::Corner, edge::Edge};
+
+use super::piece_table::Piece;
+use crate::{Cube, Move};
+
+const POINTS: usize = 8 * 3 + 12 * 2;
+
+/// `perm[i]` is where the point `i` goes.
+type Perm = [u8; POINTS];
+
+const IDENTITY: Perm = {
+    let mut perm = [0; POINTS];
+    let mut i = 0;
+    while i < POINTS {
+        perm[i] = i as u8;
+        i += 1;
+    }
+    perm
+};
+
+/// Applies `second` after `first`.
+fn compose(first: &Perm, second: &Perm) -> Perm {
+    std::array::from_fn(|i| second[first[i] as usize])
+}
+
+fn inverse(perm: &Perm) -> Perm {
+    let mut inverse = IDENTITY;
+    for (i, &j) in perm.iter().enumerate() {
+        inverse[j as usize] = i as u8;
+    }
+    inverse
+}
+
+/// The point of a piece in a slot, twisted by `twist`, goes to the point of
+/// its home slot with the twists added.
+///
+/// Doing moves one after another composes their permutations in the opposite
+/// order, which doesn't change what can be reached.
+fn permutation(cube: &Cube) -> Perm {
+    fn write<T: Piece>(cube: &Cube, offset: usize, perm: &mut Perm) {
+        for (slot, piece) in T::pieces(cube).iter().enumerate() {
+            for twist in 0..T::ORIENTATIONS {
+                let target = piece.home() * T::ORIENTATIONS
+                    + (piece.orientation() + twist) % T::ORIENTATIONS;
+                perm[offset + slot * T::ORIENTATIONS + twist] = (offset + target) as u8;
+            }
+        }
+    }
+
+    let mut perm = IDENTITY;
+    write::<Corner>(cube, 0, &mut perm);
+    write::<Edge>(cube, Corner::COUNT * Corner::ORIENTATIONS, &mut perm);
+    perm
+}
+
+/// The group generated by some moves, as a chain of subgroups where each one
+/// also fixes the next point.
+#[derive(Debug)]
+pub(crate) struct StabilizerChain {
+    /// The generators that fix every point before the level, but not the
+    /// level's point.
+    generators: Vec<Vec<Perm>>,
+    /// For each level, the elements (fixing the points before it) that take
+    /// the level's point to each point of its orbit.
+    transversals: Vec<Vec<Option<Perm>>>,
+}
+
+impl StabilizerChain {
+    pub(crate) fn new(moves: &[Move]) -> Self {
+        let mut chain = Self {
+            generators: vec![Vec::new(); POINTS],
+            transversals: (0..POINTS)
+                .map(|level| {
+                    let mut transversal = vec![None; POINTS];
+                    transversal[level] = Some(IDENTITY);
+                    transversal
+                })
+                .collect(),
+        };
+
+        for &mov in moves {
+            let perm = permutation(&Cube::SOLVED.mov_single(mov));
+            if let Some((level, residue)) = chain.sift(perm, 0) {
+                chain.add_generator(level, residue, 0);
+            }
+        }
+
+        chain
+    }
+
+    /// The number of states that can be reached with the moves.
+    #[cfg(test)]
+    fn order(&self) -> u128 {
+        self.transversals
+            .iter()
+            .map(|transversal| transversal.iter().flatten().count() as u128)
+            .product()
+    }
+
+    /// Whether `cube` can be reached with the moves.
+    pub(crate) fn contains(&self, cube: Cube) -> bool {
+        self.sift(permutation(&cube), 0).is_none()
+    }
+
+    /// Divides `perm` by the transversals from `level` on, until it is the
+    /// identity (it is in the group) or it takes some level's point outside of
+    /// its orbit, in which case that level and what is left are returned.
+    fn sift(&self, mut perm: Perm, level: usize) -> Option<(usize, Perm)> {
+        for level in level..POINTS {
+            let image = perm[level] as usize;
+            match &self.transversals[level][image] {
+                Some(transversal) => perm = compose(&perm, &inverse(transversal)),
+                None => return Some((level, perm)),
+            }
+        }
+
+        debug_assert_eq!(perm, IDENTITY);
+        None
+    }
+
+    /// Adds a generator that fixes the points before `level`. It is also in
+    /// the subgroups of the levels before it (down to `lowest`), so those are
+    /// completed again, the deepest first.
+    fn add_generator(&mut self, level: usize, perm: Perm, lowest: usize) {
+        self.generators[level].push(perm);
+        for level in (lowest..=level).rev() {
+            self.complete(level);
+        }
+    }
+
+    /// Extends the orbit of the level and sifts its Schreier generators until
+    /// they are all in the chain.
+    fn complete(&mut self, level: usize) {
+        loop {
+            // The subgroup of the level also contains the generators of the
+            // levels after it.
+            let generators: Vec<Perm> = self.generators[level..].concat();
+            self.extend_orbit(level, &generators);
+
+            let mut missing = None;
+            'search: for point in 0..POINTS {
+                let Some(transversal) = self.transversals[level][point] else {
+                    continue;
+                };
+                for generator in &generators {
+                    let moved = compose(&transversal, generator);
+                    let back = self.transversals[level][moved[level] as usize]
+                        .expect("The orbit is closed under the generators");
+                    let schreier = compose(&moved, &inverse(&back));
+                    if let Some(residue) = self.sift(schreier, level + 1) {
+                        missing = Some(residue);
+                        break 'search;
+                    }
+                }
+            }
+
+            match missing {
+                Some((deeper, residue)) => self.add_generator(deeper, residue, level + 1),
+                None => break,
+            }
+        }
+    }
+
+    fn extend_orbit(&mut self, level: usize, generators: &[Perm]) {
+        let mut queue: Vec<usize> = (0..POINTS)
+            .filter(|&point| self.transversals[level][point].is_some())
+            .collect();
+
+        while let Some(point) = queue.pop() {
+            let transversal = self.transversals[level][point].expect("Points are in the orbit");
+            for generator in generators {
+                let image = generator[point] as usize;
+                if self.transversals[level][image].is_none() {
+                    self.transversals[level][image] = Some(compose(&transversal, generator));
+                    queue.push(image);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use norcina_cube_n::mov::moves::*;
+
+    #[test]
+    fn permutations_compose_in_opposite_order() {
+        let scramble = [R, U, F2, LP, D, B, R2, UP];
+        let perm = scramble
+            .iter()
+            .map(|&mov| permutation(&Cube::SOLVED.mov_single(mov)))
+            .fold(IDENTITY, |perm, mov| compose(&mov, &perm));
+
+        assert_eq!(perm, permutation(&Cube::SOLVED.mov(scramble)));
+    }
+
+    #[test]
+    fn counts_states_of_known_groups() {
+        assert_eq!(StabilizerChain::new(&[R, U]).order(), 73_483_200);
+        assert_eq!(StabilizerChain::new(&[R2, U2]).order(), 12);
+        assert_eq!(
+            StabilizerChain::new(&[R, U, F, L, D, B]).order(),
+            43_252_003_274_489_856_000
+        );
+    }
+
+    #[test]
+    fn every_state_is_in_the_full_group() {
+        let chain = StabilizerChain::new(&[R, U, F, L, D, B]);
+        for _ in 0..10 {
+            assert!(chain.contains(Cube::random()));
+        }
+    }
+}
@@ -0,0 +1,278 @@
+//! Solving with a restricted set of moves, like `<R, U>` or `<R, U, F>`.
+//!
+//! The pruning tables track the positions and orientations of the pieces
+//! that the moves can reach, in chunks small enough to generate quickly.
+//! Whether a state can be solved with the given moves at all (it can't with a
+//! twisted corner in `<R, U>`, for example) is told by a
+//! [stabilizer chain](super::stabilizer_chain) of the group.
+//!
+//! Only face turns are supported. Slice moves like `M` also move the centers,
+//! which [`Cube`] doesn't keep track of.
+
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+use norcina_core::{Alg, search::IdaStar};
+use norcina_cube_n::{
+    Face,
+    mov::Amount,
+    piece::{corner::Corner, edge::Edge},
+};
+
+use super::{
+    SearchSolution, SolutionFilter, piece_table::PieceTable, stabilizer_chain::StabilizerChain,
+};
+use crate::{Cube, Move};
+
+/// [`Subgroup::solve`] gives up on solutions longer than this.
+pub const MAX_SOLUTION_LEN: u8 = 40;
+
+/// The states that can be reached from the solved state with some set of
+/// moves, along with pruning tables to solve them.
+#[derive(Debug)]
+pub struct Subgroup {
+    generators: Vec<Move>,
+    corner_tables: Vec<PieceTable<Corner>>,
+    edge_tables: Vec<PieceTable<Edge>>,
+    chain: StabilizerChain,
+}
+
+impl Subgroup {
+    /// Generates the pruning tables of the subgroup generated by the given
+    /// moves. Every power of each move is also used (that is, `R` means `R`,
+    /// `R2` and `R'`, while `R2` means just `R2`).
+    pub fn new(generators: impl IntoIterator<Item = Move>) -> Self {
+        let generators = with_powers(generators);
+        let mut faces: Vec<Face> = generators.iter().map(|mov| mov.face()).collect();
+        faces.dedup();
+
+        Self {
+            corner_tables: PieceTable::generate_subgroup(&faces, &generators),
+            edge_tables: PieceTable::generate_subgroup(&faces, &generators),
+            chain: StabilizerChain::new(&generators),
+            generators,
+        }
+    }
+
+    /// Like [`Subgroup::new`], but reuses the tables if a subgroup with the
+    /// same moves was already generated.
+    pub fn cached(generators: impl IntoIterator<Item = Move>) -> Arc<Self> {
+        static CACHE: Mutex<Vec<Arc<Subgroup>>> = Mutex::new(Vec::new());
+
+        let generators = with_powers(generators);
+        let mut cache = CACHE.lock().expect("Cache shouldn't be poisoned");
+        if let Some(cached) = cache.iter().find(|cached| cached.generators == generators) {
+            return Arc::clone(cached);
+        }
+
+        let subgroup = Arc::new(Self::new(generators));
+        cache.push(Arc::clone(&subgroup));
+        subgroup
+    }
+
+    /// Every move that can be used to solve (including the powers of the
+    /// original generators).
+    pub fn generators(&self) -> &[Move] {
+        &self.generators
+    }
+
+    /// A lower bound of the number of moves needed to solve `cube` with the
+    /// moves of the subgroup, or `None` if it can't be solved with them.
+    pub fn distance_lower_bound(&self, cube: Cube) -> Option<u8> {
+        self.contains(cube).then(|| self.table_distance(cube))
+    }
+
+    /// Like [`Subgroup::distance_lower_bound`], for a state that is known to
+    /// be in the subgroup.
+    fn table_distance(&self, cube: Cube) -> u8 {
+        let corners = self.corner_tables.iter().map(|table| table.distance(cube));
+        let edges = self.edge_tables.iter().map(|table| table.distance(cube));
+        corners
+            .chain(edges)
+            .map(|distance| distance.expect("States in the subgroup are in the tables"))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Whether `cube` can be solved with the moves of the subgroup.
+    pub fn contains(&self, cube: Cube) -> bool {
+        self.chain.contains(cube)
+    }
+
+    /// Finds one of the shortest solutions using only the moves of the
+    /// subgroup.
+    pub fn solve(&self, cube: Cube) -> Result<SearchSolution, NotInSubgroupError> {
+        self.solve_all(cube, MAX_SOLUTION_LEN)?
+            .next()
+            .ok_or_else(|| self.error())
+    }
+
+    /// Every solution with at most `max_len` moves of the subgroup, shortest
+    /// first. See [`super::solve_all`].
+    pub fn solve_all(
+        &self,
+        cube: Cube,
+        max_len: u8,
+    ) -> Result<impl Iterator<Item = SearchSolution> + '_, NotInSubgroupError> {
+        self.solve_all_filtered(cube, max_len, SolutionFilter::default())
+    }
+
+    /// Like [`Subgroup::solve_all`], but only returns the solutions that
+    /// `filter` accepts.
+    pub fn solve_all_filtered(
+        &self,
+        cube: Cube,
+        max_len: u8,
+        filter: SolutionFilter,
+    ) -> Result<impl Iterator<Item = SearchSolution> + '_, NotInSubgroupError> {
+        if !self.contains(cube) {
+            return Err(self.error());
+        }
+
+        let generators: Vec<_> = self
+            .generators
+            .iter()
+            .copied()
+            .filter(|&mov| filter.allows_move(mov))
+            .collect();
+        let solutions = IdaStar::new(
+            // States outside the subgroup are never reached.
+            |cube| self.table_distance(cube),
+            Cube::is_solved,
+        )
+        .with_generators(generators)
+        .with_max_depth(max_len)
+        .all_solutions(cube)
+        .filter(move |alg| filter.accepts(alg))
+        .map(move |alg| SearchSolution::from_alg(cube, alg));
+
+        Ok(solutions)
+    }
+
+    fn error(&self) -> NotInSubgroupError {
+        NotInSubgroupError(Alg {
+            moves: self.generators.clone(),
+        })
+    }
+}
+
+/// Adds every power of each move, sorted by face.
+fn with_powers(generators: impl IntoIterator<Item = Move>) -> Vec<Move> {
+    let mut moves = Vec::new();
+    for generator in generators {
+        let powers = match generator.amount() {
+            Amount::Double => [Amount::Double].as_slice(),
+            Amount::Single | Amount::Reverse => {
+                [Amount::Single, Amount::Double, Amount::Reverse].as_slice()
+            }
+        };
+
+        for &amount in powers {
+            let mov = Move::new(generator.face(), amount);
+            if !moves.contains(&mov) {
+                moves.push(mov);
+            }
+        }
+    }
+
+    moves.sort_by_key(|mov| (mov.face().u8(), mov.amount().u8()));
+    moves
+}
+
+/// The state can't be solved using only the given moves.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotInSubgroupError(pub Alg<Move>);
+
+impl fmt::Display for NotInSubgroupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "state can't be solved using only <{}>", self.0)
+    }
+}
+
+impl std::error::Error for NotInSubgroupError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algs;
+    use norcina_core::types::Orientation3;
+    use norcina_cube_n::mov::moves::*;
+    use norcina_cube_n::piece::corner::CornerPosition;
+
+    #[test]
+    fn solves_in_two_generator_subgroup() {
+        let subgroup = Subgroup::cached([R, U]);
+        let cube = Cube::SOLVED.mov([R, U, RP, UP, R2]);
+
+        let solution = subgroup.solve(cube).unwrap().alg();
+        assert_eq!(solution.moves, [R2, U, R, UP, RP]);
+    }
+
+    fn twist_u_corners(orientations: &[u8]) -> Cube {
+        let mut cube = Cube::SOLVED;
+        let u_corners = CornerPosition::ALL
+            .into_iter()
+            .filter(|position| position.contains_face(Face::U));
+        for (position, &orientation) in u_corners.zip(orientations) {
+            cube.corners[position.u8() as usize]
+                .set_orientation(Orientation3::from_u8(orientation));
+        }
+
+        cube
+    }
+
+    #[test]
+    fn unreachable_states_are_reported() {
+        let subgroup = Subgroup::cached([R, U]);
+        assert!(Arc::ptr_eq(&subgroup, &Subgroup::cached([RP, U2, U])));
+
+        let error = NotInSubgroupError(Alg {
+            moves: vec![R, R2, RP, U, U2, UP],
+        });
+        assert_eq!(subgroup.solve(twist_u_corners(&[1])), Err(error.clone()));
+        assert_eq!(subgroup.solve(Cube::SOLVED.mov(algs::pll::T)), Err(error));
+    }
+
+    #[test]
+    fn parity_is_checked_across_pieces() {
+        // Corners and edges are in different tables, and each of them can be
+        // solved on its own, but not both together.
+        let mut cube = Cube::SOLVED.mov([R]);
+        cube.edges = Edge::SOLVED;
+
+        let subgroup = Subgroup::cached([R, U]);
+        assert!(!subgroup.contains(cube));
+        assert_eq!(subgroup.distance_lower_bound(cube), None);
+        assert!(subgroup.solve(cube).is_err());
+    }
+
+    #[test]
+    fn two_twisted_corners_are_solvable_in_two_generator_subgroup() {
+        let cube = twist_u_corners(&[1, 2]);
+        let solution = Subgroup::cached([R, U]).solve(cube).unwrap();
+        assert!(solution.final_state().is_solved());
+        assert!(
+            solution
+                .alg()
+                .iter()
+                .all(|mov| [Face::R, Face::U].contains(&mov.face()))
+        );
+    }
+
+    #[test]
+    fn solves_in_three_generator_subgroup() {
+        let cube = Cube::SOLVED.mov(algs::SLEDGEHAMMER);
+        let solution = Subgroup::cached([R, U, F]).solve(cube).unwrap();
+        assert_eq!(solution.alg().moves, [F, RP, FP, R]);
+    }
+
+    #[test]
+    fn pieces_outside_of_subgroup_must_be_solved() {
+        let subgroup = Subgroup::new([R2, U2]);
+        assert!(subgroup.contains(Cube::SOLVED.mov([R2, U2, R2])));
+        assert!(!subgroup.contains(Cube::SOLVED.mov([F])));
+        assert!(!subgroup.contains(Cube::SOLVED.mov([R])));
+    }
+}
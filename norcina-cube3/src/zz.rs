@@ -0,0 +1,272 @@
+//! Edge orientation (EO) and the first step of ZZ: EOLine and EOCross.
+//!
+//! EO is relative to an axis: an edge is misoriented if it can't be solved
+//! without quarter turns of the faces of that axis. The usual EO (the one of
+//! [`Edge::is_oriented`]) is along [`Axis::Z`], so F and B quarter turns flip
+//! edges.
+
+use std::collections::VecDeque;
+
+use norcina_core::{
+    search::IdaStar,
+    types::{Axis, Direction},
+};
+use norcina_cube_n::{
+    Face,
+    mov::Amount,
+    piece::edge::{self, Edge, EdgePosition},
+};
+
+use crate::{
+    Cube, Move,
+    search::{SearchSolution, piece_table::PieceTable},
+};
+
+/// Whether `piece` would be misoriented at `position`, along `axis`.
+pub fn is_misoriented(piece: Edge, position: EdgePosition, axis: Axis) -> bool {
+    // The sticker of the piece that is checked, and the face it should be on,
+    // are the ones of another axis (or of `axis` itself if there isn't one).
+    let reference_face = |position: EdgePosition| {
+        let [a, b] = position.faces();
+        if a.axis() == axis.prev() || (b.axis() != axis.prev() && a.axis() == axis) {
+            a
+        } else {
+            b
+        }
+    };
+
+    edge::sticker(piece, position, reference_face(position)) != reference_face(piece.position())
+}
+
+/// The number of misoriented edges along `axis`.
+pub fn misoriented_edges(cube: Cube, axis: Axis) -> u8 {
+    cube.edges()
+        .filter(|&(position, piece)| is_misoriented(piece, position, axis))
+        .count() as u8
+}
+
+/// The number of misoriented edges along each axis, indexed by [`Axis::u8`].
+pub fn misoriented_edges_per_axis(cube: Cube) -> [u8; 3] {
+    [Axis::X, Axis::Y, Axis::Z].map(|axis| misoriented_edges(cube, axis))
+}
+
+/// Whether every edge is oriented along `axis`.
+pub fn is_eo_solved(cube: Cube, axis: Axis) -> bool {
+    misoriented_edges(cube, axis) == 0
+}
+
+/// The number of moves to orient the edges along one axis, for every EO.
+#[derive(Debug)]
+struct EoTable {
+    axis: Axis,
+    distances: Vec<u8>,
+}
+
+impl EoTable {
+    /// Fills in the table with a breadth first search from the oriented
+    /// state, where each state is the set of positions with misoriented edges.
+    fn generate(axis: Axis) -> Self {
+        // Where each move takes the edge at each position, and whether it
+        // flips it.
+        let effects: Vec<[(usize, bool); 12]> = Move::iter()
+            .map(|mov| {
+                let mut effect = [(0, false); 12];
+                for (position, piece) in Cube::SOLVED.mov_single(mov).edges() {
+                    effect[piece.position().index() as usize] = (
+                        position.index() as usize,
+                        is_misoriented(piece, position, axis),
+                    );
+                }
+                effect
+            })
+            .collect();
+
+        let mut distances = vec![u8::MAX; 1 << 12];
+        distances[0] = 0;
+        let mut queue = VecDeque::from([0_usize]);
+        while let Some(state) = queue.pop_front() {
+            for effect in &effects {
+                let mut next = 0;
+                for (from, &(to, flip)) in effect.iter().enumerate() {
+                    let is_misoriented = (state >> from) & 1 == 1;
+                    next |= ((is_misoriented ^ flip) as usize) << to;
+                }
+
+                if distances[next] == u8::MAX {
+                    distances[next] = distances[state] + 1;
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        Self { axis, distances }
+    }
+
+    fn distance(&self, cube: Cube) -> u8 {
+        let state = cube
+            .edges()
+            .filter(|&(position, piece)| is_misoriented(piece, position, self.axis))
+            .fold(0, |state, (position, _)| state | 1 << position.index());
+
+        self.distances[state]
+    }
+}
+
+/// Finds the optimal solutions of EO along an axis, optionally along with
+/// some edges (the line or the cross).
+#[derive(Debug)]
+pub struct EoSolver {
+    axis: Axis,
+    edges: Vec<usize>,
+    eo_table: EoTable,
+    edge_tables: Vec<PieceTable<Edge>>,
+}
+
+impl EoSolver {
+    fn new(axis: Axis, edges: Vec<usize>) -> Self {
+        Self {
+            eo_table: EoTable::generate(axis),
+            edge_tables: PieceTable::generate_chunks((0..12).collect(), &edges, &Move::ALL),
+            axis,
+            edges,
+        }
+    }
+
+    /// Just the orientation of the edges along `axis`.
+    pub fn eo(axis: Axis) -> Self {
+        Self::new(axis, Vec::new())
+    }
+
+    /// EO along `axis`, and the two edges of `bottom` that are on the faces
+    /// of `axis` (DF and DB for the usual EOLine on D, along [`Axis::Z`]).
+    ///
+    /// # Panics
+    ///
+    /// If `bottom` is on `axis`.
+    pub fn eoline(bottom: Face, axis: Axis) -> Self {
+        assert_ne!(bottom.axis(), axis, "Line can't be on the EO axis");
+        let line = [Direction::Positive, Direction::Negative]
+            .map(|direction| EdgePosition::from_faces([bottom, Face::new(axis, direction)]));
+
+        Self::new(
+            axis,
+            line.map(|position| position.index() as usize).to_vec(),
+        )
+    }
+
+    /// EO along `axis`, and the four edges of `bottom`.
+    pub fn eocross(bottom: Face, axis: Axis) -> Self {
+        let cross = EdgePosition::ALL
+            .into_iter()
+            .filter(|position| position.contains_face(bottom))
+            .map(|position| position.index() as usize)
+            .collect();
+
+        Self::new(axis, cross)
+    }
+
+    /// Whether the step is done.
+    pub fn is_solved(&self, cube: Cube) -> bool {
+        is_eo_solved(cube, self.axis)
+            && self
+                .edges
+                .iter()
+                .all(|&i| cube.edges[i] == Edge::solved(i as u8))
+    }
+
+    /// A lower bound of the number of moves to do the step.
+    pub fn distance_lower_bound(&self, cube: Cube) -> u8 {
+        self.edge_tables
+            .iter()
+            .map(|table| table.distance(cube).expect("Every edge can be solved"))
+            .fold(self.eo_table.distance(cube), u8::max)
+    }
+
+    /// Every optimal solution of the step.
+    pub fn solutions(&self, cube: Cube) -> impl Iterator<Item = SearchSolution> + '_ {
+        IdaStar::new(
+            |cube| self.distance_lower_bound(cube),
+            |cube| self.is_solved(cube),
+        )
+        .solutions(cube)
+        .map(move |alg| SearchSolution::from_alg(cube, alg))
+    }
+
+    /// One of the optimal solutions of the step.
+    pub fn solve(&self, cube: Cube) -> SearchSolution {
+        self.solutions(cube)
+            .next()
+            .expect("Every step can be solved")
+    }
+}
+
+/// The shortest EO along each axis, for FMC.
+pub fn eo_first(cube: Cube) -> [(Axis, SearchSolution); 3] {
+    [Axis::X, Axis::Y, Axis::Z].map(|axis| (axis, EoSolver::eo(axis).solve(cube)))
+}
+
+/// The moves that keep the edges oriented along `axis`: quarter turns of the
+/// other faces, and half turns of the faces of `axis`.
+pub fn eo_preserving_moves(axis: Axis) -> impl Iterator<Item = Move> {
+    Move::iter().filter(move |mov| mov.axis() != axis || mov.amount() == Amount::Double)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use norcina_cube_n::mov::moves::*;
+
+    #[test]
+    fn misoriented_edges_along_z_match_edge_orientation() {
+        let cube = Cube::SOLVED.mov([F, R, UP, B, L2, D]);
+        let flipped = cube.edges.iter().filter(|edge| !edge.is_oriented()).count();
+        assert_eq!(misoriented_edges(cube, Axis::Z) as usize, flipped);
+    }
+
+    #[test]
+    fn quarter_turns_flip_edges_along_their_axis() {
+        assert_eq!(misoriented_edges_per_axis(Cube::SOLVED.mov([F])), [0, 0, 4]);
+        assert_eq!(misoriented_edges_per_axis(Cube::SOLVED.mov([R])), [4, 0, 0]);
+        assert_eq!(misoriented_edges_per_axis(Cube::SOLVED.mov([U])), [0, 4, 0]);
+
+        let cube = Cube::SOLVED.mov([F, R, U, B2, L]);
+        for axis in [Axis::X, Axis::Y, Axis::Z] {
+            let moved = cube.mov(eo_preserving_moves(axis));
+            assert_eq!(
+                misoriented_edges(moved, axis),
+                misoriented_edges(cube, axis)
+            );
+        }
+    }
+
+    #[test]
+    fn eoline_is_optimal() {
+        let cube = Cube::SOLVED.mov([F, D, R, UP, B]);
+        let solver = EoSolver::eoline(Face::D, Axis::Z);
+        let solution = solver.solve(cube);
+
+        assert!(solver.is_solved(solution.final_state()));
+        assert!(is_eo_solved(solution.final_state(), Axis::Z));
+        assert!(solution.states.len() - 1 <= 5);
+    }
+
+    #[test]
+    fn eocross_solves_the_cross() {
+        let cube = Cube::SOLVED.mov([F, R2, D, B, U2]);
+        let state = EoSolver::eocross(Face::D, Axis::Z)
+            .solve(cube)
+            .final_state();
+
+        assert!(is_eo_solved(state, Axis::Z));
+        assert!(crate::cfop::StepSolver::cross(Face::D).is_solved(state));
+    }
+
+    #[test]
+    fn eo_first_for_each_axis() {
+        let cube = Cube::SOLVED.mov([F, R, U]);
+        for (axis, solution) in eo_first(cube) {
+            assert!(is_eo_solved(solution.final_state(), axis));
+            assert!(solution.states.len() - 1 <= 3);
+        }
+    }
+}
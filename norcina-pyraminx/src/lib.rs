@@ -10,7 +10,7 @@ pub mod piece;
 /// A [Pyraminx](https://www.worldcubeassociation.org/results/rankings/pyram/)
 ///
 /// See also [`CorePyraminx`].
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Pyraminx {
     core: CorePyraminx,
     tips: Tips,
@@ -43,17 +43,43 @@ impl Pyraminx {
         }
     }
 
+    pub fn is_solved(self) -> bool {
+        self == Self::SOLVED
+    }
+
+    /// Generates a uniformly random state.
+    pub fn random_with_rng(rng: &mut impl rand::Rng) -> Self {
+        Self {
+            core: CorePyraminx {
+                centers: Centers::random(rng),
+                edges: Edge::random(rng),
+            },
+            tips: Tips::random(rng),
+        }
+    }
+
+    pub fn random() -> Self {
+        Self::random_with_rng(&mut rand::rng())
+    }
+
+    /// Every state that is reachable with one move, along with that move.
+    pub fn neighbors(self) -> impl Iterator<Item = (Move, Self)> {
+        Move::ALL
+            .into_iter()
+            .map(move |mov| (mov, self.mov_single(mov)))
+    }
+
     pub fn write(
         self,
         f: &mut fmt::Formatter<'_>,
         color_scheme: ColorScheme,
         render_as_triangles: bool,
     ) -> fmt::Result {
-        let mut i = 0;
+        let mut i = 0u8;
         let mut write = |f: &mut fmt::Formatter<'_>, sticker| {
             if render_as_triangles {
                 i += 1;
-                if i % 2 != 0 {
+                if !i.is_multiple_of(2) {
                     write!(f, "{}", "▲".color((color_scheme)(sticker)))
                 } else {
                     write!(f, "{}", "▼".color((color_scheme)(sticker)))
@@ -65,17 +91,17 @@ impl Pyraminx {
 
         let space = |f: &mut fmt::Formatter<'_>| {
             if render_as_triangles {
-                write!(f, "{}", " ")
+                f.write_str(" ")
             } else {
-                write!(f, "{}", "  ")
+                f.write_str("  ")
             }
         };
 
         let smallspace = |f: &mut fmt::Formatter<'_>| {
             if render_as_triangles {
-                write!(f, "{}", " ")
+                f.write_str(" ")
             } else {
-                write!(f, "{}", "  ")
+                f.write_str("  ")
             }
         };
 
@@ -142,7 +168,7 @@ impl Pyraminx {
         //
         // Edge if either if index == 2 or row == 1
 
-        let is_center = index % 2 != 0;
+        let is_center = !index.is_multiple_of(2);
         let is_edge_or_center = index == 2 || row == 1;
         // We start off with edges, because centers and tips are basically identical.
         if is_edge_or_center && !is_center {
@@ -175,7 +201,7 @@ impl Pyraminx {
                 edge.non_orientation_face()
             }
         } else {
-            let is_center = index % 2 != 0;
+            let is_center = !index.is_multiple_of(2);
             let (i, set) = if is_center {
                 (row.max(index) - 1, self.core.centers)
             } else {
@@ -201,7 +227,7 @@ impl Pyraminx {
 /// without affecting each other. The number of states is not thaaat low (3⁴ =
 /// 81) but it's extremely easy to basically map out all states, because the
 /// transition between them are, again, very straightforward.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct CorePyraminx {
     centers: Centers,
     edges: [Edge; 6],
@@ -237,9 +263,56 @@ impl fmt::Display for Pyraminx {
     }
 }
 
+impl norcina_core::Puzzle for Pyraminx {
+    type Move = Move;
+
+    const SOLVED: Self = Self::SOLVED;
+
+    fn apply(self, mov: Move) -> Self {
+        self.mov_single(mov)
+    }
+
+    fn random_state(rng: &mut impl rand::Rng) -> Self {
+        Self::random_with_rng(rng)
+    }
+
+    fn neighbors(self) -> impl Iterator<Item = (Move, Self)> {
+        self.neighbors()
+    }
+
+    /// Moves of the same vertex can be merged. Tip moves commute with
+    /// everything, so they are only allowed in order of vertex.
+    fn is_redundant(previous: Move, next: Move) -> bool {
+        let same_vertex = previous.core().vertex() == next.core().vertex();
+        if previous.is_tip_move() && next.is_tip_move() {
+            next.core().vertex().u8() <= previous.core().vertex().u8()
+        } else {
+            same_vertex && previous.is_tip_move() == next.is_tip_move()
+        }
+    }
+
+    fn render(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, DEFAULT_COLOR_SCHEME, false)
+    }
+}
+
 #[cfg(feature = "quickcheck")]
 mod quickcheck_impl {
+    use super::*;
     use quickcheck::{Arbitrary, Gen};
+
+    impl Arbitrary for Move {
+        fn arbitrary(g: &mut Gen) -> Self {
+            *g.choose(&Move::ALL).unwrap()
+        }
+    }
+
+    impl Arbitrary for Pyraminx {
+        fn arbitrary(g: &mut Gen) -> Self {
+            let moves: Vec<Move> = Arbitrary::arbitrary(g);
+            Self::SOLVED.mov(moves)
+        }
+    }
 }
 
 #[cfg(test)]
@@ -254,6 +327,40 @@ mod tests {
         insta::assert_snapshot!(Pyraminx::SOLVED)
     }
 
+    #[test]
+    fn random_edges_are_reachable() {
+        use rand::{SeedableRng as _, rngs::SmallRng};
+        use std::collections::HashSet;
+
+        let mut reachable = HashSet::from([Edge::SOLVED]);
+        let mut stack = vec![Edge::SOLVED];
+        while let Some(edges) = stack.pop() {
+            for mov in CoreMove::ALL {
+                let next = edge::move_pieces(edges, mov);
+                if reachable.insert(next) {
+                    stack.push(next);
+                }
+            }
+        }
+
+        assert_eq!(reachable.len(), 6 * 5 * 4 * 3 * 2 / 2 * 2usize.pow(5));
+
+        let mut rng = SmallRng::seed_from_u64(4);
+        for _ in 0..100 {
+            assert!(reachable.contains(&Edge::random(&mut rng)));
+        }
+    }
+
+    #[test]
+    fn centers_only_change_with_their_vertex() {
+        let centers = Centers::SOLVED
+            .mov(CoreMove::new(Vertex::R, Amount::Single))
+            .mov(CoreMove::new(Vertex::U, Amount::Single))
+            .mov(CoreMove::new(Vertex::U, Amount::Single));
+        assert_eq!(centers.orientation_of(Vertex::R), Orientation3::ONE);
+        assert_eq!(centers.orientation_of(Vertex::U), Orientation3::TWO);
+    }
+
     #[test]
     #[cfg_attr(miri, ignore)]
     fn snapshot_every_move() {
@@ -273,13 +380,14 @@ mod tests {
 
     #[cfg(feature = "quickcheck")]
     quickcheck::quickcheck! {
-         // fn move_and_inverse_is_identity(mov: Move, state: Pyraminx) -> bool {
-         //     state.mov([mov, mov.inverse()]) == state
-         // }
-
-         // fn triple_move_is_identity(mov: Move, state: Pyraminx) -> bool {
-         //     state.mov([mov, mov, mov]) == state
-         // }
+         fn move_and_inverse_is_identity(mov: Move, state: Pyraminx) -> bool {
+             use norcina_core::mov::InvertibleMove as _;
+             state.mov([mov, mov.inverse()]) == state
+         }
+
+         fn triple_move_is_identity(mov: Move, state: Pyraminx) -> bool {
+             state.mov([mov, mov, mov]) == state
+         }
 
          // fn from_faces_faces_is_identity(f1: Face, f2: Face) -> bool {
          //     let n = Edge::position_from_faces([f1, f2]).faces();
@@ -1,4 +1,4 @@
-use std::{fmt, mem};
+use std::{fmt, mem, str::FromStr};
 
 use crate::piece::Vertex;
 
@@ -34,6 +34,13 @@ impl Amount {
         // SAFETY: We take modulo 2.
         unsafe { Self::from_u8_unchecked(data & 0b1) }
     }
+
+    pub const fn reverse(self) -> Self {
+        match self {
+            Self::Single => Self::Reverse,
+            Self::Reverse => Self::Single,
+        }
+    }
 }
 
 #[repr(transparent)]
@@ -73,7 +80,7 @@ impl CoreMove {
 }
 
 #[repr(transparent)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Move {
     // Packed field: ----tavv
     data: u8,
@@ -89,6 +96,17 @@ impl Move {
     pub const U: Self = Self::new(Vertex::U, Amount::Single, false);
     pub const UP: Self = Self::new(Vertex::U, Amount::Reverse, false);
 
+    /// Every move, including tip moves.
+    pub const ALL: [Self; 16] = {
+        let mut all = [Self::R; 16];
+        let mut i = 0;
+        while i < 16 {
+            all[i] = Self { data: i as u8 };
+            i += 1;
+        }
+        all
+    };
+
     pub const fn new(vertex: Vertex, amount: Amount, tip: bool) -> Self {
         Self {
             data: vertex.u8() + (amount.u8() << 2) + ((tip as u8) << 3),
@@ -111,6 +129,22 @@ impl Move {
     }
 }
 
+impl norcina_core::Move for Move {}
+impl norcina_core::mov::InvertibleMove for Move {
+    fn inverse(&self) -> Self {
+        Self::new(
+            self.core().vertex(),
+            self.core().amount().reverse(),
+            self.is_tip_move(),
+        )
+    }
+}
+impl norcina_core::mov::RandomMove for Move {
+    fn random(rng: &mut impl rand::Rng) -> Self {
+        Self::ALL[rng.random_range(0..16)]
+    }
+}
+
 impl fmt::Display for Move {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let addendum = if self.core().amount() == Amount::Single {
@@ -134,3 +168,43 @@ impl fmt::Display for Move {
         write!(f, "{main}{addendum}")
     }
 }
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseMoveError(String);
+
+impl fmt::Display for ParseMoveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid Pyraminx move {:?}", self.0)
+    }
+}
+
+impl std::error::Error for ParseMoveError {}
+
+impl FromStr for Move {
+    type Err = ParseMoveError;
+
+    /// Parses a vertex, uppercase for the whole corner or lowercase for just
+    /// the tip, optionally followed by `'` (e.g., `R`, `u'`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseMoveError(s.to_string());
+        let s = s.trim();
+        let (vertex, amount) = match s.strip_suffix('\'') {
+            Some(vertex) => (vertex, Amount::Reverse),
+            None => (s, Amount::Single),
+        };
+
+        let (vertex, tip) = match vertex {
+            "U" => (Vertex::U, false),
+            "L" => (Vertex::L, false),
+            "B" => (Vertex::B, false),
+            "R" => (Vertex::R, false),
+            "u" => (Vertex::U, true),
+            "l" => (Vertex::L, true),
+            "b" => (Vertex::B, true),
+            "r" => (Vertex::R, true),
+            _ => return Err(err()),
+        };
+
+        Ok(Self::new(vertex, amount, tip))
+    }
+}
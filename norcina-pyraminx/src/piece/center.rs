@@ -3,7 +3,7 @@ use crate::mov::CoreMove;
 use norcina_core::types::Orientation3;
 
 #[repr(transparent)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Centers {
     // Packed struct: BBLLUURR
     data: u8,
@@ -12,6 +12,15 @@ pub struct Centers {
 impl Centers {
     pub const SOLVED: Self = Self { data: 0 };
 
+    /// Generates uniformly random orientations.
+    pub fn random(rng: &mut impl rand::Rng) -> Self {
+        let mut data = 0;
+        for vertex in Vertex::ALL {
+            data |= rng.random_range(0..3) << (vertex.u8() * 2);
+        }
+        Self { data }
+    }
+
     /// The orientation of the tip at the given vertex, relative to the
     /// corresponding center.
     ///
@@ -28,7 +37,8 @@ impl Centers {
     // TODO: Make const
     pub fn mov(self, mov: CoreMove) -> Self {
         let orientation_delta = mov.amount().u8() + 1;
-        let new_orientation = ((self.data >> (mov.vertex().u8() * 2)) + orientation_delta) % 3;
+        let new_orientation =
+            (((self.data >> (mov.vertex().u8() * 2)) & 0b11) + orientation_delta) % 3;
 
         // TODO: Is there a more consice way to overwrite using bit twiddling?
         Self {
@@ -15,7 +15,7 @@ use super::{Face, Vertex};
 /// They also have an orientation. Two of the moves toggle orientation, the
 /// other two don't. This decision is arbitrary, so let's arbitrarily choose the
 /// moves not on the +x axis, i.e., L and B moves toggle orientation.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Edge {
     // Packed field: ----oaad.
     //
@@ -196,6 +196,54 @@ impl Edge {
     pub fn is_on_orientation_face(self) -> bool {
         self.axis_direction() == Direction::Negative
     }
+
+    /// Generates a uniformly random reachable set of edges.
+    ///
+    /// Moves are 3-cycles which flip an even number of edges, so only even
+    /// permutations with an even number of flipped edges are reachable (and
+    /// every one of them is).
+    pub fn random(rng: &mut impl rand::Rng) -> [Edge; 6] {
+        use rand::seq::SliceRandom;
+
+        let mut out = Self::SOLVED;
+        out.shuffle(rng);
+
+        if Self::count_swaps(out) % 2 == 1 {
+            out.swap(0, 1);
+        }
+
+        let mut final_orientation = false;
+        for edge in &mut out[0..5] {
+            let orientation = rng.random_bool(0.5);
+            edge.data |= (orientation as u8) << 3;
+            final_orientation ^= orientation;
+        }
+
+        out[5].data |= (final_orientation as u8) << 3;
+
+        out
+    }
+
+    /// The number of swaps needed to solve the permutation of `edges`.
+    pub fn count_swaps(edges: [Edge; 6]) -> u8 {
+        let mut visited = [false; 6];
+        let mut output = 0;
+        for start in 0..6 {
+            if visited[start] {
+                continue;
+            }
+
+            visited[start] = true;
+            let mut current = (edges[start].data & 0b111) as usize;
+            while current != start {
+                output += 1;
+                visited[current] = true;
+                current = (edges[current].data & 0b111) as usize;
+            }
+        }
+
+        output
+    }
 }
 
 pub fn move_pieces(mut edges: [Edge; 6], mov: CoreMove) -> [Edge; 6] {
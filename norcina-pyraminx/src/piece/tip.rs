@@ -3,12 +3,17 @@ use crate::mov::CoreMove;
 use norcina_core::types::Orientation3;
 
 /// Tips hold literally the same information as tips.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Tips(Centers);
 
 impl Tips {
     pub const SOLVED: Self = Self(Centers::SOLVED);
 
+    /// See [`Centers::random`]
+    pub fn random(rng: &mut impl rand::Rng) -> Self {
+        Self(Centers::random(rng))
+    }
+
     /// See [`Centers::orientation_of`]
     #[inline(always)]
     pub const fn orientation_of(self, vertex: Vertex) -> Orientation3 {
@@ -0,0 +1,7 @@
+use norcina_sq1::{Square1, solve};
+
+fn main() {
+    let scramble = solve::scramble(&mut rand::rng());
+    println!("Scramble is {scramble}");
+    println!("{}", Square1::SOLVED.mov(scramble.iter().copied()));
+}
@@ -0,0 +1,516 @@
+//! A [Square-1](https://www.worldcubeassociation.org/results/rankings/sq1/)
+//!
+//! # Conventions
+//!
+//! Each layer is divided into 12 slots of 30°. Slots are numbered clockwise, as
+//! seen from the face of the layer, starting at the front end of the slice.
+//! That way, the slice swaps slots 0-5 of the top layer with slots 6-11 of the
+//! bottom layer, and turning any layer clockwise moves the piece at slot `i`
+//! to slot `i + 1`.
+
+use owo_colors::{OwoColorize as _, Rgb};
+use std::fmt::{self, Write as _};
+
+pub mod mov;
+pub use mov::Move;
+
+pub mod piece;
+pub use piece::{Face, Piece};
+
+pub mod shape;
+pub use shape::Shape;
+
+pub mod solve;
+
+pub type Alg = norcina_core::Alg<Move>;
+
+/// The top or bottom layer of a [`Square1`].
+#[repr(transparent)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Layer {
+    /// Packed field: 12 nibbles, each one a [`Piece`]. The piece at slot `i`
+    /// is at bits `4i..4i + 4`. Corners appear in two consecutive slots.
+    ///
+    /// The 16 most significant bits are always 0.
+    data: u64,
+}
+
+impl Layer {
+    const MASK: u64 = (1 << 48) - 1;
+    const HALF_MASK: u64 = (1 << 24) - 1;
+
+    pub const SOLVED_TOP: Self = Self::from_pieces_const([0, 0, 8, 1, 1, 9, 2, 2, 10, 3, 3, 11]);
+    pub const SOLVED_BOTTOM: Self =
+        Self::from_pieces_const([12, 4, 4, 13, 5, 5, 14, 6, 6, 15, 7, 7]);
+
+    const fn from_pieces_const(slots: [u8; 12]) -> Self {
+        let mut data = 0;
+        let mut i = 0;
+        while i < 12 {
+            assert!(slots[i] < 16);
+            data |= (slots[i] as u64) << (4 * i);
+            i += 1;
+        }
+
+        Self { data }
+    }
+
+    /// Constructs a layer that starts with the given pieces at slot 0, in order.
+    ///
+    /// Returns `None` if the pieces don't take up exactly 12 slots.
+    pub fn from_pieces(pieces: impl IntoIterator<Item = Piece>) -> Option<Self> {
+        let mut data = 0;
+        let mut slot = 0;
+        for piece in pieces {
+            for _ in 0..piece.width() {
+                if slot >= 12 {
+                    return None;
+                }
+                data |= (piece.u8() as u64) << (4 * slot);
+                slot += 1;
+            }
+        }
+
+        (slot == 12).then_some(Self { data })
+    }
+
+    #[inline]
+    pub const fn piece_at(self, slot: u8) -> Piece {
+        debug_assert!(slot < 12);
+        // SAFETY: We only take 4 bits.
+        unsafe { Piece::from_u8_unchecked(((self.data >> (4 * slot)) & 0b1111) as u8) }
+    }
+
+    /// Turns the layer `amount` twelfths of a turn clockwise.
+    #[inline]
+    pub const fn turn(self, amount: i8) -> Self {
+        let shift = 4 * amount.rem_euclid(12) as u32;
+        Self {
+            data: ((self.data << shift) | (self.data >> ((48 - shift) % 48))) & Self::MASK,
+        }
+    }
+
+    /// Whether slot `slot` contains the beginning of a piece (i.e., there's no
+    /// corner crossing the line between `slot - 1` and `slot`).
+    #[inline]
+    pub const fn starts_piece(self, slot: u8) -> bool {
+        self.piece_at(slot).u8() != self.piece_at((slot + 11) % 12).u8()
+    }
+
+    /// Whether the slice can go through this layer.
+    #[inline]
+    pub const fn can_slice(self) -> bool {
+        self.starts_piece(0) && self.starts_piece(6)
+    }
+
+    /// The pieces of the layer in order, starting at the first piece that
+    /// begins at slot 0 or after.
+    pub fn pieces(self) -> impl Iterator<Item = Piece> {
+        let start = if self.starts_piece(0) { 0 } else { 1 };
+        (start..12 + start)
+            .map(move |slot| (slot, self.piece_at(slot % 12)))
+            .filter(move |&(slot, _)| self.starts_piece(slot % 12))
+            .map(|(_, piece)| piece)
+    }
+
+    pub fn shape(self) -> Shape {
+        Shape::of(self)
+    }
+}
+
+impl fmt::Debug for Layer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries((0..12).map(|slot| self.piece_at(slot)))
+            .finish()
+    }
+}
+
+/// A [Square-1](https://www.worldcubeassociation.org/results/rankings/sq1/)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Square1 {
+    pub top: Layer,
+    pub bottom: Layer,
+    /// Whether the right half of the middle layer is turned 180° with respect
+    /// to the left half.
+    pub flipped_middle: bool,
+}
+
+impl Square1 {
+    pub const SOLVED: Self = Self {
+        top: Layer::SOLVED_TOP,
+        bottom: Layer::SOLVED_BOTTOM,
+        flipped_middle: false,
+    };
+
+    /// Whether the slice can be done, namely, that no corner is crossing the
+    /// slice in either layer.
+    #[inline]
+    pub const fn can_slice(self) -> bool {
+        self.top.can_slice() && self.bottom.can_slice()
+    }
+
+    /// Whether `mov` can be applied to this state.
+    pub const fn is_legal(self, mov: Move) -> bool {
+        match mov {
+            Move::Turn { .. } => true,
+            Move::Slice => self.can_slice(),
+        }
+    }
+
+    /// Applies `mov`, or returns `None` if it is an illegal slice.
+    pub const fn try_mov_single(self, mov: Move) -> Option<Self> {
+        match mov {
+            Move::Turn { top, bottom } => Some(Self {
+                top: self.top.turn(top),
+                bottom: self.bottom.turn(bottom),
+                flipped_middle: self.flipped_middle,
+            }),
+            Move::Slice => {
+                if !self.can_slice() {
+                    return None;
+                }
+
+                let (top, bottom) = (self.top.data, self.bottom.data);
+                Some(Self {
+                    top: Layer {
+                        data: (top & !Layer::HALF_MASK) | (bottom >> 24),
+                    },
+                    bottom: Layer {
+                        data: (bottom & Layer::HALF_MASK) | ((top & Layer::HALF_MASK) << 24),
+                    },
+                    flipped_middle: !self.flipped_middle,
+                })
+            }
+        }
+    }
+
+    /// # Panics
+    ///
+    /// If `mov` is an illegal slice.
+    pub fn mov_single(self, mov: Move) -> Self {
+        self.try_mov_single(mov)
+            .unwrap_or_else(|| panic!("Can't slice when a corner is in the way ({self:?})"))
+    }
+
+    /// # Panics
+    ///
+    /// If any of the moves is an illegal slice. See [`Self::try_mov`] for a
+    /// non-panicking version.
+    pub fn mov(mut self, alg: impl IntoIterator<Item = Move>) -> Self {
+        for mov in alg {
+            self = self.mov_single(mov);
+        }
+        self
+    }
+
+    /// Applies every move of `alg`, or returns `None` if any of the slices is
+    /// illegal.
+    pub fn try_mov(mut self, alg: impl IntoIterator<Item = Move>) -> Option<Self> {
+        for mov in alg {
+            self = self.try_mov_single(mov)?;
+        }
+        Some(self)
+    }
+
+    /// Whether every slice of `alg` is legal, when applied from this state.
+    pub fn is_legal_alg(self, alg: impl IntoIterator<Item = Move>) -> bool {
+        self.try_mov(alg).is_some()
+    }
+
+    pub fn is_solved(self) -> bool {
+        self == Self::SOLVED
+    }
+
+    /// The parity of the permutation of the pieces, read in order from the
+    /// top layer and then the bottom one (see [`Layer::pieces`]).
+    ///
+    /// It is not preserved by moves in general, but it is while the puzzle is
+    /// in cube shape and aligned for slicing, so it has to be fixed before
+    /// getting there.
+    pub fn parity(self) -> bool {
+        let pieces: Vec<_> = self.top.pieces().chain(self.bottom.pieces()).collect();
+        let mut parity = false;
+        for (i, p1) in pieces.iter().enumerate() {
+            for p2 in &pieces[i + 1..] {
+                parity ^= p1 > p2;
+            }
+        }
+
+        parity
+    }
+
+    /// Generates a uniformly random state, where every shape is weighted by
+    /// how many states it has (as done by the WCA).
+    pub fn random_with_rng(rng: &mut impl rand::Rng) -> Self {
+        use rand::seq::SliceRandom as _;
+
+        // We shuffle all pieces and fill the top layer first and the bottom
+        // layer after, retrying if a corner would be split between layers.
+        // Then, each layer is turned randomly, but only accepted if the first
+        // piece ends up at slot 0. That way, every state has exactly one way
+        // of being generated, so the distribution is uniform.
+        let random_layer = |rng: &mut _, pieces: &[Piece]| -> Option<Layer> {
+            let layer = Layer::from_pieces(pieces.iter().copied())?;
+            let turned = layer.turn(rand::Rng::random_range(rng, 0..12));
+            (turned.pieces().next() == Some(pieces[0])).then_some(turned)
+        };
+
+        loop {
+            let mut pieces: [Piece; 16] = std::array::from_fn(|i| Piece::from_u8(i as u8));
+            pieces.shuffle(rng);
+
+            let mut width = 0;
+            let Some(split) = pieces.iter().position(|piece| {
+                width += piece.width();
+                width >= 12
+            }) else {
+                continue;
+            };
+
+            if width != 12 {
+                continue;
+            }
+
+            let (top, bottom) = pieces.split_at(split + 1);
+            let (Some(top), Some(bottom)) = (random_layer(rng, top), random_layer(rng, bottom))
+            else {
+                continue;
+            };
+
+            return Self {
+                top,
+                bottom,
+                flipped_middle: rng.random(),
+            };
+        }
+    }
+
+    pub fn random() -> Self {
+        Self::random_with_rng(&mut rand::rng())
+    }
+
+    /// Writes the top and bottom layers unrolled, as seen from the front.
+    ///
+    /// Each layer is written as a row of caps (the U or D stickers) and a row
+    /// of sides, one cell per slot, with a `|` marking the slice. The middle
+    /// layer is in between them.
+    ///
+    /// If `color_scheme` is `None`, stickers are written as face letters
+    /// instead.
+    pub fn write(
+        self,
+        f: &mut fmt::Formatter<'_>,
+        color_scheme: Option<ColorScheme>,
+    ) -> fmt::Result {
+        let sticker = |f: &mut fmt::Formatter<'_>, face: Face| match color_scheme {
+            Some(color_scheme) => write!(f, "{}", "██".color((color_scheme)(face))),
+            None => write!(f, "{face}{face}"),
+        };
+
+        let write_row = |f: &mut fmt::Formatter<'_>, layer: Layer, caps: bool| {
+            for slot in 0..12 {
+                if slot == 6 {
+                    f.write_char('|')?;
+                }
+
+                let piece = layer.piece_at(slot);
+                let face = if caps {
+                    piece.cap()
+                } else {
+                    let half = !layer.starts_piece(slot) as u8;
+                    piece.side(half)
+                };
+                sticker(f, face)?;
+            }
+
+            f.write_char('\n')
+        };
+
+        write_row(f, self.top, true)?;
+        write_row(f, self.top, false)?;
+
+        // The left half of the middle layer is always in place, the right half
+        // shows its back side if it is flipped.
+        let right = if self.flipped_middle {
+            Face::B
+        } else {
+            Face::F
+        };
+        for _ in 0..6 {
+            sticker(f, Face::F)?;
+        }
+        f.write_char('|')?;
+        for _ in 0..6 {
+            sticker(f, right)?;
+        }
+        f.write_char('\n')?;
+
+        write_row(f, self.bottom, false)?;
+        write_row(f, self.bottom, true)
+    }
+}
+
+pub type ColorScheme = fn(Face) -> Rgb;
+
+pub const DEFAULT_COLOR_SCHEME: ColorScheme = |face| match face {
+    Face::U => Rgb(250, 250, 250),
+    Face::D => Rgb(255, 224, 0),
+    Face::F => Rgb(109, 242, 116),
+    Face::R => Rgb(217, 39, 39),
+    Face::B => Rgb(79, 123, 212),
+    Face::L => Rgb(255, 153, 12),
+};
+
+impl fmt::Display for Square1 {
+    /// Uses [`DEFAULT_COLOR_SCHEME`], or plain letters with the alternate flag
+    /// (`{:#}`).
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let color_scheme = (!f.alternate()).then_some(DEFAULT_COLOR_SCHEME);
+        self.write(f, color_scheme)
+    }
+}
+
+impl norcina_core::Puzzle for Square1 {
+    type Move = Move;
+
+    const SOLVED: Self = Self::SOLVED;
+
+    /// # Panics
+    ///
+    /// If `mov` is a slice and the puzzle can't be sliced.
+    fn apply(self, mov: Move) -> Self {
+        self.mov_single(mov)
+    }
+
+    fn random_state(rng: &mut impl rand::Rng) -> Self {
+        Self::random_with_rng(rng)
+    }
+
+    /// Every turn of either layer (or both), and the slice if it is legal.
+    fn neighbors(self) -> impl Iterator<Item = (Move, Self)> {
+        let turns = (-5..=6)
+            .flat_map(|top| (-5..=6).map(move |bottom| Move::turn(top, bottom)))
+            .filter(|mov| !mov.is_identity());
+
+        turns
+            .chain([Move::Slice])
+            .filter_map(move |mov| Some((mov, self.try_mov_single(mov)?)))
+    }
+
+    /// Consecutive turns can be merged, and consecutive slices cancel.
+    fn is_redundant(previous: Move, next: Move) -> bool {
+        matches!(
+            (previous, next),
+            (Move::Turn { .. }, Move::Turn { .. }) | (Move::Slice, Move::Slice)
+        )
+    }
+
+    fn render(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+#[cfg(feature = "quickcheck")]
+mod quickcheck_impl {
+    use super::*;
+    use quickcheck::{Arbitrary, Gen};
+
+    impl Arbitrary for Square1 {
+        fn arbitrary(g: &mut Gen) -> Self {
+            let moves = <Vec<Move>>::arbitrary(g);
+            let mut state = Square1::SOLVED;
+            for mov in moves {
+                if let Some(new_state) = state.try_mov_single(mov) {
+                    state = new_state;
+                }
+            }
+            state
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{SeedableRng as _, rngs::SmallRng};
+
+    #[test]
+    fn solved_can_slice() {
+        assert!(Square1::SOLVED.can_slice());
+    }
+
+    #[test]
+    fn slice_twice_is_identity() {
+        let state = Square1::SOLVED.mov([Move::turn(1, 0), Move::Slice, Move::turn(2, -1)]);
+        assert_eq!(state.mov([Move::Slice, Move::Slice]), state);
+    }
+
+    #[test]
+    fn corner_in_the_way_is_illegal() {
+        assert!(!Square1::SOLVED.is_legal_alg([Move::turn(2, 0), Move::Slice]));
+        assert!(!Square1::SOLVED.is_legal_alg([Move::turn(0, 1), Move::Slice]));
+        assert!(Square1::SOLVED.is_legal_alg([Move::turn(3, 3), Move::Slice]));
+    }
+
+    #[test]
+    fn quarter_turns_keep_cube_shape() {
+        let state = Square1::SOLVED.mov([Move::turn(0, -1), Move::Slice, Move::turn(3, 0)]);
+        assert_eq!(state.top.shape(), Square1::SOLVED.top.shape());
+        assert!(state.mov([Move::Slice]).top.shape().is_square());
+    }
+
+    #[test]
+    fn random_states_have_16_pieces() {
+        let mut rng = SmallRng::seed_from_u64(1);
+        for _ in 0..100 {
+            let state = Square1::random_with_rng(&mut rng);
+            let mut pieces: Vec<_> = state.top.pieces().chain(state.bottom.pieces()).collect();
+            pieces.sort();
+            assert_eq!(pieces, (0..16).map(Piece::from_u8).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn parse_and_display_roundtrip() {
+        for mov in [
+            Move::Slice,
+            Move::turn(1, 0),
+            Move::turn(-5, 6),
+            Move::turn(-3, -2),
+        ] {
+            assert_eq!(mov.to_string().parse(), Ok(mov));
+        }
+        assert_eq!(" ( 7, -1 )".parse(), Ok(Move::turn(-5, -1)));
+    }
+
+    #[test]
+    fn parses_algs_with_and_without_spaces() {
+        let alg = Alg {
+            moves: vec![Move::turn(1, 0), Move::Slice, Move::turn(3, 3), Move::Slice],
+        };
+        for s in [
+            "(1,0) / (3,3) /",
+            "(1,0)/ (3,3)/",
+            "(1, 0)/(3, 3)/",
+            " (1,0)/(3,3)/ ",
+        ] {
+            assert_eq!(mov::parse_alg(s), Ok(alg.clone()), "{s:?}");
+        }
+        assert!(mov::parse_alg("(1,0)/ 3,3/").is_err());
+        assert!(mov::parse_alg("(1,0").is_err());
+    }
+
+    #[cfg(feature = "quickcheck")]
+    quickcheck::quickcheck! {
+        fn move_and_inverse_is_identity(state: Square1, mov: Move) -> bool {
+            match state.try_mov_single(mov) {
+                Some(moved) => {
+                    use norcina_core::mov::InvertibleMove as _;
+                    moved.mov_single(mov.inverse()) == state
+                }
+                None => true,
+            }
+        }
+    }
+}
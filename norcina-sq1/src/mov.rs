@@ -0,0 +1,128 @@
+use std::{fmt, str::FromStr};
+
+/// A move of a Square-1, in WCA notation.
+///
+/// A scramble alternates between turns of the top and bottom layers, written
+/// `(top, bottom)`, and slices, written `/`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Move {
+    /// Turns the top layer `top` twelfths of a turn clockwise and the bottom
+    /// layer `bottom` twelfths of a turn clockwise, each seen from its own
+    /// face.
+    ///
+    /// Amounts are always normalized between -5 and 6 (inclusive).
+    Turn { top: i8, bottom: i8 },
+    /// Rotates the right half of the puzzle by 180°. Only legal if no corner
+    /// is crossing the slice, see [`Square1::can_slice`](crate::Square1::can_slice).
+    Slice,
+}
+
+impl Move {
+    /// Normalizes the amounts to be between -5 and 6, as done by the WCA.
+    pub const fn turn(top: i8, bottom: i8) -> Self {
+        Self::Turn {
+            top: normalize(top),
+            bottom: normalize(bottom),
+        }
+    }
+
+    /// Whether the move does nothing, i.e., it is `(0,0)`.
+    pub const fn is_identity(self) -> bool {
+        matches!(self, Self::Turn { top: 0, bottom: 0 })
+    }
+}
+
+/// Maps any amount of twelfths of a turn to the equivalent between -5 and 6.
+pub const fn normalize(amount: i8) -> i8 {
+    (amount as i16 + 5).rem_euclid(12) as i8 - 5
+}
+
+impl norcina_core::Move for Move {}
+impl norcina_core::mov::InvertibleMove for Move {
+    fn inverse(&self) -> Self {
+        match *self {
+            Self::Turn { top, bottom } => Self::turn(-top, -bottom),
+            Self::Slice => Self::Slice,
+        }
+    }
+}
+
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Turn { top, bottom } => write!(f, "({top},{bottom})"),
+            Self::Slice => f.write_str("/"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseMoveError(String);
+
+impl fmt::Display for ParseMoveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid Square-1 move {:?}", self.0)
+    }
+}
+
+impl std::error::Error for ParseMoveError {}
+
+impl FromStr for Move {
+    type Err = ParseMoveError;
+
+    /// Parses either `/` or `(top,bottom)`, with optional spaces.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseMoveError(s.to_string());
+        let s = s.trim();
+        if s == "/" {
+            return Ok(Self::Slice);
+        }
+
+        let (top, bottom) = s
+            .strip_prefix('(')
+            .and_then(|s| s.strip_suffix(')'))
+            .and_then(|s| s.split_once(','))
+            .ok_or_else(err)?;
+
+        let top = top.trim().parse().map_err(|_| err())?;
+        let bottom = bottom.trim().parse().map_err(|_| err())?;
+        Ok(Self::turn(top, bottom))
+    }
+}
+
+/// Parses a sequence of moves as they are usually written, which can't just
+/// be split by whitespace: slices don't need spaces around them, as in
+/// `(1,0)/ (3,3)/` (csTimer), and turns can have spaces inside, as in
+/// `(1, 0)`.
+pub fn parse_alg(s: &str) -> Result<crate::Alg, ParseMoveError> {
+    let mut moves = Vec::new();
+    let mut rest = s.trim_start();
+    while let Some(first) = rest.chars().next() {
+        let len = match first {
+            '/' => 1,
+            '(' => rest.find(')').map_or(rest.len(), |end| end + 1),
+            _ => rest.find(char::is_whitespace).unwrap_or(rest.len()),
+        };
+
+        moves.push(rest[..len].parse()?);
+        rest = rest[len..].trim_start();
+    }
+
+    Ok(crate::Alg { moves })
+}
+
+#[cfg(feature = "quickcheck")]
+mod quickcheck_impl {
+    use super::*;
+    use quickcheck::{Arbitrary, Gen};
+
+    impl Arbitrary for Move {
+        fn arbitrary(g: &mut Gen) -> Self {
+            if bool::arbitrary(g) {
+                Self::Slice
+            } else {
+                Self::turn(i8::arbitrary(g) % 12, i8::arbitrary(g) % 12)
+            }
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use std::fmt;
+
+/// The faces of a Square-1 in cube shape, used to name the stickers of each
+/// piece.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Face {
+    U,
+    D,
+    F,
+    R,
+    B,
+    L,
+}
+
+impl Face {
+    fn char(self) -> char {
+        match self {
+            Self::U => 'U',
+            Self::D => 'D',
+            Self::F => 'F',
+            Self::R => 'R',
+            Self::B => 'B',
+            Self::L => 'L',
+        }
+    }
+}
+
+impl fmt::Display for Face {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use fmt::Write as _;
+        f.write_char(self.char())
+    }
+}
+
+/// One of the 16 pieces of the top and bottom layers.
+///
+/// Corners take up two 30° slots of a layer, edges take up one.
+///
+/// # Invariants
+///
+/// The bitpattern is always between 0 and 15.
+#[repr(transparent)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Piece {
+    /// Packed field: `----ebii`
+    ///
+    /// e: whether it is an edge
+    /// b: whether it belongs to the bottom layer
+    /// ii: index of the piece in its layer, clockwise (as seen from that layer)
+    data: u8,
+}
+
+impl Piece {
+    /// Corners in solved order, top layer first.
+    pub const CORNERS: [Piece; 8] = [
+        Piece::from_u8(0),
+        Piece::from_u8(1),
+        Piece::from_u8(2),
+        Piece::from_u8(3),
+        Piece::from_u8(4),
+        Piece::from_u8(5),
+        Piece::from_u8(6),
+        Piece::from_u8(7),
+    ];
+
+    /// Edges in solved order, top layer first.
+    pub const EDGES: [Piece; 8] = [
+        Piece::from_u8(8),
+        Piece::from_u8(9),
+        Piece::from_u8(10),
+        Piece::from_u8(11),
+        Piece::from_u8(12),
+        Piece::from_u8(13),
+        Piece::from_u8(14),
+        Piece::from_u8(15),
+    ];
+
+    /// # Panics
+    ///
+    /// If `data` is 16 or more.
+    #[inline]
+    pub const fn from_u8(data: u8) -> Self {
+        assert!(data < 16);
+        Self { data }
+    }
+
+    /// # Safety
+    ///
+    /// `data` must be less than 16.
+    #[inline(always)]
+    pub const unsafe fn from_u8_unchecked(data: u8) -> Self {
+        debug_assert!(data < 16);
+        Self { data }
+    }
+
+    #[inline(always)]
+    pub const fn u8(self) -> u8 {
+        self.data
+    }
+
+    #[inline(always)]
+    pub const fn is_edge(self) -> bool {
+        self.data & 0b1000 != 0
+    }
+
+    #[inline(always)]
+    pub const fn is_corner(self) -> bool {
+        !self.is_edge()
+    }
+
+    /// How many 30° slots the piece takes up in a layer.
+    #[inline(always)]
+    pub const fn width(self) -> u8 {
+        // 0 -> 2 (corner), 1 -> 1 (edge)
+        2 - (self.data >> 3)
+    }
+
+    /// The index of the piece amongst the pieces of the same kind (between 0
+    /// and 7).
+    #[inline(always)]
+    pub const fn index(self) -> u8 {
+        self.data & 0b111
+    }
+
+    /// The layer the piece belongs to when solved.
+    #[inline(always)]
+    pub const fn cap(self) -> Face {
+        if self.data & 0b100 == 0 {
+            Face::U
+        } else {
+            Face::D
+        }
+    }
+
+    /// The color of the side sticker at the given half of the piece.
+    ///
+    /// Halves are ordered in the same direction as the slots of a layer, so
+    /// `half` is either 0 or 1 for corners and always 0 for edges.
+    pub const fn side(self, half: u8) -> Face {
+        use Face::*;
+        const CORNERS: [[Face; 2]; 8] = [
+            [F, R],
+            [R, B],
+            [B, L],
+            [L, F],
+            [F, L],
+            [L, B],
+            [B, R],
+            [R, F],
+        ];
+        const EDGES: [Face; 8] = [R, B, L, F, F, L, B, R];
+
+        debug_assert!(half < self.width());
+        if self.is_edge() {
+            EDGES[self.index() as usize]
+        } else {
+            CORNERS[self.index() as usize][half as usize]
+        }
+    }
+}
+
+impl fmt::Debug for Piece {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Piece({self})")
+    }
+}
+
+impl fmt::Display for Piece {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_edge() {
+            write!(f, "{}{}", self.cap(), self.side(0))
+        } else {
+            write!(f, "{}{}{}", self.cap(), self.side(0), self.side(1))
+        }
+    }
+}
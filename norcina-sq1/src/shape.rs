@@ -0,0 +1,181 @@
+//! The shape of a Square-1, ignoring which piece is which.
+
+use std::collections::{HashMap, VecDeque};
+
+use crate::{Layer, Move, Square1, solve::PHASE2_SOLVED_TURN};
+
+/// The shape of a layer.
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Shape {
+    /// Packed field: bit `i` is set if a piece starts at slot `i`. The 4 most
+    /// significant bits are always 0.
+    data: u16,
+}
+
+impl Shape {
+    const MASK: u16 = (1 << 12) - 1;
+    const HALF_MASK: u16 = (1 << 6) - 1;
+
+    /// Corners at slots 0-1, 3-4, 6-7 and 9-10. It is the only alignment of
+    /// the square where slices keep it a square.
+    pub const SQUARE: Self = Self {
+        data: 0b1011_0110_1101,
+    };
+
+    pub fn of(layer: Layer) -> Self {
+        let mut data = 0;
+        for slot in 0..12 {
+            data |= (layer.starts_piece(slot) as u16) << slot;
+        }
+        Self { data }
+    }
+
+    #[inline]
+    pub const fn u16(self) -> u16 {
+        self.data
+    }
+
+    #[inline]
+    pub const fn turn(self, amount: i8) -> Self {
+        let shift = amount.rem_euclid(12) as u32;
+        Self {
+            data: ((self.data << shift) | (self.data >> ((12 - shift) % 12))) & Self::MASK,
+        }
+    }
+
+    #[inline]
+    pub const fn can_slice(self) -> bool {
+        self.data & 0b0000_0100_0001 == 0b0000_0100_0001
+    }
+
+    /// Whether the layer is a square, regardless of how it is turned.
+    pub fn is_square(self) -> bool {
+        self.canonical() == Self::SQUARE.canonical()
+    }
+
+    /// The smallest shape amongst every turn of `self`, so that turns of the
+    /// same layer have the same canonical shape.
+    pub fn canonical(self) -> Self {
+        (0..12).map(|amount| self.turn(amount)).min().unwrap()
+    }
+
+    /// Applies a slice to a pair of top and bottom shapes. Same as
+    /// [`Square1::try_mov_single`](crate::Square1::try_mov_single) with
+    /// [`Move::Slice`](crate::Move::Slice).
+    pub const fn slice(top: Self, bottom: Self) -> Option<(Self, Self)> {
+        if !top.can_slice() || !bottom.can_slice() {
+            return None;
+        }
+
+        Some((
+            Self {
+                data: (top.data & !Self::HALF_MASK) | (bottom.data >> 6),
+            },
+            Self {
+                data: (bottom.data & Self::HALF_MASK) | ((top.data & Self::HALF_MASK) << 6),
+            },
+        ))
+    }
+}
+
+/// Stores the minimum number of slices needed to get to the phase 2 shape
+/// (see [`solve`](crate::solve)), for every pair of top and bottom shapes and
+/// parity (see [`Square1::parity`]).
+#[derive(Debug, Clone)]
+pub struct ShapeTable {
+    distances: HashMap<ShapeKey, u8>,
+}
+
+/// The shapes of the top and bottom layers, and the parity of the state.
+pub type ShapeKey = (Shape, Shape, bool);
+
+pub fn key(state: Square1) -> ShapeKey {
+    (state.top.shape(), state.bottom.shape(), state.parity())
+}
+
+impl ShapeTable {
+    /// Does a breadth-first search from the phase 2 shape, where turns are
+    /// free and slices count as one move. Takes a few milliseconds.
+    ///
+    /// How moves change the parity only depends on the shape, so we only need
+    /// to keep track of one state per key.
+    pub fn generate() -> Self {
+        let start = Square1::SOLVED.mov_single(PHASE2_SOLVED_TURN);
+        let mut distances = HashMap::from([(key(start), 0)]);
+        let mut queue = VecDeque::from([(start, 0)]);
+
+        let free_moves = [
+            Move::turn(1, 0),
+            Move::turn(-1, 0),
+            Move::turn(0, 1),
+            Move::turn(0, -1),
+        ];
+
+        while let Some((state, distance)) = queue.pop_front() {
+            if distances[&key(state)] < distance {
+                continue;
+            }
+
+            for mov in free_moves {
+                let next = state.mov_single(mov);
+                if distances.get(&key(next)).is_none_or(|&d| d > distance) {
+                    distances.insert(key(next), distance);
+                    queue.push_front((next, distance));
+                }
+            }
+
+            if let Some(next) = state.try_mov_single(Move::Slice)
+                && distances.get(&key(next)).is_none_or(|&d| d > distance + 1)
+            {
+                distances.insert(key(next), distance + 1);
+                queue.push_back((next, distance + 1));
+            }
+        }
+
+        Self { distances }
+    }
+
+    /// The number of slices needed to get to the phase 2 shape.
+    pub fn distance(&self, state: Square1) -> u8 {
+        self.distances[&key(state)]
+    }
+
+    pub fn len(&self) -> usize {
+        self.distances.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.distances.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solved_layers_are_squares() {
+        assert_eq!(Square1::SOLVED.top.shape(), Shape::SQUARE);
+        assert!(Square1::SOLVED.bottom.shape().is_square());
+        assert_ne!(Square1::SOLVED.bottom.shape(), Shape::SQUARE);
+    }
+
+    #[test]
+    fn shape_slice_matches_state_slice() {
+        let state = Square1::SOLVED.mov([Move::turn(1, 0), Move::Slice]);
+        let (top, bottom) = Shape::slice(
+            Square1::SOLVED.top.shape().turn(1),
+            Square1::SOLVED.bottom.shape(),
+        )
+        .unwrap();
+        assert_eq!((top, bottom), (state.top.shape(), state.bottom.shape()));
+    }
+
+    #[test]
+    fn every_shape_is_reachable_in_at_most_7_slices() {
+        let table = ShapeTable::generate();
+        assert_eq!(table.len(), 38610);
+        assert!(table.distances.values().all(|&distance| distance <= 7));
+    }
+}
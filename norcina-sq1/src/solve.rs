@@ -0,0 +1,569 @@
+//! Two-phase solver, used to generate random-state scrambles.
+//!
+//! 1. Get both layers to a square, with the right parity, using [`ShapeTable`].
+//! 2. Solve the permutation while staying in the square (see
+//!    [`phase2_moves`]).
+//!
+//! Both phases minimize the number of slices, which is what the WCA uses for
+//! the length of Square-1 scrambles. The final solution is not optimal, but it
+//! is usually between 10 and 15 slices long.
+//!
+//! # Resources
+//! - Jaap's Square-1 page: <https://www.jaapsch.net/puzzles/square1.htm>
+
+use std::sync::LazyLock;
+
+use norcina_core::{
+    Alg,
+    math::{
+        choose, combination_rank, combination_unrank, fac, permutation_rank, permutation_unrank,
+    },
+    mov::InvertibleMove as _,
+};
+
+use crate::{
+    Move, Piece, Square1,
+    shape::{self, ShapeTable},
+};
+
+/// Every state of phase 2 is the square-square shape, turned so that a slice
+/// keeps it that way.
+///
+/// The solved state is not like this, the bottom layer needs to be turned once
+/// more: `Square1::SOLVED.mov([PHASE2_SOLVED_TURN])`.
+pub const PHASE2_SOLVED_TURN: Move = Move::turn(0, -1);
+
+/// Turns that keep the puzzle in a phase 2 state once followed by a slice.
+///
+/// In a phase 2 state both layers are squares that start either with a corner
+/// (like [`PHASE2_SOLVED_TURN`]) or with an edge at slot 0, so they can be
+/// quarter turns of each layer, or quarter turns plus one slot more that
+/// change which kind of piece is first. Without the latter, corners and edges
+/// would always move in pairs.
+///
+/// The first 16 keep the kind of piece that is first, the last 16 change it.
+pub fn phase2_moves(edge_first: bool) -> [(i8, i8); 32] {
+    let offset = if edge_first { -1 } else { 1 };
+    std::array::from_fn(|i| {
+        let extra = if i < 16 { 0 } else { offset };
+        let (top, bottom) = (3 * (i % 16 / 4) as i8, 3 * (i % 4) as i8);
+        match Move::turn(top + extra, bottom + extra) {
+            Move::Turn { top, bottom } => (top, bottom),
+            Move::Slice => unreachable!(),
+        }
+    })
+}
+
+/// Number of permutations of the 8 corners or the 8 edges.
+const PERMUTATIONS: usize = fac(8);
+/// Number of ways to split 8 corners or 8 edges between the two layers, see
+/// [`index_layers`].
+const LAYERS: usize = choose(8, 4);
+
+/// Whether the layers of a phase 2 state start with an edge at slot 0.
+fn is_edge_first(state: Square1) -> bool {
+    state.top.piece_at(0).is_edge()
+}
+
+/// Tables needed to solve a Square-1.
+#[derive(Debug, Clone)]
+pub struct Tables {
+    shapes: ShapeTable,
+    /// The resulting permutation of the corners after each of the
+    /// [`phase2_moves`] (followed by a slice). See [`move_index`].
+    corner_moves: Vec<u16>,
+    /// Same as `corner_moves`, for edges.
+    edge_moves: Vec<u16>,
+    /// The resulting [layers](index_layers) of the corners after each of the
+    /// [`phase2_moves`]. See [`move_index`].
+    corner_layer_moves: Vec<u8>,
+    /// Same as `corner_layer_moves`, for edges.
+    edge_layer_moves: Vec<u8>,
+    /// The phase 2 states that are one turn away from being solved, along with
+    /// that turn.
+    goals: Vec<(Phase2, Move)>,
+    /// Minimum number of [`phase2_moves`] to solve the corners, which layer
+    /// each edge is in and the middle layer, up to a turn. Indexed by
+    /// [`table_index`].
+    corners: Vec<u8>,
+    /// Same as `corners`, swapping corners and edges.
+    edges: Vec<u8>,
+}
+
+impl Tables {
+    /// Takes a few seconds.
+    pub fn generate() -> Self {
+        let (corner_moves, edge_moves) = generate_move_tables();
+        let corner_layer_moves = generate_layer_move_table(&corner_moves);
+        let edge_layer_moves = generate_layer_move_table(&edge_moves);
+        let goals = goals();
+
+        Self {
+            shapes: ShapeTable::generate(),
+            corners: generate_phase2_table(
+                (&corner_moves, &edge_layer_moves),
+                goals
+                    .iter()
+                    .map(|(goal, _)| (goal.corners, goal.edge_layers, *goal)),
+            ),
+            edges: generate_phase2_table(
+                (&edge_moves, &corner_layer_moves),
+                goals
+                    .iter()
+                    .map(|(goal, _)| (goal.edges, goal.corner_layers, *goal)),
+            ),
+            corner_moves,
+            edge_moves,
+            corner_layer_moves,
+            edge_layer_moves,
+            goals,
+        }
+    }
+
+    fn phase2_heuristic(&self, state: Phase2) -> u8 {
+        let corners = self.corners[table_index(state.corners, state.edge_layers, state)];
+        let edges = self.edges[table_index(state.edges, state.corner_layers, state)];
+        corners.max(edges)
+    }
+
+    fn phase2_mov(&self, state: Phase2, mov: usize) -> Phase2 {
+        Phase2 {
+            corners: self.corner_moves[move_index(state.corners as usize, state.edge_first, mov)],
+            edges: self.edge_moves[move_index(state.edges as usize, state.edge_first, mov)],
+            corner_layers: self.corner_layer_moves
+                [move_index(state.corner_layers as usize, state.edge_first, mov)],
+            edge_layers: self.edge_layer_moves
+                [move_index(state.edge_layers as usize, state.edge_first, mov)],
+            flipped_middle: !state.flipped_middle,
+            edge_first: state.edge_first ^ (mov >= 16),
+        }
+    }
+}
+
+/// Index of the move tables for the `mov`th of [`phase2_moves`], applied to
+/// the given coordinate.
+fn move_index(coordinate: usize, edge_first: bool, mov: usize) -> usize {
+    (2 * coordinate + edge_first as usize) * 32 + mov
+}
+
+/// Index of the pruning tables, between 0 and 4 * [`PERMUTATIONS`] * [`LAYERS`].
+fn table_index(permutation: u16, layers: u8, state: Phase2) -> usize {
+    let coordinate = permutation as usize * LAYERS + layers as usize;
+    4 * coordinate + 2 * state.flipped_middle as usize + state.edge_first as usize
+}
+
+/// A phase 2 state, reduced to what the search needs.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct Phase2 {
+    /// Index of the permutation of the corners, ordered from slot 0 of the
+    /// top layer and then the bottom one.
+    corners: u16,
+    /// Same as `corners`, for edges.
+    edges: u16,
+    /// Which corners belong to the top layer, see [`index_layers`].
+    corner_layers: u8,
+    /// Same as `corner_layers`, for edges.
+    edge_layers: u8,
+    flipped_middle: bool,
+    edge_first: bool,
+}
+
+impl Phase2 {
+    fn of(state: Square1) -> Self {
+        let [corners, edges] = phase2_permutations(state);
+        Self {
+            corners: index_permutation(corners) as u16,
+            edges: index_permutation(edges) as u16,
+            corner_layers: index_layers(corners) as u8,
+            edge_layers: index_layers(edges) as u8,
+            flipped_middle: state.flipped_middle,
+            edge_first: is_edge_first(state),
+        }
+    }
+}
+
+/// The indices of the corners and of the edges, in order from slot 0 of the
+/// top layer and then the bottom one.
+fn phase2_permutations(state: Square1) -> [[u8; 8]; 2] {
+    [Piece::is_corner, Piece::is_edge].map(|is_kind| {
+        let mut pieces = state
+            .top
+            .pieces()
+            .chain(state.bottom.pieces())
+            .filter(move |&piece| is_kind(piece));
+
+        std::array::from_fn(|_| {
+            pieces
+                .next()
+                .expect("There are 8 pieces of each kind")
+                .index()
+        })
+    })
+}
+
+/// Generates the corner and edge move tables.
+///
+/// A move always sends the piece at some position to the same position, so we
+/// find out where each position goes once, and then apply that to every
+/// permutation.
+fn generate_move_tables() -> (Vec<u16>, Vec<u16>) {
+    let mut corner_moves = vec![0; 2 * PERMUTATIONS * 32];
+    let mut edge_moves = vec![0; 2 * PERMUTATIONS * 32];
+
+    for edge_first in [false, true] {
+        let mut start = Square1::SOLVED.mov_single(PHASE2_SOLVED_TURN);
+        if edge_first {
+            start = start.mov_single(Move::turn(1, 1));
+        }
+
+        let before = phase2_permutations(start);
+        for (mov, (top, bottom)) in phase2_moves(edge_first).into_iter().enumerate() {
+            let after = phase2_permutations(start.mov([Move::turn(top, bottom), Move::Slice]));
+            // Position `i` after the move has the piece that was at position
+            // `sources[_][i]` before.
+            let sources: [[usize; 8]; 2] = std::array::from_fn(|kind| {
+                after[kind].map(|piece| before[kind].iter().position(|&p| p == piece).unwrap())
+            });
+
+            for permutation in 0..PERMUTATIONS {
+                let pieces = permutation_from_index(permutation);
+                let index = move_index(permutation, edge_first, mov);
+                corner_moves[index] = index_permutation(sources[0].map(|i| pieces[i])) as u16;
+                edge_moves[index] = index_permutation(sources[1].map(|i| pieces[i])) as u16;
+            }
+        }
+    }
+
+    (corner_moves, edge_moves)
+}
+
+/// Generates the move table of [`index_layers`] from the move table of the
+/// permutation of the same kind of piece.
+fn generate_layer_move_table(permutation_moves: &[u16]) -> Vec<u8> {
+    let mut layer_moves = vec![0; 2 * LAYERS * 32];
+    for layers in 0..LAYERS {
+        let permutation = index_permutation(permutation_from_layers(layers));
+        for edge_first in [false, true] {
+            for mov in 0..32 {
+                let next = permutation_moves[move_index(permutation, edge_first, mov)];
+                layer_moves[move_index(layers, edge_first, mov)] =
+                    index_layers(permutation_from_index(next as usize)) as u8;
+            }
+        }
+    }
+
+    layer_moves
+}
+
+/// The states that are one of the final turns away from being solved.
+fn goals() -> Vec<(Phase2, Move)> {
+    let target = Square1::SOLVED.mov_single(PHASE2_SOLVED_TURN);
+    [false, true]
+        .into_iter()
+        .flat_map(|edge_first| phase2_moves(edge_first).map(move |turn| (edge_first, turn)))
+        .filter_map(|(edge_first, (top, bottom))| {
+            let turn = Move::turn(top, bottom);
+            let state = target.mov_single(turn.inverse());
+            // Turns that change the kind of piece that is first only get to
+            // the target from the other alignment, otherwise they leave a
+            // corner across slot 0.
+            let is_phase2 = state.can_slice() && is_edge_first(state) == edge_first;
+            is_phase2.then_some((Phase2::of(state), turn))
+        })
+        .collect()
+}
+
+/// Tables shared by [`solve`] and [`scramble`], generated the first time they
+/// are needed.
+static TABLES: LazyLock<Tables> = LazyLock::new(Tables::generate);
+
+/// Finds a sequence of moves that solves `state`.
+pub fn solve(state: Square1) -> Alg<Move> {
+    solve_with_tables(state, &TABLES)
+}
+
+/// Generates a random-state scramble, as the WCA does.
+pub fn scramble(rng: &mut impl rand::Rng) -> Alg<Move> {
+    scramble_with_tables(rng, &TABLES)
+}
+
+pub fn scramble_with_tables(rng: &mut impl rand::Rng, tables: &Tables) -> Alg<Move> {
+    let state = Square1::random_with_rng(rng);
+    solve_with_tables(state, tables).reversed()
+}
+
+pub fn solve_with_tables(initial: Square1, tables: &Tables) -> Alg<Move> {
+    let mut moves = solve_shape(initial, &tables.shapes);
+    let state = initial.mov(moves.iter().copied());
+    moves.extend(solve_phase2(Phase2::of(state), tables));
+    moves.push(PHASE2_SOLVED_TURN.inverse());
+
+    debug_assert!(initial.mov(moves.iter().copied()).is_solved());
+    Alg {
+        moves: simplify(moves),
+    }
+}
+
+/// Finds the moves to get to the square-square shape, aligned for slicing and
+/// with the same parity as the phase 2 solved state, with the fewest slices.
+///
+/// Parity can't be changed in phase 2, so it has to be fixed here.
+fn solve_shape(mut state: Square1, shapes: &ShapeTable) -> Vec<Move> {
+    let target = shape::key(Square1::SOLVED.mov_single(PHASE2_SOLVED_TURN));
+    let turns = || (-5..=6).flat_map(|top| (-5..=6).map(move |bottom| Move::turn(top, bottom)));
+
+    let mut moves = Vec::new();
+    let mut distance = shapes.distance(state);
+    while distance > 0 {
+        let (turn, next) = turns()
+            .find_map(|turn| {
+                let next = state.mov_single(turn).try_mov_single(Move::Slice)?;
+                (shapes.distance(next) == distance - 1).then_some((turn, next))
+            })
+            .expect("Some slice brings the shape closer to a square");
+
+        moves.extend([turn, Move::Slice]);
+        state = next;
+        distance -= 1;
+    }
+
+    let align = turns()
+        .find(|&turn| shape::key(state.mov_single(turn)) == target)
+        .expect("Turns are free in the shape table");
+    moves.push(align);
+
+    moves
+}
+
+/// Solves a phase 2 state up to the [final turn](PHASE2_SOLVED_TURN), using
+/// IDA*.
+fn solve_phase2(state: Phase2, tables: &Tables) -> Vec<Move> {
+    fn search(
+        state: Phase2,
+        depth: u8,
+        bound: u8,
+        path: &mut Vec<(i8, i8)>,
+        tables: &Tables,
+    ) -> Option<Move> {
+        let heuristic = tables.phase2_heuristic(state);
+        if heuristic == 0
+            && let Some(&(_, turn)) = tables.goals.iter().find(|(goal, _)| *goal == state)
+        {
+            return Some(turn);
+        }
+
+        if depth + heuristic > bound {
+            return None;
+        }
+
+        for (mov, turn) in phase2_moves(state.edge_first).into_iter().enumerate() {
+            // `(0,0) /` twice in a row cancels, and `(a,b) / (0,0) /` is
+            // better done as a single `(a,b) /` one slice earlier.
+            if turn == (0, 0) && depth > 0 {
+                continue;
+            }
+
+            path.push(turn);
+            if let Some(turn) = search(
+                tables.phase2_mov(state, mov),
+                depth + 1,
+                bound,
+                path,
+                tables,
+            ) {
+                return Some(turn);
+            }
+            path.pop();
+        }
+
+        None
+    }
+
+    let mut path = Vec::new();
+    for bound in tables.phase2_heuristic(state).. {
+        if let Some(final_turn) = search(state, 0, bound, &mut path, tables) {
+            return path
+                .into_iter()
+                .flat_map(|(top, bottom)| [Move::turn(top, bottom), Move::Slice])
+                .chain([final_turn])
+                .collect();
+        }
+    }
+
+    unreachable!()
+}
+
+/// Merges consecutive turns and removes the ones that do nothing.
+fn simplify(moves: Vec<Move>) -> Vec<Move> {
+    let mut output: Vec<Move> = Vec::with_capacity(moves.len());
+    for mov in moves {
+        match (output.last_mut(), mov) {
+            (
+                Some(Move::Turn { top, bottom }),
+                Move::Turn {
+                    top: next_top,
+                    bottom: next_bottom,
+                },
+            ) => {
+                *output.last_mut().unwrap() = Move::turn(*top + next_top, *bottom + next_bottom);
+            }
+            _ => output.push(mov),
+        }
+
+        if output.last().is_some_and(|mov| mov.is_identity()) {
+            output.pop();
+        }
+    }
+
+    output
+}
+
+/// Breadth-first search over a permutation and the layers of the other kind
+/// of pieces, using their move tables.
+fn generate_phase2_table(
+    (permutation_moves, layer_moves): (&[u16], &[u8]),
+    goals: impl IntoIterator<Item = (u16, u8, Phase2)>,
+) -> Vec<u8> {
+    let mut buffer = vec![u8::MAX; 4 * PERMUTATIONS * LAYERS];
+    for (permutation, layers, goal) in goals {
+        buffer[table_index(permutation, layers, goal)] = 0;
+    }
+
+    for depth in 1.. {
+        let mut complete = true;
+        for i in 0..buffer.len() {
+            if buffer[i] != depth - 1 {
+                continue;
+            }
+
+            let (flipped_middle, edge_first) = (i / 2 % 2 != 0, i % 2 != 0);
+            let (permutation, layers) = (i / 4 / LAYERS, i / 4 % LAYERS);
+            for mov in 0..32 {
+                let next = Phase2 {
+                    flipped_middle: !flipped_middle,
+                    edge_first: edge_first ^ (mov >= 16),
+                    ..Phase2::default()
+                };
+
+                let new_index = table_index(
+                    permutation_moves[move_index(permutation, edge_first, mov)],
+                    layer_moves[move_index(layers, edge_first, mov)],
+                    next,
+                );
+
+                if buffer[new_index] > depth {
+                    buffer[new_index] = depth;
+                    complete = false;
+                }
+            }
+        }
+
+        if complete {
+            break;
+        }
+    }
+
+    buffer
+}
+
+/// Index of the permutation of `pieces` (each one between 0 and 7), between 0
+/// and 8!.
+fn index_permutation(pieces: [u8; 8]) -> usize {
+    permutation_rank(&pieces)
+}
+
+fn permutation_from_index(index: usize) -> [u8; 8] {
+    permutation_unrank(index)
+}
+
+/// Index of which positions have pieces of the top layer (the ones with
+/// index less than 4), between 0 and [`LAYERS`].
+fn index_layers(pieces: [u8; 8]) -> usize {
+    combination_rank(pieces.map(|piece| piece < 4))
+}
+
+/// A permutation with the given [layers](index_layers).
+fn permutation_from_layers(index: usize) -> [u8; 8] {
+    let mut pieces = [0; 8];
+    let (mut top, mut bottom) = (0, 4);
+    for (piece, is_top) in pieces.iter_mut().zip(combination_unrank::<8>(index, 4)) {
+        let next = if is_top { &mut top } else { &mut bottom };
+        *piece = *next;
+        *next += 1;
+    }
+
+    pieces
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng as _, SeedableRng as _, rngs::SmallRng};
+
+    #[test]
+    fn index_from_index_is_identity() {
+        for i in (0..PERMUTATIONS).step_by(89) {
+            assert_eq!(index_permutation(permutation_from_index(i)), i);
+        }
+
+        for i in 0..LAYERS {
+            assert_eq!(index_layers(permutation_from_layers(i)), i);
+        }
+    }
+
+    #[test]
+    fn phase2_moves_keep_square_shape() {
+        let state = Square1::SOLVED.mov_single(PHASE2_SOLVED_TURN);
+        for (top, bottom) in phase2_moves(false) {
+            let next = state.mov([Move::turn(top, bottom), Move::Slice]);
+            assert!(next.top.shape().is_square());
+            assert!(next.bottom.shape().is_square());
+            assert_eq!(next.parity(), state.parity());
+
+            for (top, bottom) in phase2_moves(is_edge_first(next)) {
+                let next = next.mov([Move::turn(top, bottom), Move::Slice]);
+                assert!(next.top.shape().is_square());
+                assert!(next.bottom.shape().is_square());
+                assert_eq!(next.parity(), state.parity());
+            }
+        }
+    }
+
+    #[test]
+    fn move_tables_match_moves() {
+        let (corner_moves, edge_moves) = generate_move_tables();
+        let edge_layer_moves = generate_layer_move_table(&edge_moves);
+        let mut rng = SmallRng::seed_from_u64(3);
+        let mut state = Square1::SOLVED.mov_single(PHASE2_SOLVED_TURN);
+        for _ in 0..50 {
+            let before = Phase2::of(state);
+            let mov = rng.random_range(0..32);
+            let (top, bottom) = phase2_moves(before.edge_first)[mov];
+            state = state.mov([Move::turn(top, bottom), Move::Slice]);
+
+            let after = Phase2::of(state);
+            let index = |coordinate| move_index(coordinate as usize, before.edge_first, mov);
+            assert_eq!(corner_moves[index(before.corners)], after.corners);
+            assert_eq!(edge_moves[index(before.edges)], after.edges);
+            assert_eq!(
+                edge_layer_moves[index(before.edge_layers as u16)],
+                after.edge_layers
+            );
+            assert_eq!(after.edge_first, before.edge_first ^ (mov >= 16));
+        }
+    }
+
+    #[test]
+    fn solves_random_states() {
+        let mut rng = SmallRng::seed_from_u64(2025);
+        let tables = Tables::generate();
+        for _ in 0..10 {
+            let state = Square1::random_with_rng(&mut rng);
+            let solution = solve_with_tables(state, &tables);
+            assert!(state.mov(solution.iter().copied()).is_solved());
+        }
+
+        let scramble = scramble_with_tables(&mut rng, &tables);
+        assert!(Square1::SOLVED.is_legal_alg(scramble.iter().copied()));
+    }
+}
@@ -0,0 +1,188 @@
+use std::fmt;
+
+use crate::{Alg, DynMove, Event, Puzzle as _, clock, cube3, pyraminx, sq1};
+
+/// The state of any of the implemented puzzles.
+///
+/// Moves are given as [`DynMove`]s, which have to be of the same puzzle as the
+/// state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DynPuzzle {
+    Cube3(cube3::Cube),
+    Pyraminx(pyraminx::Pyraminx),
+    Square1(sq1::Square1),
+    Clock(clock::Clock),
+}
+
+impl DynPuzzle {
+    /// The solved state of the puzzle used in `event`, or `None` if that
+    /// puzzle is not implemented yet.
+    pub const fn for_event(event: Event) -> Option<Self> {
+        Some(match event {
+            Event::Cube3 | Event::Blind3 | Event::FewestMoves | Event::OneHanded => {
+                Self::Cube3(cube3::Cube::SOLVED)
+            }
+            Event::Pyraminx => Self::Pyraminx(pyraminx::Pyraminx::SOLVED),
+            Event::Square1 => Self::Square1(sq1::Square1::SOLVED),
+            Event::Clock => Self::Clock(clock::Clock::SOLVED),
+            _ => return None,
+        })
+    }
+
+    pub fn is_solved(self) -> bool {
+        match self {
+            Self::Cube3(state) => state.is_solved(),
+            Self::Pyraminx(state) => state.is_solved(),
+            Self::Square1(state) => state.is_solved(),
+            Self::Clock(state) => state.is_solved(),
+        }
+    }
+
+    /// Applies a single move.
+    ///
+    /// Fails if the move is of another puzzle, or if it can't be done (e.g., a
+    /// Square-1 slice when a corner is in the way).
+    pub fn apply_single(self, mov: DynMove) -> Result<Self, ApplyError> {
+        match (self, mov) {
+            (Self::Cube3(state), DynMove::Cube3(mov)) => Ok(Self::Cube3(state.apply(mov))),
+            (Self::Pyraminx(state), DynMove::Pyraminx(mov)) => Ok(Self::Pyraminx(state.apply(mov))),
+            (Self::Square1(state), DynMove::Square1(sq1_mov)) => state
+                .try_mov_single(sq1_mov)
+                .map(Self::Square1)
+                .ok_or(ApplyError::Illegal(mov)),
+            (Self::Clock(state), DynMove::Clock(mov)) => Ok(Self::Clock(state.apply(mov))),
+            _ => Err(ApplyError::WrongPuzzle(mov)),
+        }
+    }
+
+    /// Applies every move of `alg`, in order. See [`DynPuzzle::apply_single`].
+    pub fn apply(mut self, alg: &Alg<DynMove>) -> Result<Self, ApplyError> {
+        for &mov in alg.iter() {
+            self = self.apply_single(mov)?;
+        }
+
+        Ok(self)
+    }
+
+    /// Parses an algorithm in the notation of this puzzle.
+    pub fn parse_alg(self, s: &str) -> Result<Alg<DynMove>, ParseAlgError> {
+        fn parse<M: std::str::FromStr<Err: fmt::Display>>(
+            s: &str,
+            variant: fn(M) -> DynMove,
+        ) -> Result<Alg<DynMove>, ParseAlgError> {
+            let alg: Alg<M> = s
+                .parse()
+                .map_err(|err: M::Err| ParseAlgError(err.to_string()))?;
+            Ok(Alg {
+                moves: alg.moves.into_iter().map(variant).collect(),
+            })
+        }
+
+        match self {
+            Self::Cube3(_) => parse(s, DynMove::Cube3),
+            Self::Pyraminx(_) => parse(s, DynMove::Pyraminx),
+            Self::Square1(_) => {
+                let alg = sq1::mov::parse_alg(s).map_err(|err| ParseAlgError(err.to_string()))?;
+                Ok(Alg {
+                    moves: alg.moves.into_iter().map(DynMove::Square1).collect(),
+                })
+            }
+            Self::Clock(_) => parse(s, DynMove::Clock),
+        }
+    }
+
+    /// Writes the state with the default colors of the puzzle.
+    pub fn render(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Cube3(state) => state.render(f),
+            Self::Pyraminx(state) => state.render(f),
+            Self::Square1(state) => state.render(f),
+            Self::Clock(state) => state.render(f),
+        }
+    }
+}
+
+impl fmt::Display for DynPuzzle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.render(f)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApplyError {
+    /// The move is of a different puzzle.
+    WrongPuzzle(DynMove),
+    /// The move can't be done in the current state.
+    Illegal(DynMove),
+}
+
+impl fmt::Display for ApplyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::WrongPuzzle(mov) => write!(f, "move {mov} is of a different puzzle"),
+            Self::Illegal(mov) => write!(f, "move {mov} can't be done in this state"),
+        }
+    }
+}
+
+impl std::error::Error for ApplyError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseAlgError(String);
+
+impl fmt::Display for ParseAlgError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for ParseAlgError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scrambles_apply_to_their_event() {
+        let mut rng = rand::rng();
+        for event in [Event::Cube3, Event::Square1, Event::Clock] {
+            let puzzle = DynPuzzle::for_event(event).unwrap();
+            let scramble = crate::gen_scramble(event, &mut rng);
+            let parsed = puzzle.parse_alg(&scramble.to_string()).unwrap();
+            assert_eq!(parsed, scramble);
+
+            let scrambled = puzzle.apply(&scramble).unwrap();
+            assert!(!scrambled.is_solved());
+        }
+    }
+
+    #[test]
+    fn rejects_moves_of_other_puzzles() {
+        let cube = DynPuzzle::for_event(Event::Cube3).unwrap();
+        let alg = DynPuzzle::for_event(Event::Pyraminx)
+            .unwrap()
+            .parse_alg("R U' l")
+            .unwrap();
+        assert_eq!(cube.apply(&alg), Err(ApplyError::WrongPuzzle(alg.moves[0])));
+        assert!(cube.parse_alg("R U x").is_err());
+    }
+
+    #[test]
+    fn parses_cstimer_square1_scrambles() {
+        let sq1 = DynPuzzle::for_event(Event::Square1).unwrap();
+        let wca = "(1,3) / (-4,2) / (-3,3) / (0,-3) / (1,4) / (0,3) / (-4,-1) / (3,-2) / (0,-3) / (4,5) / (4,3) / (2,0)";
+        let cstimer = "(1,3)/ (-4,2)/ (-3,3)/ (0,-3)/ (1,4)/ (0,3)/ (-4,-1)/ (3,-2)/ (0,-3)/ (4,5)/ (4,3)/ (2,0)";
+        let alg = sq1.parse_alg(wca).unwrap();
+        assert_eq!(alg.len(), 23);
+        assert_eq!(sq1.parse_alg(cstimer), Ok(alg.clone()));
+        assert_eq!(sq1.parse_alg(&cstimer.replace(',', ", ")), Ok(alg.clone()));
+        assert!(!sq1.apply(&alg).unwrap().is_solved());
+    }
+
+    #[test]
+    fn rejects_illegal_square1_moves() {
+        let sq1 = DynPuzzle::for_event(Event::Square1).unwrap();
+        let alg = sq1.parse_alg("(2,0) /").unwrap();
+        assert!(matches!(sq1.apply(&alg), Err(ApplyError::Illegal(_))));
+    }
+}
@@ -2,20 +2,32 @@ use std::fmt;
 
 use enum_dispatch::enum_dispatch;
 
+pub use norcina_clock as clock;
 pub use norcina_core::*;
 pub use norcina_cube_n as cube_n;
 pub use norcina_cube3 as cube3;
 pub use norcina_pyraminx as pyraminx;
+pub use norcina_sq1 as sq1;
+
+mod dyn_puzzle;
+pub use dyn_puzzle::{ApplyError, DynPuzzle, ParseAlgError};
 
 #[enum_dispatch(Move, MoveDisplay)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DynMove {
     Cube3(cube3::Move),
+    Pyraminx(pyraminx::mov::Move),
+    Square1(sq1::Move),
+    Clock(clock::Move),
 }
 
 impl fmt::Display for DynMove {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::Cube3(mov) => fmt::Display::fmt(mov, f),
+            Self::Pyraminx(mov) => fmt::Display::fmt(mov, f),
+            Self::Square1(mov) => fmt::Display::fmt(mov, f),
+            Self::Clock(mov) => fmt::Display::fmt(mov, f),
         }
     }
 }
@@ -32,7 +44,7 @@ impl fmt::Display for DynMove {
 /// - Megaminx: 77 (10 + 1 rotation, 7 times)
 ///
 /// This follows [csTimer's](https://cstimer.net) conventions.
-pub fn gen_scramble(event: Event, _rng: &mut impl rand::Rng) -> Alg<DynMove> {
+pub fn gen_scramble(event: Event, rng: &mut impl rand::Rng) -> Alg<DynMove> {
     match event {
         Event::Cube3 => Alg {
             moves: cube3::algs::pll::T
@@ -40,6 +52,18 @@ pub fn gen_scramble(event: Event, _rng: &mut impl rand::Rng) -> Alg<DynMove> {
                 .map(DynMove::Cube3)
                 .collect(),
         },
+        Event::Square1 => Alg {
+            moves: sq1::solve::scramble(rng)
+                .into_iter()
+                .map(DynMove::Square1)
+                .collect(),
+        },
+        Event::Clock => Alg {
+            moves: clock::solve::scramble(rng)
+                .into_iter()
+                .map(DynMove::Clock)
+                .collect(),
+        },
         _ => Alg { moves: Vec::new() },
     }
 }
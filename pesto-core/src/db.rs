@@ -1,18 +1,33 @@
+mod migrations;
+
 use std::{
+    collections::{HashMap, HashSet},
     fs, iter,
     path::{Path, PathBuf},
     time::Duration,
 };
 
 use color_eyre::eyre::{self, Context};
+use jiff::Zoned;
 use norcina::Event;
-use rusqlite::{Connection, types::ValueRef};
+use rusqlite::{Connection, OptionalExtension as _, types::ValueRef};
 
 use crate::{
     event::{CustomEvent, MaybeCustomEvent, Session},
-    solve::{Penalty, Solve},
+    inspection,
+    pb::{self, Pb, PbEvent, Record},
+    solve::{Penalty, Solve, Split},
+    stats::Window,
 };
 
+/// What to do with the solves of a session that is deleted.
+#[derive(Debug, Clone)]
+pub enum OrphanedSolves {
+    Delete,
+    /// Moves them to another session of the same event.
+    MoveTo(Session),
+}
+
 #[derive(Debug)]
 pub struct Db {
     conn: Connection,
@@ -24,56 +39,40 @@ impl Db {
     }
 
     pub fn new_at_path(path: Option<impl AsRef<Path>>) -> eyre::Result<Self> {
-        let conn = if let Some(path) = path {
-            fs::create_dir_all(path.as_ref().parent().unwrap())?;
-            Connection::open(path)
+        let path = if let Some(path) = path {
+            path.as_ref().to_path_buf()
         } else if cfg!(debug_assertions) {
-            Connection::open("./main.db")
+            PathBuf::from("./main.db")
         } else {
-            let proj_dirs = proj_dirs();
-            let dir = proj_dirs.data_dir();
-            fs::create_dir_all(&dir)?;
-            Connection::open(dir.join("main.db"))
-        }?;
-
-        conn.execute_batch(
-        "BEGIN;
-        CREATE TABLE IF NOT EXISTS solve (
-            id INTEGER PRIMARY KEY,
-            --- Amount of time it took to solve
-            time_ms INTEGER NOT NULL,
-            --- Datetime when the solve ended, according to [RFC 8536](https://datatracker.ietf.org/doc/html/rfc8536)
-            end_date INTEGER NOT NULL,
-            scramble TEXT NOT NULL,
-            penalty INTEGER NOT NULL,
-            event_id INTEGER NOT NULL,
-            session_id INTEGER NOT NULL
-        );
-        CREATE TABLE IF NOT EXISTS custom_event (
-            --- Cannot be 0-16 (inclusive), minimum allowed value is 17. 0-16 indicate official WCA events.
-            id INTEGER PRIMARY KEY,
-            name TEXT NOT NULL,
-            --- Between 0-16, index of an official WCA event. Can be null.
-            scramble_type INTEGER
-        );
-        CREATE TABLE IF NOT EXISTS custom_session (
-            --- Cannot be 0 in the database. An index of 0 refers to the 'main' session.
-            id INTEGER NOT NULL,
-            name TEXT NOT NULL,
-            event_id INTEGER NOT NULL,
-            PRIMARY KEY (id, event_id)
-        );
-        COMMIT;",
-    )
-    .wrap_err("Couldn't initialize database")?;
+            proj_dirs().data_dir().join("main.db")
+        };
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let mut conn = Connection::open(&path)?;
+        let backup = path.with_extension(format!(
+            "v{}.bak",
+            migrations::version(&conn).wrap_err("Couldn't read database version")?
+        ));
+        migrations::migrate(&mut conn, Some(&backup)).wrap_err("Couldn't migrate database")?;
 
         Ok(Self { conn })
     }
 
+    /// An empty database that is not saved anywhere.
+    #[cfg(test)]
+    pub(crate) fn in_memory() -> Self {
+        let mut conn = Connection::open_in_memory().unwrap();
+        migrations::migrate(&mut conn, None).unwrap();
+        Self { conn }
+    }
+
     pub fn get_events(&mut self) -> eyre::Result<Vec<MaybeCustomEvent>> {
         let mut stmt = self
             .conn
-            .prepare("SELECT id, name, scramble_type FROM custom_event")?;
+            .prepare("SELECT id, name, scramble_type FROM custom_event ORDER BY position, id")?;
 
         let custom_events = stmt
             .query_map([], |row| {
@@ -96,9 +95,9 @@ impl Db {
         &mut self,
         event: &MaybeCustomEvent,
     ) -> eyre::Result<Vec<Session>> {
-        let mut stmt = self
-            .conn
-            .prepare("SELECT id, name FROM custom_session WHERE event_id = $1")?;
+        let mut stmt = self.conn.prepare(
+            "SELECT id, name FROM custom_session WHERE event_id = $1 ORDER BY position, id",
+        )?;
 
         let iter = stmt.query_map([event.id()], |row| {
             Ok(Session::Custom {
@@ -125,57 +124,813 @@ impl Db {
             .collect()
     }
 
-    pub fn insert_solve(
+    /// Creates a custom event, with the next free id.
+    pub fn create_custom_event(
+        &mut self,
+        name: &str,
+        scramble_type: Option<Event>,
+    ) -> eyre::Result<CustomEvent> {
+        let name = self.validate_event_name(name, None)?;
+        let id = self.conn.query_row(
+            "SELECT MAX(COALESCE(MAX(id) + 1, 0), $1) FROM custom_event",
+            [CustomEvent::FIRST_ID],
+            |row| row.get(0),
+        )?;
+
+        self.conn.execute(
+            "INSERT INTO custom_event (id, name, scramble_type, position)
+            VALUES ($1, $2, $3, (SELECT COALESCE(MAX(position) + 1, 0) FROM custom_event))",
+            (id, &name, scramble_type.map(Event::id)),
+        )?;
+
+        Ok(CustomEvent {
+            id,
+            name,
+            scramble_type,
+        })
+    }
+
+    pub fn rename_custom_event(&mut self, id: usize, name: &str) -> eyre::Result<()> {
+        let name = self.validate_event_name(name, Some(id))?;
+        self.update_custom_event(id, "UPDATE custom_event SET name = $1 WHERE id = $2", name)
+    }
+
+    pub fn set_custom_event_scramble_type(
+        &mut self,
+        id: usize,
+        scramble_type: Option<Event>,
+    ) -> eyre::Result<()> {
+        self.update_custom_event(
+            id,
+            "UPDATE custom_event SET scramble_type = $1 WHERE id = $2",
+            scramble_type.map(Event::id),
+        )
+    }
+
+    fn update_custom_event(
+        &mut self,
+        id: usize,
+        sql: &str,
+        value: impl rusqlite::ToSql,
+    ) -> eyre::Result<()> {
+        validate_custom_event_id(id)?;
+        if self.conn.execute(sql, (value, id))? == 0 {
+            eyre::bail!("No custom event with id {id}");
+        }
+
+        Ok(())
+    }
+
+    /// Deletes a custom event, along with its sessions, solves and settings.
+    pub fn delete_custom_event(&mut self, id: usize) -> eyre::Result<()> {
+        validate_custom_event_id(id)?;
+
+        let tx = self.conn.transaction()?;
+        if tx.execute("DELETE FROM custom_event WHERE id = $1", [id])? == 0 {
+            eyre::bail!("No custom event with id {id}");
+        }
+
+        for table in ["custom_session", "solve", "personal_best", "event_settings"] {
+            tx.execute(&format!("DELETE FROM {table} WHERE event_id = $1"), [id])?;
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Sets the order of the custom events. Official events always go first.
+    pub fn reorder_custom_events(&mut self, ids: &[usize]) -> eyre::Result<()> {
+        let tx = self.conn.transaction()?;
+        for (position, &id) in ids.iter().enumerate() {
+            validate_custom_event_id(id)?;
+            tx.execute(
+                "UPDATE custom_event SET position = $1 WHERE id = $2",
+                [position, id],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// A trimmed `name`, if it's not empty and no other event is called like
+    /// that.
+    fn validate_event_name(&self, name: &str, id: Option<usize>) -> eyre::Result<String> {
+        let name = validate_name(name)?;
+        let is_official = Event::ALL
+            .iter()
+            .any(|event| event.short_name().eq_ignore_ascii_case(name));
+
+        let is_taken = self.conn.query_row(
+            "SELECT COUNT(*) > 0 FROM custom_event WHERE name = $1 COLLATE NOCASE AND id IS NOT $2",
+            (name, id),
+            |row| row.get::<_, bool>(0),
+        )?;
+
+        if is_official || is_taken {
+            eyre::bail!("There is already an event called {name:?}");
+        }
+
+        Ok(name.to_string())
+    }
+
+    /// Creates a session for `event`, with the next free id.
+    pub fn create_session(
+        &mut self,
+        event: &MaybeCustomEvent,
+        name: &str,
+    ) -> eyre::Result<Session> {
+        let name = self.validate_session_name(event, name, None)?;
+        let id = self.conn.query_row(
+            "SELECT COALESCE(MAX(id) + 1, 1) FROM custom_session WHERE event_id = $1",
+            [event.id()],
+            |row| row.get(0),
+        )?;
+
+        self.conn.execute(
+            "INSERT INTO custom_session (id, name, event_id, position)
+            VALUES ($1, $2, $3, (
+                SELECT COALESCE(MAX(position) + 1, 0) FROM custom_session WHERE event_id = $3
+            ))",
+            (id, &name, event.id()),
+        )?;
+
+        Ok(Session::Custom { name, id })
+    }
+
+    pub fn rename_session(
+        &mut self,
+        event: &MaybeCustomEvent,
+        session: &Session,
+        name: &str,
+    ) -> eyre::Result<()> {
+        let id = validate_custom_session(session)?;
+        let name = self.validate_session_name(event, name, Some(id))?;
+        let updated = self.conn.execute(
+            "UPDATE custom_session SET name = $1 WHERE id = $2 AND event_id = $3",
+            (name, id, event.id()),
+        )?;
+
+        if updated == 0 {
+            eyre::bail!("No session with id {id}");
+        }
+
+        Ok(())
+    }
+
+    /// Deletes a session, and either deletes its solves or moves them to
+    /// another session.
+    pub fn delete_session(
+        &mut self,
+        event: &MaybeCustomEvent,
+        session: &Session,
+        solves: OrphanedSolves,
+    ) -> eyre::Result<()> {
+        let id = validate_custom_session(session)?;
+        if let OrphanedSolves::MoveTo(target) = &solves
+            && target.id() == id
+        {
+            eyre::bail!("Can't move the solves of a session to itself");
+        }
+
+        let tx = self.conn.transaction()?;
+        if let OrphanedSolves::MoveTo(Session::Custom { id: target, .. }) = &solves {
+            let exists = tx.query_row(
+                "SELECT COUNT(*) > 0 FROM custom_session WHERE id = $1 AND event_id = $2",
+                [target, &event.id()],
+                |row| row.get::<_, bool>(0),
+            )?;
+
+            if !exists {
+                eyre::bail!("No session with id {target} to move the solves to");
+            }
+        }
+
+        let deleted = tx.execute(
+            "DELETE FROM custom_session WHERE id = $1 AND event_id = $2",
+            [id, event.id()],
+        )?;
+
+        if deleted == 0 {
+            eyre::bail!("No session with id {id}");
+        }
+
+        match &solves {
+            OrphanedSolves::Delete => tx.execute(
+                "DELETE FROM solve WHERE session_id = $1 AND event_id = $2",
+                [id, event.id()],
+            )?,
+            OrphanedSolves::MoveTo(target) => tx.execute(
+                "UPDATE solve SET session_id = $1 WHERE session_id = $2 AND event_id = $3",
+                [target.id(), id, event.id()],
+            )?,
+        };
+
+        tx.execute(
+            "DELETE FROM personal_best WHERE session_id = $1 AND event_id = $2",
+            [id, event.id()],
+        )?;
+        tx.commit()?;
+
+        if let OrphanedSolves::MoveTo(target) = solves {
+            self.rebuild_pbs(event, &target)?;
+        }
+
+        Ok(())
+    }
+
+    /// Sets the order of the sessions of `event`, by id. The main session
+    /// always goes first.
+    pub fn reorder_sessions(
+        &mut self,
+        event: &MaybeCustomEvent,
+        ids: &[usize],
+    ) -> eyre::Result<()> {
+        let tx = self.conn.transaction()?;
+        for (position, &id) in ids.iter().enumerate() {
+            tx.execute(
+                "UPDATE custom_session SET position = $1 WHERE id = $2 AND event_id = $3",
+                [position, id, event.id()],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// A trimmed `name`, if it's not empty and no other session of `event`
+    /// is called like that.
+    fn validate_session_name(
         &self,
+        event: &MaybeCustomEvent,
+        name: &str,
+        id: Option<usize>,
+    ) -> eyre::Result<String> {
+        let name = validate_name(name)?;
+        let is_taken = name.eq_ignore_ascii_case(Session::Main.name())
+            || self.conn.query_row(
+                "SELECT COUNT(*) > 0 FROM custom_session
+                WHERE event_id = $1 AND name = $2 COLLATE NOCASE AND id IS NOT $3",
+                (event.id(), name, id),
+                |row| row.get::<_, bool>(0),
+            )?;
+
+        if is_taken {
+            eyre::bail!("There is already a session called {name:?}");
+        }
+
+        Ok(name.to_string())
+    }
+
+    /// Whether to use WCA inspection for `event`.
+    pub fn inspection(&self, event: &MaybeCustomEvent) -> eyre::Result<bool> {
+        let inspection = self
+            .conn
+            .query_row(
+                "SELECT inspection FROM event_settings WHERE event_id = $1",
+                [event.id()],
+                |row| row.get::<_, Option<bool>>(0),
+            )
+            .optional()?
+            .flatten();
+
+        Ok(inspection.unwrap_or_else(|| inspection::is_enabled_by_default(event)))
+    }
+
+    pub fn set_inspection(
+        &mut self,
+        event: &MaybeCustomEvent,
+        inspection: bool,
+    ) -> eyre::Result<()> {
+        self.conn.execute(
+            "INSERT INTO event_settings (event_id, inspection) VALUES ($1, $2)
+            ON CONFLICT (event_id) DO UPDATE SET inspection = excluded.inspection",
+            (event.id(), inspection),
+        )?;
+
+        Ok(())
+    }
+
+    /// The names of the phases that solves of `event` are split into, or
+    /// nothing if they are not split.
+    pub fn phases(&self, event: &MaybeCustomEvent) -> eyre::Result<Vec<String>> {
+        let phases = self
+            .conn
+            .query_row(
+                "SELECT phases FROM event_settings WHERE event_id = $1",
+                [event.id()],
+                |row| row.get::<_, Option<String>>(0),
+            )
+            .optional()?
+            .flatten();
+
+        Ok(phases
+            .map(|phases| phases.split(PHASE_SEPARATOR).map(str::to_string).collect())
+            .unwrap_or_default())
+    }
+
+    /// Sets the phases of `event`, like `["Memo", "Execution"]`. There have to
+    /// be at least two, or none to stop splitting solves.
+    pub fn set_phases(&mut self, event: &MaybeCustomEvent, phases: &[&str]) -> eyre::Result<()> {
+        let phases = phases
+            .iter()
+            .map(|&phase| validate_phase_name(phase))
+            .collect::<eyre::Result<Vec<_>>>()?;
+
+        if phases.len() == 1 {
+            eyre::bail!("There have to be at least two phases");
+        }
+
+        for (i, phase) in phases.iter().enumerate() {
+            if phases[..i]
+                .iter()
+                .any(|other| other.eq_ignore_ascii_case(phase))
+            {
+                eyre::bail!("There already is a phase called {phase:?}");
+            }
+        }
+
+        let phases = (!phases.is_empty()).then(|| phases.join(PHASE_SEPARATOR));
+        self.conn.execute(
+            "INSERT INTO event_settings (event_id, phases) VALUES ($1, $2)
+            ON CONFLICT (event_id) DO UPDATE SET phases = excluded.phases",
+            (event.id(), phases),
+        )?;
+
+        Ok(())
+    }
+
+    /// Inserts a solve, and returns the records it broke.
+    pub fn insert_solve(
+        &mut self,
         solve: Solve,
         event: &MaybeCustomEvent,
         session: &Session,
+    ) -> eyre::Result<PbEvent> {
+        self.in_transaction(|db| {
+            if db.has_untracked_pbs(event.id(), session.id())? {
+                db.rebuild_pbs(event, session)?;
+            }
+
+            let times = |pbs: Vec<Pb>| {
+                pbs.into_iter()
+                    .map(|pb| (pb.record, pb.time))
+                    .collect::<HashMap<_, _>>()
+            };
+            let previous = times(db.get_pbs(event, session)?);
+            let event_previous = times(db.get_event_pbs(event)?);
+
+            db.insert_solve_without_pbs(&solve, event.id(), session.id())?;
+
+            let last =
+                db.get_last_solves_by_id(event.id(), session.id(), Some(Window::AO100.size))?;
+            let pb_event = pb::broken_records(
+                &last,
+                |record| previous.get(&record).copied(),
+                |record| event_previous.get(&record).copied(),
+            );
+            for broken in &pb_event.broken {
+                db.insert_pb(
+                    &Pb {
+                        record: broken.record,
+                        time: broken.time,
+                        end_date: solve.end_date.clone(),
+                    },
+                    event.id(),
+                    session.id(),
+                )?;
+            }
+
+            Ok(pb_event)
+        })
+    }
+
+    /// Inserts a solve without updating the PBs, for when many solves are
+    /// inserted at once (and then [`Db::rebuild_pbs`] is called).
+    pub(crate) fn insert_solve_without_pbs(
+        &self,
+        solve: &Solve,
+        event_id: usize,
+        session_id: usize,
     ) -> eyre::Result<()> {
-        let mut stmt = self.conn.prepare(
+        self.conn.execute(
             "INSERT INTO solve
-                (time_ms, end_date, scramble, penalty, event_id, session_id)
-            VALUES ($1,   $2,       $3,       $4,      $5,       $6)",
+                (time_ms, end_date, scramble, penalty, event_id, session_id, comment)
+            VALUES ($1,   $2,       $3,       $4,      $5,       $6,         $7)",
+            (
+                solve.time.as_millis() as u64,
+                solve.end_date.to_string(),
+                &solve.scramble,
+                solve.penalty.index(),
+                event_id,
+                session_id,
+                &solve.comment,
+            ),
         )?;
 
-        stmt.execute((
-            solve.time.as_millis() as u64,
-            solve.end_date.to_string(),
-            solve.scramble,
-            solve.penalty.index(),
-            event.id(),
-            session.id(),
-        ))?;
+        let id = self.conn.last_insert_rowid();
+        for (position, split) in solve.splits.iter().enumerate() {
+            self.conn.execute(
+                "INSERT INTO solve_split (solve_id, position, name, time_ms)
+                VALUES ($1,       $2,       $3,   $4)",
+                (id, position, &split.name, split.time.as_millis() as u64),
+            )?;
+        }
 
         Ok(())
     }
 
+    /// Runs `f` in a transaction, which is rolled back if it fails.
+    pub(crate) fn in_transaction<T>(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> eyre::Result<T>,
+    ) -> eyre::Result<T> {
+        self.conn.execute_batch("BEGIN")?;
+        match f(self) {
+            Ok(value) => {
+                self.conn.execute_batch("COMMIT")?;
+                Ok(value)
+            }
+            Err(err) => {
+                self.conn.execute_batch("ROLLBACK")?;
+                Err(err)
+            }
+        }
+    }
+
+    /// The length and end date (as a Unix timestamp in seconds) of every
+    /// solve of `event`, to find duplicates.
+    pub(crate) fn solve_keys(&self, event: &MaybeCustomEvent) -> eyre::Result<HashSet<(u64, i64)>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT time_ms, end_date FROM solve WHERE event_id = $1")?;
+        let iter = stmt.query_map([event.id()], |row| {
+            let end_date: Zoned = row
+                .get::<_, String>(1)?
+                .parse()
+                .expect("valid RFC 9557 format in db");
+            Ok((row.get(0)?, end_date.timestamp().as_second()))
+        })?;
+
+        iter.map(|v| v.map_err(Into::into)).collect()
+    }
+
+    fn insert_pb(&self, pb: &Pb, event_id: usize, session_id: usize) -> eyre::Result<()> {
+        self.conn.execute(
+            "INSERT INTO personal_best
+                (record, time_ms, end_date, event_id, session_id)
+            VALUES ($1,     $2,      $3,       $4,       $5)",
+            (
+                pb.record.to_string(),
+                pb.time.as_millis() as u64,
+                pb.end_date.to_string(),
+                event_id,
+                session_id,
+            ),
+        )?;
+
+        Ok(())
+    }
+
+    /// Whether a session has solves from before PBs were tracked. Once one of
+    /// them is not a DNF, there is a single PB, so this is only true once.
+    fn has_untracked_pbs(&self, event_id: usize, session_id: usize) -> eyre::Result<bool> {
+        Ok(self.conn.query_row(
+            "SELECT NOT EXISTS (
+                SELECT * FROM personal_best WHERE event_id = $1 AND session_id = $2
+            ) AND EXISTS (
+                SELECT * FROM solve
+                WHERE event_id = $1 AND session_id = $2 AND deleted = 0 AND penalty != $3
+            )",
+            (event_id, session_id, Penalty::DNF.index()),
+            |row| row.get(0),
+        )?)
+    }
+
+    /// Recomputes every PB of a session from its solves.
+    pub fn rebuild_pbs(&mut self, event: &MaybeCustomEvent, session: &Session) -> eyre::Result<()> {
+        self.rebuild_pbs_by_id(event.id(), session.id())
+    }
+
+    fn rebuild_pbs_by_id(&mut self, event_id: usize, session_id: usize) -> eyre::Result<()> {
+        let solves = self.get_solves_by_id(event_id, session_id)?;
+
+        self.conn.execute(
+            "DELETE FROM personal_best WHERE event_id = $1 AND session_id = $2",
+            [event_id, session_id],
+        )?;
+
+        for record in Record::ALL {
+            for pb in pb::progression(&solves, record) {
+                self.insert_pb(&pb, event_id, session_id)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The current PB of each record that has one.
+    pub fn get_pbs(
+        &mut self,
+        event: &MaybeCustomEvent,
+        session: &Session,
+    ) -> eyre::Result<Vec<Pb>> {
+        self.get_pbs_by_id(event.id(), Some(session.id()))
+    }
+
+    /// The best PB of each record among every session of `event`.
+    pub fn get_event_pbs(&mut self, event: &MaybeCustomEvent) -> eyre::Result<Vec<Pb>> {
+        self.get_pbs_by_id(event.id(), None)
+    }
+
+    fn get_pbs_by_id(&self, event_id: usize, session_id: Option<usize>) -> eyre::Result<Vec<Pb>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT record, MIN(time_ms), end_date FROM personal_best
+            WHERE event_id = $1 AND ($2 IS NULL OR session_id = $2)
+            GROUP BY record",
+        )?;
+
+        let iter = stmt.query_map((event_id, session_id), pb_from_row)?;
+        iter.map(|v| v.map_err(Into::into)).collect()
+    }
+
+    /// Every time `record` was broken, in order, to see how it improved.
+    pub fn get_pb_progression(
+        &mut self,
+        event: &MaybeCustomEvent,
+        session: &Session,
+        record: Record,
+    ) -> eyre::Result<Vec<Pb>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT record, time_ms, end_date FROM personal_best
+            WHERE event_id = $1 AND session_id = $2 AND record = $3
+            ORDER BY id",
+        )?;
+
+        let iter = stmt.query_map((event.id(), session.id(), record.to_string()), pb_from_row)?;
+        iter.map(|v| v.map_err(Into::into)).collect()
+    }
+
+    /// Like [`Db::get_pb_progression`], but among every session of `event`:
+    /// each time a session PB was also better than those of every session.
+    pub fn get_event_pb_progression(
+        &mut self,
+        event: &MaybeCustomEvent,
+        record: Record,
+    ) -> eyre::Result<Vec<Pb>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT record, time_ms, end_date FROM personal_best
+            WHERE event_id = $1 AND record = $2
+            ORDER BY {END_INSTANT}, id"
+        ))?;
+
+        let mut progression = Vec::<Pb>::new();
+        for pb in stmt.query_map((event.id(), record.to_string()), pb_from_row)? {
+            let pb = pb?;
+            if progression.last().is_none_or(|best| pb.time < best.time) {
+                progression.push(pb);
+            }
+        }
+
+        Ok(progression)
+    }
+
+    /// The solves of a session that are not deleted, in the order they were
+    /// finished (and in the order they were inserted, if at the same time).
     pub fn get_solves(
         &mut self,
         event: &MaybeCustomEvent,
         session: &Session,
     ) -> eyre::Result<Vec<Solve>> {
-        let mut stmt = self.conn.prepare("SELECT time_ms, end_date, scramble, penalty FROM solve WHERE event_id = $1 AND session_id = $2")?;
-        let iter = stmt.query_map([event.id(), session.id()], |row| {
+        self.get_solves_by_id(event.id(), session.id())
+    }
+
+    fn get_solves_by_id(&mut self, event_id: usize, session_id: usize) -> eyre::Result<Vec<Solve>> {
+        self.get_last_solves_by_id(event_id, session_id, None)
+    }
+
+    /// Like [`Db::get_solves`], but only the `last` ones (or every one, if
+    /// it's `None`).
+    fn get_last_solves_by_id(
+        &self,
+        event_id: usize,
+        session_id: usize,
+        last: Option<usize>,
+    ) -> eyre::Result<Vec<Solve>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT id, time_ms, end_date, scramble, penalty, comment FROM solve
+            WHERE id IN ({})
+            ORDER BY {END_INSTANT}, id",
+            last_solve_ids()
+        ))?;
+        // A negative limit is no limit.
+        let params = (event_id, session_id, last.map_or(-1, |last| last as i64));
+        let iter = stmt.query_map(params, |row| {
             Ok(Solve {
-                time: Duration::from_millis(row.get(0)?),
+                id: Some(row.get(0)?),
+                time: Duration::from_millis(row.get(1)?),
                 end_date: row
-                    .get::<_, String>(1)?
+                    .get::<_, String>(2)?
                     .parse()
-                    .expect("valid RFC 8536 format in db"),
-                scramble: row.get(2)?,
-                penalty: Penalty::from_index(row.get(3)?).expect("Valid penalty index in db"),
+                    .expect("valid RFC 9557 format in db"),
+                scramble: row.get(3)?,
+                penalty: Penalty::from_index(row.get(4)?).expect("Valid penalty index in db"),
+                comment: row.get(5)?,
+                splits: Vec::new(),
             })
         })?;
 
-        iter.map(|v| v.map_err(Into::into)).collect()
+        let mut solves = iter.collect::<rusqlite::Result<Vec<_>>>()?;
+        let mut splits = self.get_splits(params)?;
+        for solve in &mut solves {
+            let id = solve.id.expect("Solves from the db have an id");
+            solve.splits = splits.remove(&id).unwrap_or_default();
+        }
+
+        Ok(solves)
+    }
+
+    /// The splits of the solves selected by [`last_solve_ids`] that have
+    /// them, by solve id.
+    fn get_splits(&self, params: (usize, usize, i64)) -> eyre::Result<HashMap<usize, Vec<Split>>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT solve_id, name, time_ms FROM solve_split
+            WHERE solve_id IN ({})
+            ORDER BY solve_id, position",
+            last_solve_ids()
+        ))?;
+
+        let mut splits = HashMap::<usize, Vec<Split>>::new();
+        let mut rows = stmt.query(params)?;
+        while let Some(row) = rows.next()? {
+            splits.entry(row.get(0)?).or_default().push(Split {
+                name: row.get(1)?,
+                time: Duration::from_millis(row.get(2)?),
+            });
+        }
+
+        Ok(splits)
+    }
+
+    /// The ids of the event and session of a solve.
+    fn solve_location(&self, id: usize) -> eyre::Result<(usize, usize)> {
+        self.conn
+            .query_row(
+                "SELECT event_id, session_id FROM solve WHERE id = $1",
+                [id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .wrap_err_with(|| format!("No solve with id {id}"))
+    }
+
+    /// Changes the penalty of a solve, and rebuilds the PBs of its session.
+    pub fn update_penalty(&mut self, id: usize, penalty: Penalty) -> eyre::Result<()> {
+        self.in_transaction(|db| {
+            let (event_id, session_id) = db.solve_location(id)?;
+            db.conn.execute(
+                "UPDATE solve SET penalty = $1 WHERE id = $2",
+                (penalty.index(), id),
+            )?;
+
+            db.rebuild_pbs_by_id(event_id, session_id)
+        })
+    }
+
+    /// Changes the comment of a solve, or removes it with `None`.
+    pub fn update_comment(&mut self, id: usize, comment: Option<&str>) -> eyre::Result<()> {
+        self.solve_location(id)?;
+        self.conn
+            .execute("UPDATE solve SET comment = $1 WHERE id = $2", (comment, id))?;
+
+        Ok(())
+    }
+
+    /// Marks a solve as deleted. It can be brought back with
+    /// [`Db::restore_solve`] until [`Db::purge_deleted_solves`] is called.
+    pub fn delete_solve(&mut self, id: usize) -> eyre::Result<()> {
+        self.set_deleted(id, true)
+    }
+
+    /// Undoes [`Db::delete_solve`].
+    pub fn restore_solve(&mut self, id: usize) -> eyre::Result<()> {
+        self.set_deleted(id, false)
+    }
+
+    fn set_deleted(&mut self, id: usize, deleted: bool) -> eyre::Result<()> {
+        self.in_transaction(|db| {
+            db.conn
+                .execute("UPDATE solve SET deleted = $1 WHERE id = $2", (deleted, id))?;
+
+            let (event_id, session_id) = db.solve_location(id)?;
+            db.rebuild_pbs_by_id(event_id, session_id)
+        })
+    }
+
+    /// Removes the deleted solves for good.
+    pub fn purge_deleted_solves(&mut self) -> eyre::Result<()> {
+        self.conn
+            .execute("DELETE FROM solve WHERE deleted = 1", ())?;
+        Ok(())
+    }
+
+    /// Moves a solve to another session of the same event.
+    pub fn move_solve_to_session(&mut self, id: usize, session: &Session) -> eyre::Result<()> {
+        self.in_transaction(|db| {
+            let (event_id, old_session_id) = db.solve_location(id)?;
+            if let &Session::Custom { id: target, .. } = session {
+                let exists = db.conn.query_row(
+                    "SELECT COUNT(*) > 0 FROM custom_session WHERE id = $1 AND event_id = $2",
+                    [target, event_id],
+                    |row| row.get::<_, bool>(0),
+                )?;
+
+                if !exists {
+                    eyre::bail!("No session with id {target} in the event of the solve");
+                }
+            }
+
+            db.conn.execute(
+                "UPDATE solve SET session_id = $1 WHERE id = $2",
+                (session.id(), id),
+            )?;
+
+            db.rebuild_pbs_by_id(event_id, old_session_id)?;
+            db.rebuild_pbs_by_id(event_id, session.id())
+        })
     }
 }
 
+/// The `end_date` of a solve as a number that sorts chronologically, since
+/// the text doesn't when the offsets are different. SQLite understands the
+/// date and the offset, but not the time zone in brackets after them.
+const END_INSTANT: &str = "julianday(substr(end_date, 1, instr(end_date || '[', '[') - 1))";
+
+/// A query for the ids of the last `$3` solves of event `$1` and session
+/// `$2` that are not deleted.
+fn last_solve_ids() -> String {
+    format!(
+        "SELECT id FROM solve
+        WHERE event_id = $1 AND session_id = $2 AND deleted = 0
+        ORDER BY {END_INSTANT} DESC, id DESC
+        LIMIT $3"
+    )
+}
+
 fn proj_dirs() -> directories::ProjectDirs {
     directories::ProjectDirs::from("com", "odilf", "pesto")
         .expect("Have a project directory available, in either Windows, MacOS or Linux.")
 }
 
+fn validate_name(name: &str) -> eyre::Result<&str> {
+    let name = name.trim();
+    if name.is_empty() {
+        eyre::bail!("Name can't be empty");
+    }
+
+    Ok(name)
+}
+
+/// Separates the names of the phases in the `phases` column.
+const PHASE_SEPARATOR: &str = ";";
+
+fn validate_phase_name(name: &str) -> eyre::Result<&str> {
+    let name = validate_name(name)?;
+    if name.contains(PHASE_SEPARATOR) || name.contains(':') {
+        eyre::bail!("Phase names can't contain `{PHASE_SEPARATOR}` or `:`");
+    }
+
+    Ok(name)
+}
+
+fn validate_custom_event_id(id: usize) -> eyre::Result<()> {
+    if id < CustomEvent::FIRST_ID {
+        eyre::bail!("Official events can't be changed");
+    }
+
+    Ok(())
+}
+
+fn validate_custom_session(session: &Session) -> eyre::Result<usize> {
+    match session {
+        Session::Main => eyre::bail!("The main session can't be changed"),
+        &Session::Custom { id, .. } => Ok(id),
+    }
+}
+
+fn pb_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Pb> {
+    Ok(Pb {
+        record: row
+            .get::<_, String>(0)?
+            .parse()
+            .expect("valid record in db"),
+        time: Duration::from_millis(row.get(1)?),
+        end_date: row
+            .get::<_, String>(2)?
+            .parse()
+            .expect("valid RFC 9557 format in db"),
+    })
+}
+
 fn official_event_from_sql(value: ValueRef<'_>) -> rusqlite::Result<Option<Event>> {
     Ok(match value {
         ValueRef::Null => None,
@@ -189,3 +944,243 @@ fn official_event_from_sql(value: ValueRef<'_>) -> rusqlite::Result<Option<Event
         }
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn custom_event(db: &mut Db, name: &str) -> MaybeCustomEvent {
+        MaybeCustomEvent::Unofficial(db.create_custom_event(name, None).unwrap())
+    }
+
+    fn insert_times(db: &mut Db, event: &MaybeCustomEvent, session: &Session, secs: &[u64]) {
+        for &secs in secs {
+            let solve = Solve::new(Duration::from_secs(secs), String::new());
+            db.insert_solve(solve, event, session).unwrap();
+        }
+    }
+
+    #[test]
+    fn solves_are_sorted_by_end_date() {
+        let mut db = Db::in_memory();
+        let event = MaybeCustomEvent::Official(Event::Cube3);
+        // Inserted in the opposite order, and in the opposite order as text.
+        for (secs, end_date) in [
+            (2, "2024-01-01T09:00:00+00:00[UTC]"),
+            (1, "2024-01-01T10:59:59.123456789+02:00[+02:00]"),
+            (3, "2024-01-01T09:00:00+00:00[UTC]"),
+        ] {
+            let solve = Solve {
+                end_date: end_date.parse().unwrap(),
+                ..Solve::new(Duration::from_secs(secs), String::new())
+            };
+            db.insert_solve(solve, &event, &Session::Main).unwrap();
+        }
+
+        let times: Vec<_> = db
+            .get_solves(&event, &Session::Main)
+            .unwrap()
+            .iter()
+            .map(|solve| solve.time.as_secs())
+            .collect();
+        assert_eq!(times, [1, 2, 3]);
+    }
+
+    fn single(db: &mut Db, event: &MaybeCustomEvent, session: Option<&Session>) -> Duration {
+        let pbs = match session {
+            Some(session) => db.get_pbs(event, session).unwrap(),
+            None => db.get_event_pbs(event).unwrap(),
+        };
+        pbs.iter()
+            .find(|pb| pb.record == Record::Single)
+            .unwrap()
+            .time
+    }
+
+    #[test]
+    fn event_pbs_are_the_best_of_every_session() {
+        let mut db = Db::in_memory();
+        let event = MaybeCustomEvent::Official(Event::Cube3);
+        let home = db.create_session(&event, "home").unwrap();
+        insert_times(&mut db, &event, &Session::Main, &[20, 15]);
+        insert_times(&mut db, &event, &home, &[12]);
+
+        assert_eq!(
+            single(&mut db, &event, Some(&Session::Main)),
+            Duration::from_secs(15)
+        );
+        assert_eq!(single(&mut db, &event, None), Duration::from_secs(12));
+
+        let solve = |secs| Solve::new(Duration::from_secs(secs), String::new());
+        let session_pb = db.insert_solve(solve(13), &event, &Session::Main).unwrap();
+        // Also the first mo3, which is an event PB too.
+        assert!(!session_pb.get(Record::Single).unwrap().event_pb);
+        assert!(session_pb.is_event_pb());
+        let event_pb = db.insert_solve(solve(11), &event, &Session::Main).unwrap();
+        assert!(event_pb.get(Record::Single).unwrap().event_pb);
+
+        let progression: Vec<_> = db
+            .get_event_pb_progression(&event, Record::Single)
+            .unwrap()
+            .iter()
+            .map(|pb| pb.time.as_secs())
+            .collect();
+        assert_eq!(progression, [20, 15, 12, 11]);
+    }
+
+    #[test]
+    fn pbs_of_untracked_solves_are_built_once() {
+        let mut db = Db::in_memory();
+        let event = MaybeCustomEvent::Official(Event::Cube3);
+        for secs in [10, 12] {
+            let solve = Solve::new(Duration::from_secs(secs), String::new());
+            db.insert_solve_without_pbs(&solve, event.id(), Session::Main.id())
+                .unwrap();
+        }
+
+        assert!(
+            db.has_untracked_pbs(event.id(), Session::Main.id())
+                .unwrap()
+        );
+        let solve = Solve::new(Duration::from_secs(11), String::new());
+        let pb_event = db.insert_solve(solve, &event, &Session::Main).unwrap();
+        assert!(
+            !db.has_untracked_pbs(event.id(), Session::Main.id())
+                .unwrap()
+        );
+
+        // Not a single PB, but the first mo3.
+        assert_eq!(pb_event.broken.len(), 1);
+        assert!(pb_event.get(Record::Window(Window::MO3)).is_some());
+        assert_eq!(
+            single(&mut db, &event, Some(&Session::Main)),
+            Duration::from_secs(10)
+        );
+    }
+
+    #[test]
+    fn custom_events_come_after_official_ones() {
+        let mut db = Db::in_memory();
+        let first = db.create_custom_event("Relay", None).unwrap();
+        let second = db.create_custom_event("Feet", Some(Event::Cube3)).unwrap();
+
+        assert_eq!(first.id, 17);
+        assert_eq!(second.id, 18);
+        assert_eq!(db.get_events().unwrap().len(), Event::ALL.len() + 2);
+    }
+
+    #[test]
+    fn event_names_must_be_free() {
+        let mut db = Db::in_memory();
+        let relay = db.create_custom_event("Relay", None).unwrap();
+
+        assert!(db.create_custom_event(" relay ", None).is_err());
+        assert!(db.create_custom_event("3x3", None).is_err());
+        assert!(db.create_custom_event("  ", None).is_err());
+        assert!(db.rename_custom_event(relay.id, "Relay").is_ok());
+        assert!(db.rename_custom_event(0, "Cube").is_err());
+    }
+
+    #[test]
+    fn session_names_must_be_free() {
+        let mut db = Db::in_memory();
+        let event = custom_event(&mut db, "Relay");
+        let session = db.create_session(&event, "Warmup").unwrap();
+
+        assert!(db.create_session(&event, "warmup").is_err());
+        assert!(db.create_session(&event, "Main").is_err());
+        assert!(db.rename_session(&event, &session, "main").is_err());
+
+        // Other events can have a session with the same name.
+        let other = custom_event(&mut db, "Feet");
+        assert!(db.create_session(&other, "Warmup").is_ok());
+    }
+
+    #[test]
+    fn main_session_cant_be_changed() {
+        let mut db = Db::in_memory();
+        let event = custom_event(&mut db, "Relay");
+
+        assert!(db.rename_session(&event, &Session::Main, "Other").is_err());
+        assert!(
+            db.delete_session(&event, &Session::Main, OrphanedSolves::Delete)
+                .is_err()
+        );
+        assert_eq!(db.get_sessions_of_event(&event).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn deleting_a_session_moves_its_solves() {
+        let mut db = Db::in_memory();
+        let event = custom_event(&mut db, "Relay");
+        let session = db.create_session(&event, "Warmup").unwrap();
+        insert_times(&mut db, &event, &Session::Main, &[20, 30]);
+        insert_times(&mut db, &event, &session, &[10, 40, 15]);
+
+        db.delete_session(&event, &session, OrphanedSolves::MoveTo(Session::Main))
+            .unwrap();
+
+        let solves = db.get_solves(&event, &Session::Main).unwrap();
+        assert_eq!(solves.len(), 5);
+        assert_eq!(db.get_sessions_of_event(&event).unwrap().len(), 1);
+
+        let pbs = db.get_pbs(&event, &Session::Main).unwrap();
+        let single = pbs.iter().find(|pb| pb.record == Record::Single).unwrap();
+        assert_eq!(single.time, Duration::from_secs(10));
+        assert!(db.get_pbs(&event, &session).unwrap().is_empty());
+    }
+
+    #[test]
+    fn solves_cant_be_moved_to_a_missing_session() {
+        let mut db = Db::in_memory();
+        let event = custom_event(&mut db, "Relay");
+        let session = db.create_session(&event, "Warmup").unwrap();
+        insert_times(&mut db, &event, &session, &[10]);
+
+        let missing = Session::Custom {
+            name: "Missing".to_string(),
+            id: session.id() + 1,
+        };
+        assert!(
+            db.delete_session(&event, &session, OrphanedSolves::MoveTo(missing))
+                .is_err()
+        );
+        assert_eq!(db.get_sessions_of_event(&event).unwrap().len(), 2);
+        assert_eq!(db.get_solves(&event, &session).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn solves_are_only_moved_to_sessions_of_their_event() {
+        let mut db = Db::in_memory();
+        let event = custom_event(&mut db, "Relay");
+        let other_event = custom_event(&mut db, "Blind relay");
+        let other_session = db.create_session(&other_event, "Warmup").unwrap();
+        insert_times(&mut db, &event, &Session::Main, &[10]);
+        let id = db.get_solves(&event, &Session::Main).unwrap()[0]
+            .id
+            .unwrap();
+
+        let missing = Session::Custom {
+            name: "Missing".to_string(),
+            id: other_session.id() + 1,
+        };
+        assert!(db.move_solve_to_session(id, &missing).is_err());
+        assert!(db.move_solve_to_session(id, &other_session).is_err());
+        assert_eq!(db.get_solves(&event, &Session::Main).unwrap().len(), 1);
+
+        let session = db.create_session(&event, "Warmup").unwrap();
+        db.move_solve_to_session(id, &session).unwrap();
+        assert_eq!(db.get_solves(&event, &session).unwrap().len(), 1);
+        assert_eq!(
+            single(&mut db, &event, Some(&session)),
+            Duration::from_secs(10)
+        );
+    }
+
+    #[test]
+    fn missing_solves_cant_be_updated() {
+        let mut db = Db::in_memory();
+        assert!(db.update_penalty(1, Penalty::Plus2).is_err());
+        assert!(db.update_comment(1, Some("Lucky")).is_err());
+    }
+}
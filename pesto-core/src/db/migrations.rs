@@ -0,0 +1,386 @@
+//! Changes to the schema of the database, applied in order.
+//!
+//! The number of migrations that have been applied is stored in
+//! `PRAGMA user_version`, so version 0 is an empty database (or one from
+//! before there were migrations) and version `MIGRATIONS.len()` is the
+//! current schema.
+//!
+//! Migrations must never be changed or reordered once released, only
+//! appended.
+
+use std::path::Path;
+
+use color_eyre::eyre::{self, Context};
+use rusqlite::{Connection, Transaction};
+
+struct Migration {
+    description: &'static str,
+    apply: fn(&Transaction) -> rusqlite::Result<()>,
+}
+
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        description: "Create the solve, custom event and custom session tables",
+        apply: create_initial_tables,
+    },
+    Migration {
+        description: "Create the personal best table",
+        apply: create_personal_best,
+    },
+    Migration {
+        description: "Add comments and soft deletion to solves",
+        apply: add_solve_comment_and_deleted,
+    },
+    Migration {
+        description: "Store end dates as text",
+        apply: end_date_as_text,
+    },
+    Migration {
+        description: "Add positions to custom events and sessions",
+        apply: add_positions,
+    },
+    Migration {
+        description: "Create the event settings table",
+        apply: create_event_settings,
+    },
+    Migration {
+        description: "Create the solve split table and add phases to event settings",
+        apply: create_solve_split,
+    },
+];
+
+/// The version of the schema that this version of pesto uses.
+pub const CURRENT_VERSION: usize = MIGRATIONS.len();
+
+pub fn version(conn: &Connection) -> rusqlite::Result<usize> {
+    conn.pragma_query_value(None, "user_version", |row| row.get(0))
+}
+
+/// Applies every migration that hasn't been applied yet.
+///
+/// If the database is not empty, a copy is written to `backup` before
+/// changing anything.
+pub fn migrate(conn: &mut Connection, backup: Option<&Path>) -> eyre::Result<()> {
+    let version = version(conn)?;
+    if version > CURRENT_VERSION {
+        eyre::bail!(
+            "Database is at version {version}, but this version of pesto only supports up to {CURRENT_VERSION}"
+        );
+    }
+
+    if version == CURRENT_VERSION {
+        return Ok(());
+    }
+
+    if let Some(backup) = backup
+        && !is_empty(conn)?
+    {
+        if backup.exists() {
+            std::fs::remove_file(backup)?;
+        }
+
+        conn.execute("VACUUM INTO $1", [backup.to_string_lossy()])
+            .wrap_err_with(|| format!("Couldn't back up database to {}", backup.display()))?;
+    }
+
+    for (i, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+        let tx = conn.transaction()?;
+        (migration.apply)(&tx)
+            .wrap_err_with(|| format!("Migration {} failed: {}", i + 1, migration.description))?;
+        tx.pragma_update(None, "user_version", i + 1)?;
+        tx.commit()?;
+    }
+
+    Ok(())
+}
+
+fn is_empty(conn: &Connection) -> rusqlite::Result<bool> {
+    conn.query_row("SELECT COUNT(*) = 0 FROM sqlite_schema", [], |row| {
+        row.get(0)
+    })
+}
+
+// NOTE: The first migrations use `IF NOT EXISTS` and `add_column_if_missing`
+// because databases from before there were migrations are at version 0 but
+// might already have some of these changes.
+
+fn create_initial_tables(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE IF NOT EXISTS solve (
+            id INTEGER PRIMARY KEY,
+            time_ms INTEGER NOT NULL,
+            end_date INTEGER NOT NULL,
+            scramble TEXT NOT NULL,
+            penalty INTEGER NOT NULL,
+            event_id INTEGER NOT NULL,
+            session_id INTEGER NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS custom_event (
+            --- Cannot be 0-16 (inclusive), minimum allowed value is 17. 0-16 indicate official WCA events.
+            id INTEGER PRIMARY KEY,
+            name TEXT NOT NULL,
+            --- Between 0-16, index of an official WCA event. Can be null.
+            scramble_type INTEGER
+        );
+        CREATE TABLE IF NOT EXISTS custom_session (
+            --- Cannot be 0 in the database. An index of 0 refers to the 'main' session.
+            id INTEGER NOT NULL,
+            name TEXT NOT NULL,
+            event_id INTEGER NOT NULL,
+            PRIMARY KEY (id, event_id)
+        );",
+    )
+}
+
+fn create_personal_best(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE IF NOT EXISTS personal_best (
+            id INTEGER PRIMARY KEY,
+            record TEXT NOT NULL,
+            time_ms INTEGER NOT NULL,
+            end_date INTEGER NOT NULL,
+            event_id INTEGER NOT NULL,
+            session_id INTEGER NOT NULL
+        );",
+    )
+}
+
+fn add_solve_comment_and_deleted(tx: &Transaction) -> rusqlite::Result<()> {
+    add_column_if_missing(tx, "solve", "comment", "TEXT")?;
+    add_column_if_missing(tx, "solve", "deleted", "INTEGER NOT NULL DEFAULT 0")
+}
+
+/// End dates were always written as strings, but the columns were declared
+/// as integers.
+fn end_date_as_text(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE solve_new (
+            id INTEGER PRIMARY KEY,
+            --- Amount of time it took to solve
+            time_ms INTEGER NOT NULL,
+            --- Datetime when the solve ended, according to [RFC 9557](https://datatracker.ietf.org/doc/html/rfc9557)
+            end_date TEXT NOT NULL,
+            scramble TEXT NOT NULL,
+            penalty INTEGER NOT NULL,
+            event_id INTEGER NOT NULL,
+            session_id INTEGER NOT NULL,
+            comment TEXT,
+            --- Deleted solves are kept until purged, so that deleting can be undone.
+            deleted INTEGER NOT NULL DEFAULT 0
+        );
+        INSERT INTO solve_new
+            SELECT id, time_ms, CAST(end_date AS TEXT), scramble, penalty, event_id, session_id, comment, deleted
+            FROM solve;
+        DROP TABLE solve;
+        ALTER TABLE solve_new RENAME TO solve;
+
+        --- Every time a record was broken, for each event and session.
+        CREATE TABLE personal_best_new (
+            id INTEGER PRIMARY KEY,
+            --- Like 'single' or 'ao5'
+            record TEXT NOT NULL,
+            time_ms INTEGER NOT NULL,
+            --- When the solve that broke the record ended, according to RFC 9557
+            end_date TEXT NOT NULL,
+            event_id INTEGER NOT NULL,
+            session_id INTEGER NOT NULL
+        );
+        INSERT INTO personal_best_new
+            SELECT id, record, time_ms, CAST(end_date AS TEXT), event_id, session_id
+            FROM personal_best;
+        DROP TABLE personal_best;
+        ALTER TABLE personal_best_new RENAME TO personal_best;",
+    )
+}
+
+/// Custom events and sessions are sorted by position and then by id, so
+/// existing ones keep their order.
+fn add_positions(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "ALTER TABLE custom_event ADD COLUMN position INTEGER NOT NULL DEFAULT 0;
+        ALTER TABLE custom_session ADD COLUMN position INTEGER NOT NULL DEFAULT 0;",
+    )
+}
+
+fn create_event_settings(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE event_settings (
+            event_id INTEGER PRIMARY KEY,
+            --- Whether to use WCA inspection. Null means the default of the event.
+            inspection INTEGER
+        );",
+    )
+}
+
+fn create_solve_split(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "ALTER TABLE event_settings ADD COLUMN phases TEXT;
+
+        CREATE TABLE solve_split (
+            solve_id INTEGER NOT NULL,
+            --- The order of the phase in the solve, starting at 0.
+            position INTEGER NOT NULL,
+            name TEXT NOT NULL,
+            time_ms INTEGER NOT NULL,
+            PRIMARY KEY (solve_id, position)
+        );
+
+        CREATE TRIGGER delete_solve_splits AFTER DELETE ON solve BEGIN
+            DELETE FROM solve_split WHERE solve_id = OLD.id;
+        END;",
+    )
+}
+
+fn add_column_if_missing(
+    tx: &Transaction,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> rusqlite::Result<()> {
+    let exists = tx.query_row(
+        "SELECT COUNT(*) FROM pragma_table_info($1) WHERE name = $2",
+        [table, column],
+        |row| row.get::<_, usize>(0),
+    )? > 0;
+
+    if !exists {
+        tx.execute(
+            &format!("ALTER TABLE {table} ADD COLUMN {column} {definition}"),
+            (),
+        )?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column_type(conn: &Connection, table: &str, column: &str) -> Option<String> {
+        conn.query_row(
+            "SELECT type FROM pragma_table_info($1) WHERE name = $2",
+            [table, column],
+            |row| row.get(0),
+        )
+        .ok()
+    }
+
+    #[test]
+    fn migrates_empty_database_to_current_version() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        migrate(&mut conn, None).unwrap();
+
+        assert_eq!(version(&conn).unwrap(), CURRENT_VERSION);
+        assert_eq!(column_type(&conn, "solve", "end_date").unwrap(), "TEXT");
+        assert_eq!(
+            column_type(&conn, "personal_best", "end_date").unwrap(),
+            "TEXT"
+        );
+        assert!(column_type(&conn, "solve", "comment").is_some());
+    }
+
+    #[test]
+    fn migrating_twice_does_nothing() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        migrate(&mut conn, None).unwrap();
+        conn.execute(
+            "INSERT INTO solve (time_ms, end_date, scramble, penalty, event_id, session_id)
+            VALUES (1000, '2025-01-01T00:00:00+00:00[UTC]', 'R', 0, 1, 0)",
+            (),
+        )
+        .unwrap();
+
+        migrate(&mut conn, None).unwrap();
+        let count: usize = conn
+            .query_row("SELECT COUNT(*) FROM solve", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn keeps_solves_of_unversioned_database() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        let tx = conn.transaction().unwrap();
+        create_initial_tables(&tx).unwrap();
+        tx.commit().unwrap();
+        conn.execute(
+            "INSERT INTO solve (time_ms, end_date, scramble, penalty, event_id, session_id)
+            VALUES (12345, '2025-01-01T00:00:00+01:00[Europe/Madrid]', 'R U', 1, 1, 0)",
+            (),
+        )
+        .unwrap();
+        assert_eq!(version(&conn).unwrap(), 0);
+
+        migrate(&mut conn, None).unwrap();
+        let (time_ms, end_date, deleted): (u64, String, bool) = conn
+            .query_row("SELECT time_ms, end_date, deleted FROM solve", [], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            })
+            .unwrap();
+
+        assert_eq!(time_ms, 12345);
+        assert_eq!(end_date, "2025-01-01T00:00:00+01:00[Europe/Madrid]");
+        assert!(!deleted);
+    }
+
+    /// A database with only the first `version` migrations applied.
+    fn at_version(version: usize) -> Connection {
+        let mut conn = Connection::open_in_memory().unwrap();
+        for (i, migration) in MIGRATIONS[..version].iter().enumerate() {
+            let tx = conn.transaction().unwrap();
+            (migration.apply)(&tx).unwrap();
+            tx.pragma_update(None, "user_version", i + 1).unwrap();
+            tx.commit().unwrap();
+        }
+
+        conn
+    }
+
+    #[test]
+    fn backs_up_before_migrating() {
+        let dir = std::env::temp_dir().join(format!("pesto-migrations-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let backup = dir.join("main.db.bak");
+
+        let mut conn = Connection::open_in_memory().unwrap();
+        migrate(&mut conn, Some(&backup)).unwrap();
+        assert!(!backup.exists(), "empty databases aren't backed up");
+
+        let mut conn = at_version(1);
+        migrate(&mut conn, Some(&backup)).unwrap();
+        let backed_up = Connection::open(&backup).unwrap();
+        assert_eq!(version(&backed_up).unwrap(), 1);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn migrates_from_every_version() {
+        let dir = std::env::temp_dir().join(format!(
+            "pesto-migrations-every-version-{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        for from in 1..CURRENT_VERSION {
+            let backup = dir.join(format!("v{from}.bak"));
+            let mut conn = at_version(from);
+            migrate(&mut conn, Some(&backup)).unwrap();
+
+            assert_eq!(version(&conn).unwrap(), CURRENT_VERSION);
+            let backed_up = Connection::open(&backup).unwrap();
+            assert_eq!(version(&backed_up).unwrap(), from);
+        }
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn refuses_newer_databases() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.pragma_update(None, "user_version", CURRENT_VERSION + 1)
+            .unwrap();
+        assert!(migrate(&mut conn, None).is_err());
+    }
+}
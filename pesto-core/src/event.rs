@@ -1,4 +1,4 @@
-use norcina::Event;
+use norcina::{DynPuzzle, Event};
 
 #[derive(Debug, Clone)]
 pub enum Session {
@@ -29,6 +29,11 @@ pub struct CustomEvent {
     pub scramble_type: Option<Event>,
 }
 
+impl CustomEvent {
+    /// The ids before this one are for the official events.
+    pub const FIRST_ID: usize = Event::ALL.len();
+}
+
 #[derive(Debug, Clone)]
 pub enum MaybeCustomEvent {
     Official(Event),
@@ -63,6 +68,13 @@ impl MaybeCustomEvent {
             Self::Unofficial(event) => norcina::gen_scramble(event.scramble_type?, rng).to_string(),
         })
     }
+
+    /// The state of the puzzle after doing `scramble`, if the puzzle is
+    /// implemented and the scramble is valid.
+    pub fn scrambled_state(&self, scramble: &str) -> Option<DynPuzzle> {
+        let puzzle = DynPuzzle::for_event(self.scramble_type()?)?;
+        puzzle.apply(&puzzle.parse_alg(scramble).ok()?).ok()
+    }
 }
 
 impl Default for MaybeCustomEvent {
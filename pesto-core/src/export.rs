@@ -0,0 +1,73 @@
+//! Exporting solves, to back them up or to use them in other timers.
+
+use color_eyre::eyre;
+
+use crate::{
+    Db, Solve,
+    event::{MaybeCustomEvent, Session},
+};
+
+pub mod cstimer;
+pub mod csv;
+pub mod twisty_timer;
+
+impl Db {
+    /// The solves of every session of every event, skipping sessions without
+    /// solves.
+    pub(crate) fn all_solves(
+        &mut self,
+    ) -> eyre::Result<Vec<(MaybeCustomEvent, Session, Vec<Solve>)>> {
+        let mut all = Vec::new();
+        for (event, sessions) in self.get_events_and_sessions()? {
+            for session in sessions {
+                let solves = self.get_solves(&event, &session)?;
+                if !solves.is_empty() {
+                    all.push((event.clone(), session, solves));
+                }
+            }
+        }
+
+        Ok(all)
+    }
+}
+
+/// A database with solves of an official and a custom event, with every
+/// kind of penalty, comments, splits and sessions, to check that exporting
+/// and importing back doesn't lose anything.
+#[cfg(test)]
+pub(crate) fn sample_db() -> Db {
+    use std::time::Duration;
+
+    use norcina::Event;
+
+    use crate::solve::{Penalty, Split};
+
+    let mut db = Db::in_memory();
+    let oh = MaybeCustomEvent::Official(Event::OneHanded);
+    let relay = MaybeCustomEvent::Unofficial(
+        db.create_custom_event("Relay, 2-4", Some(Event::Cube4))
+            .unwrap(),
+    );
+    let home = db.create_session(&oh, "home").unwrap();
+
+    let solves = [
+        (&oh, &Session::Main, Penalty::None, None),
+        (&oh, &home, Penalty::Plus2, Some("\"nice\"; lucky")),
+        (&relay, &Session::Main, Penalty::DNF, None),
+    ];
+    for (i, (event, session, penalty, comment)) in solves.into_iter().enumerate() {
+        let mut solve = Solve::new(Duration::from_millis(10_000 + i as u64), "R U".into());
+        solve.penalty = penalty;
+        solve.comment = comment.map(str::to_string);
+        if i == 0 {
+            solve.splits = Split::from_marks(
+                &["Cross".to_string(), "F2L".to_string()],
+                &[Duration::from_millis(1_500)],
+                solve.time,
+            );
+        }
+        db.insert_solve(solve, event, session).unwrap();
+    }
+
+    db
+}
@@ -0,0 +1,152 @@
+//! Exporting to the JSON format of [csTimer](https://cstimer.net), which it
+//! can import.
+//!
+//! Each session of each event becomes a csTimer session, named as described
+//! in [`crate::import::cstimer`], so that importing it back into pesto gives
+//! the same events and sessions. Times, penalties, scrambles, comments and
+//! split times are kept exactly. The names of the phases are only stored
+//! once per session (those of its first split solve), so in a session that
+//! was split in different ways, the other solves get those names too (or
+//! `Phase 1`, `Phase 2` and so on if they have a different number of phases).
+//!
+//! End dates lose some information, because csTimer only stores a Unix
+//! timestamp in seconds: they are rounded down to the second, and their time
+//! zone is lost (importing gives them the time zone of the system).
+
+use color_eyre::eyre;
+use norcina::Event;
+use serde_json::{Map, Value, json};
+
+use crate::{
+    Db, Solve,
+    event::MaybeCustomEvent,
+    import::cstimer::{PESTO_KEY, scramble_type, session_name},
+    solve::Penalty,
+};
+
+const fn penalty_value(penalty: Penalty) -> i64 {
+    match penalty {
+        Penalty::None => 0,
+        Penalty::Plus2 => 2000,
+        Penalty::DNF => -1,
+    }
+}
+
+/// `[penalty, time_ms]`, followed by the end of each phase but the last (from
+/// the start, the last one first) if the solve was split, like csTimer does
+/// with multi-phase timing.
+fn times(solve: &Solve) -> Value {
+    let mut times = vec![penalty_value(solve.penalty), solve.time.as_millis() as i64];
+
+    let phases = solve.splits.len().saturating_sub(1);
+    let ends = solve.splits[..phases].iter().scan(0, |end, split| {
+        *end += split.time.as_millis() as i64;
+        Some(*end)
+    });
+    times.extend(ends.collect::<Vec<_>>().into_iter().rev());
+
+    json!(times)
+}
+
+/// Every solve, as a csTimer export.
+pub fn export(db: &mut Db) -> eyre::Result<String> {
+    let mut export = Map::new();
+    let mut session_data = Map::new();
+
+    for (i, (event, session, solves)) in db.all_solves()?.into_iter().enumerate() {
+        let number = i + 1;
+        // What only pesto reads: csTimer has no custom events without
+        // scrambles, and doesn't name the phases.
+        let mut pesto = Map::new();
+        let scramble_event = match &event {
+            &MaybeCustomEvent::Official(event) => event,
+            MaybeCustomEvent::Unofficial(event) => event.scramble_type.unwrap_or_else(|| {
+                pesto.insert("noScrambleType".to_string(), json!(true));
+                Event::default()
+            }),
+        };
+
+        if let Some(solve) = solves.iter().find(|solve| !solve.splits.is_empty()) {
+            let phases: Vec<_> = solve.splits.iter().map(|split| &split.name).collect();
+            pesto.insert("phases".to_string(), json!(phases));
+        }
+
+        let solves = solves
+            .iter()
+            .map(|solve| {
+                json!([
+                    times(solve),
+                    solve.scramble,
+                    solve.comment.as_deref().unwrap_or_default(),
+                    solve.end_date.timestamp().as_second(),
+                ])
+            })
+            .collect();
+
+        export.insert(format!("session{number}"), Value::Array(solves));
+        let mut data = json!({
+            "name": session_name(&event, &session),
+            "opt": { "scrType": scramble_type(scramble_event) },
+            "rank": number,
+        });
+        if !pesto.is_empty() {
+            data[PESTO_KEY] = Value::Object(pesto);
+        }
+
+        session_data.insert(number.to_string(), data);
+    }
+
+    let session_count = session_data.len();
+    export.insert(
+        "properties".to_string(),
+        json!({
+            "sessionData": Value::Object(session_data).to_string(),
+            "sessionN": session_count,
+            "session": 1,
+        }),
+    );
+
+    Ok(Value::Object(export).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{event::Session, export::sample_db, import};
+
+    #[test]
+    fn round_trip() {
+        let json = export(&mut sample_db()).unwrap();
+        let mut imported = Db::in_memory();
+        let report = import::cstimer::import(&mut imported, &json).unwrap();
+        assert_eq!(report.imported, 3);
+        assert_eq!(report.skipped, []);
+        assert_eq!(export(&mut imported).unwrap(), json);
+
+        let oh = MaybeCustomEvent::Official(Event::OneHanded);
+        let sessions = imported.get_sessions_of_event(&oh).unwrap();
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[1].name(), "home");
+
+        let solves = imported.get_solves(&oh, &Session::Main).unwrap();
+        let phases: Vec<_> = solves[0].splits.iter().map(ToString::to_string).collect();
+        assert_eq!(phases, ["Cross 01.500", "F2L 08.500"]);
+    }
+
+    #[test]
+    fn custom_events_without_scrambles_round_trip() {
+        let mut db = Db::in_memory();
+        let event = MaybeCustomEvent::Unofficial(db.create_custom_event("Feet", None).unwrap());
+        let solve = Solve::new(std::time::Duration::from_secs(30), String::new());
+        db.insert_solve(solve, &event, &Session::Main).unwrap();
+
+        let mut imported = Db::in_memory();
+        import::cstimer::import(&mut imported, &export(&mut db).unwrap()).unwrap();
+        let events = imported.get_events().unwrap();
+        let MaybeCustomEvent::Unofficial(feet) = events.last().unwrap() else {
+            panic!("The custom event should be imported");
+        };
+        assert_eq!(feet.name, "Feet");
+        assert_eq!(feet.scramble_type, None);
+    }
+}
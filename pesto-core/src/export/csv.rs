@@ -0,0 +1,62 @@
+//! Exporting pesto's generic CSV format.
+//!
+//! See [`crate::import::csv`] for the format.
+
+use color_eyre::eyre::{self, Context as _};
+
+use crate::{
+    Db,
+    event::MaybeCustomEvent,
+    import::csv::{HEADER, format_splits, penalty_name},
+};
+
+/// Every solve, as CSV in the generic format.
+pub fn export(db: &mut Db) -> eyre::Result<String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(HEADER)?;
+
+    for (event, session, solves) in db.all_solves()? {
+        let scramble_type = match &event {
+            MaybeCustomEvent::Official(_) => None,
+            MaybeCustomEvent::Unofficial(event) => event.scramble_type,
+        };
+
+        for solve in solves {
+            writer.write_record([
+                event.short_name(),
+                scramble_type.map_or("", |event| event.short_name()),
+                session.name(),
+                &solve.time.as_millis().to_string(),
+                penalty_name(solve.penalty),
+                &solve.end_date.to_string(),
+                &solve.scramble,
+                solve.comment.as_deref().unwrap_or_default(),
+                &format_splits(&solve.splits),
+            ])?;
+        }
+    }
+
+    let csv = writer.into_inner().wrap_err("Couldn't write CSV")?;
+    Ok(String::from_utf8(csv)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{export::sample_db, import};
+
+    #[test]
+    fn round_trip_is_lossless() {
+        let csv = export(&mut sample_db()).unwrap();
+
+        let mut db = Db::in_memory();
+        let report = import::csv::import(&mut db, &csv).unwrap();
+        assert_eq!(report.imported, 3);
+        assert_eq!(report.skipped, []);
+        assert_eq!(export(&mut db).unwrap(), csv);
+
+        let report = import::csv::import(&mut db, &csv).unwrap();
+        assert_eq!(report.imported, 0);
+        assert_eq!(report.duplicates().count(), 3);
+    }
+}
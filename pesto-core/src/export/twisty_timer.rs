@@ -0,0 +1,170 @@
+//! Exporting backups for [Twisty Timer](https://github.com/aricneto/TwistyTimer).
+//!
+//! See [`crate::import::twisty_timer`] for the format and how events and
+//! sessions are mapped.
+
+use std::time::Duration;
+
+use color_eyre::eyre::{self, Context as _};
+
+use crate::{
+    Db,
+    import::twisty_timer::{HEADER, penalty_index, puzzle_and_category},
+    solve::Penalty,
+};
+
+/// Every solve, as a Twisty Timer backup.
+pub fn export(db: &mut Db) -> eyre::Result<String> {
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(b';')
+        .quote_style(csv::QuoteStyle::Always)
+        .from_writer(Vec::new());
+
+    // Twisty Timer doesn't quote the header.
+    let mut output = HEADER.join(";");
+    output.push('\n');
+
+    for (event, session, solves) in db.all_solves()? {
+        let (puzzle, category) = puzzle_and_category(&event, &session);
+        for solve in solves {
+            let time = match solve.penalty {
+                Penalty::Plus2 => solve.time + Duration::from_secs(2),
+                Penalty::None | Penalty::DNF => solve.time,
+            };
+
+            writer.write_record([
+                puzzle,
+                &category,
+                &time.as_millis().to_string(),
+                &solve.end_date.timestamp().as_millisecond().to_string(),
+                &solve.scramble,
+                &penalty_index(solve.penalty).to_string(),
+                solve.comment.as_deref().unwrap_or_default(),
+            ])?;
+        }
+    }
+
+    let records = writer.into_inner().wrap_err("Couldn't write backup")?;
+    output.push_str(&String::from_utf8(records)?);
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use jiff::tz::TimeZone;
+    use norcina::Event;
+
+    use super::*;
+    use crate::{
+        Solve,
+        event::{MaybeCustomEvent, Session},
+        export::sample_db,
+        import,
+    };
+
+    /// [`sample_db`] with the things that Twisty Timer can't hold.
+    fn lossy_sample_db() -> Db {
+        let mut db = sample_db();
+        let oh = MaybeCustomEvent::Official(Event::OneHanded);
+        let feet = MaybeCustomEvent::Unofficial(db.create_custom_event("Feet", None).unwrap());
+        let relay = MaybeCustomEvent::Unofficial(
+            db.create_custom_event("Relay: 2-4", Some(Event::Cube2))
+                .unwrap(),
+        );
+        let normal = db.create_session(&oh, "Normal").unwrap();
+
+        for (i, (event, session)) in [
+            (&feet, &Session::Main),
+            (&relay, &Session::Main),
+            (&oh, &normal),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let mut solve = Solve::new(Duration::from_millis(20_000 + i as u64), "R U".into());
+            solve.end_date = solve
+                .end_date
+                .with_time_zone(TimeZone::get("Asia/Tokyo").unwrap());
+            db.insert_solve(solve, event, session).unwrap();
+        }
+
+        db
+    }
+
+    #[test]
+    fn round_trip() {
+        let backup = export(&mut sample_db()).unwrap();
+
+        let mut db = Db::in_memory();
+        let report = import::twisty_timer::import(&mut db, &backup).unwrap();
+        assert_eq!(report.imported, 3);
+        assert_eq!(report.skipped, []);
+        assert_eq!(export(&mut db).unwrap(), backup);
+    }
+
+    #[test]
+    fn round_trip_loses_what_twisty_timer_cant_hold() {
+        let mut original = lossy_sample_db();
+        let backup = export(&mut original).unwrap();
+
+        let mut db = Db::in_memory();
+        let report = import::twisty_timer::import(&mut db, &backup).unwrap();
+        assert_eq!(report.imported, 6);
+        assert_eq!(report.skipped, []);
+
+        let imported = db.all_solves().unwrap();
+        let summary = imported
+            .iter()
+            .map(|(event, session, solves)| {
+                let scramble_type = match event {
+                    MaybeCustomEvent::Official(_) => None,
+                    MaybeCustomEvent::Unofficial(event) => event.scramble_type,
+                };
+                (
+                    event.short_name().to_string(),
+                    scramble_type,
+                    session.name().to_string(),
+                    solves.len(),
+                )
+            })
+            .collect::<Vec<_>>();
+        let row = |event: &str, scramble_type, session: &str, solves| {
+            (
+                event.to_string(),
+                scramble_type,
+                session.to_string(),
+                solves,
+            )
+        };
+        assert_eq!(
+            summary,
+            [
+                // The `Normal` session is merged into the main one.
+                row("OH", None, "main", 2),
+                row("OH", None, "home", 1),
+                row("Relay, 2-4", Some(Event::Cube4), "main", 1),
+                // Without a scramble type, it's a 3x3 event.
+                row("Feet", Some(Event::Cube3), "main", 1),
+                // Split at the first `: `.
+                row("Relay", Some(Event::Cube2), "2-4: Normal", 1),
+            ]
+        );
+
+        let original_solves = original.all_solves().unwrap();
+        let (_, _, with_splits) = &original_solves[0];
+        assert!(!with_splits[0].splits.is_empty());
+        assert!(imported[0].2[0].splits.is_empty());
+
+        let (_, _, in_tokyo) = original_solves.last().unwrap();
+        let (_, _, back) = imported
+            .iter()
+            .find(|(event, ..)| event.short_name() == "OH")
+            .unwrap();
+        let back = back.last().unwrap();
+        assert_eq!(back.end_date.time_zone(), &TimeZone::system());
+        assert_eq!(
+            back.end_date.timestamp().as_millisecond(),
+            in_tokyo[0].end_date.timestamp().as_millisecond()
+        );
+    }
+}
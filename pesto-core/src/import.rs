@@ -0,0 +1,276 @@
+//! Importing solves from other timers.
+
+use std::fmt;
+
+use color_eyre::eyre;
+use norcina::Event;
+
+use crate::{
+    Db, Solve,
+    event::{CustomEvent, MaybeCustomEvent, Session},
+};
+
+pub mod cstimer;
+pub mod csv;
+pub mod twisty_timer;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SkipReason {
+    /// There already is a solve of the same event with the same time that
+    /// ended at the same second.
+    Duplicate,
+    /// The solve (or its session) can't be represented in pesto.
+    Unmappable(String),
+}
+
+/// A solve that wasn't imported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Skipped {
+    /// The name of the session in the other timer.
+    pub session: String,
+    /// The position of the solve in its session, or `None` if the whole
+    /// session was skipped.
+    pub index: Option<usize>,
+    pub reason: SkipReason,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ImportReport {
+    pub imported: usize,
+    /// The custom events that didn't exist and were created.
+    pub created_events: Vec<CustomEvent>,
+    /// The sessions that didn't exist and were created.
+    pub created_sessions: Vec<(MaybeCustomEvent, Session)>,
+    pub skipped: Vec<Skipped>,
+}
+
+impl ImportReport {
+    pub fn duplicates(&self) -> impl Iterator<Item = &Skipped> {
+        self.skipped
+            .iter()
+            .filter(|skipped| skipped.reason == SkipReason::Duplicate)
+    }
+
+    pub fn unmappable(&self) -> impl Iterator<Item = &Skipped> {
+        self.skipped
+            .iter()
+            .filter(|skipped| matches!(skipped.reason, SkipReason::Unmappable(_)))
+    }
+}
+
+impl fmt::Display for ImportReport {
+    /// Like `Imported 120 solves (1 new session), skipped 3 duplicates and 1 unmappable`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Imported {} solves", self.imported)?;
+        if !self.created_events.is_empty() {
+            write!(f, " ({} new custom events)", self.created_events.len())?;
+        }
+
+        if !self.created_sessions.is_empty() {
+            write!(f, " ({} new sessions)", self.created_sessions.len())?;
+        }
+
+        write!(
+            f,
+            ", skipped {} duplicates and {} unmappable",
+            self.duplicates().count(),
+            self.unmappable().count()
+        )
+    }
+}
+
+/// The event of imported solves.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum ImportedEvent {
+    Official(Event),
+    /// The custom event with this name is used, or created if there is none.
+    Custom {
+        name: String,
+        scramble_type: Option<Event>,
+    },
+}
+
+/// The solves of a session of another timer.
+#[derive(Debug, Clone)]
+pub(crate) struct ImportedSession {
+    pub event: ImportedEvent,
+    /// The session with this name is used, or created if there is none.
+    pub name: String,
+    /// Each solve, with its position in the other timer.
+    pub solves: Vec<(usize, Solve)>,
+}
+
+impl Db {
+    /// Inserts every solve that is not a duplicate, all in one transaction.
+    pub(crate) fn import_sessions(
+        &mut self,
+        sessions: Vec<ImportedSession>,
+        mut report: ImportReport,
+    ) -> eyre::Result<ImportReport> {
+        self.in_transaction(|db| {
+            for imported in sessions {
+                let event = db.event_for_import(&imported.event, &mut report)?;
+                let session = db.session_for_import(&event, &imported.name, &mut report)?;
+                let mut existing = db.solve_keys(&event)?;
+
+                let mut solves = imported.solves;
+                solves.sort_by_key(|(_, solve)| solve.end_date.timestamp());
+                for (index, solve) in solves {
+                    let key = (
+                        solve.time.as_millis() as u64,
+                        solve.end_date.timestamp().as_second(),
+                    );
+
+                    if !existing.insert(key) {
+                        report.skipped.push(Skipped {
+                            session: imported.name.clone(),
+                            index: Some(index),
+                            reason: SkipReason::Duplicate,
+                        });
+                        continue;
+                    }
+
+                    db.insert_solve_without_pbs(&solve, event.id(), session.id())?;
+                    report.imported += 1;
+                }
+
+                db.rebuild_pbs(&event, &session)?;
+            }
+
+            Ok(report)
+        })
+    }
+
+    /// The event called like the imported one, which is created if needed.
+    fn event_for_import(
+        &mut self,
+        event: &ImportedEvent,
+        report: &mut ImportReport,
+    ) -> eyre::Result<MaybeCustomEvent> {
+        let (name, scramble_type) = match event {
+            &ImportedEvent::Official(event) => return Ok(MaybeCustomEvent::Official(event)),
+            ImportedEvent::Custom {
+                name,
+                scramble_type,
+            } => (name.trim(), *scramble_type),
+        };
+
+        let existing = self
+            .get_events()?
+            .into_iter()
+            .find(|event| event.short_name().eq_ignore_ascii_case(name));
+
+        Ok(match existing {
+            Some(event) => event,
+            None => {
+                let event = self.create_custom_event(name, scramble_type)?;
+                report.created_events.push(event.clone());
+                MaybeCustomEvent::Unofficial(event)
+            }
+        })
+    }
+
+    /// The session of `event` called `name` (or the main session, if there's
+    /// no name), which is created if needed.
+    fn session_for_import(
+        &mut self,
+        event: &MaybeCustomEvent,
+        name: &str,
+        report: &mut ImportReport,
+    ) -> eyre::Result<Session> {
+        let name = name.trim();
+        if name.is_empty() || name.eq_ignore_ascii_case(Session::Main.name()) {
+            return Ok(Session::Main);
+        }
+
+        let existing = self
+            .get_sessions_of_event(event)?
+            .into_iter()
+            .find(|session| session.name().eq_ignore_ascii_case(name));
+
+        Ok(match existing {
+            Some(session) => session,
+            None => {
+                let session = self.create_session(event, name)?;
+                report
+                    .created_sessions
+                    .push((event.clone(), session.clone()));
+                session
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    const EXPORT: &str = r#"{
+        "session1": [
+            [[0, 12000], "R", "", 1700000000],
+            [[0, 11000], "U", "", 1700000060]
+        ],
+        "properties": {
+            "sessionData": "{\"1\":{\"name\":\"3x3\",\"opt\":{}}}"
+        }
+    }"#;
+
+    const CUBE3: MaybeCustomEvent = MaybeCustomEvent::Official(Event::Cube3);
+
+    fn times(db: &mut Db) -> Vec<u64> {
+        db.get_solves(&CUBE3, &Session::Main)
+            .unwrap()
+            .iter()
+            .map(|solve| solve.time.as_secs())
+            .collect()
+    }
+
+    #[test]
+    fn duplicates_are_reported() {
+        let mut db = Db::in_memory();
+        let first = cstimer::import(&mut db, EXPORT).unwrap();
+        assert_eq!(first.imported, 2);
+        assert_eq!(first.duplicates().count(), 0);
+
+        let second = cstimer::import(&mut db, EXPORT).unwrap();
+        assert_eq!(second.imported, 0);
+        assert_eq!(
+            second
+                .duplicates()
+                .map(|skipped| skipped.index)
+                .collect::<Vec<_>>(),
+            [Some(0), Some(1)]
+        );
+        assert_eq!(times(&mut db), [12, 11]);
+    }
+
+    #[test]
+    fn older_solves_go_before_newer_ones() {
+        let mut db = Db::in_memory();
+        let solve = Solve::new(Duration::from_secs(10), String::new());
+        db.insert_solve(solve, &CUBE3, &Session::Main).unwrap();
+
+        cstimer::import(&mut db, EXPORT).unwrap();
+        assert_eq!(times(&mut db), [12, 11, 10]);
+    }
+
+    #[test]
+    fn failed_imports_are_rolled_back() {
+        // The second session would go to a custom event without a name.
+        let export = r#"{
+            "session1": [[[0, 12000], "R", "", 1700000000]],
+            "session2": [[[0, 11000], "U", "", 1700000060]],
+            "properties": {
+                "sessionData": "{\"1\":{\"name\":\"home\"},\"2\":{\"name\":\": home\"}}"
+            }
+        }"#;
+
+        let mut db = Db::in_memory();
+        assert!(cstimer::import(&mut db, export).is_err());
+        assert_eq!(db.get_sessions_of_event(&CUBE3).unwrap().len(), 1);
+        assert_eq!(db.get_events().unwrap().len(), Event::ALL.len());
+        assert_eq!(db.solve_keys(&CUBE3).unwrap().len(), 0);
+    }
+}
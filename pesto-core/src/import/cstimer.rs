@@ -0,0 +1,350 @@
+//! Importing the JSON exports of [csTimer](https://cstimer.net).
+//!
+//! An export has a `sessionN` array for each session, where every solve is
+//! `[[penalty, time_ms, ...], scramble, comment, timestamp, ...]`. The names
+//! and scramble types of the sessions are in `properties.sessionData`, which
+//! is itself JSON in a string. With multi-phase timing, the time array also
+//! has the end of each phase but the last, the last one first.
+//!
+//! csTimer doesn't have events, so each session goes to the event of its
+//! scramble type. Sessions exported from pesto have the name of the event in
+//! their name too: `3x3` for the main session of 3x3, `3x3: home` for the
+//! others, and `Relay: main` for every session of custom events. Their
+//! session data also has a `pesto` object with the names of the phases and
+//! whether the custom event has no scramble type.
+
+use std::time::Duration;
+
+use color_eyre::eyre::{self, Context as _, OptionExt as _};
+use jiff::{Timestamp, tz::TimeZone};
+use norcina::Event;
+use serde_json::Value;
+
+use crate::{
+    Db, Solve,
+    event::{MaybeCustomEvent, Session},
+    import::{ImportReport, ImportedEvent, ImportedSession, SkipReason, Skipped},
+    solve::{Penalty, Split},
+};
+
+/// The csTimer scramble type of each event, by [`Event::str_id`]. The first
+/// one of each event is the one csTimer uses for WCA scrambles.
+const SCRAMBLE_TYPES: [(&str, &str); 21] = [
+    ("222", "222so"),
+    ("333", "333"),
+    ("444", "444wca"),
+    ("555", "555wca"),
+    ("666", "666wca"),
+    ("777", "777wca"),
+    ("333bf", "333ni"),
+    ("444bf", "444bld"),
+    ("555bf", "555bld"),
+    ("333mbf", "r3ni"),
+    ("333fm", "333fm"),
+    ("333oh", "333oh"),
+    ("clock", "clkwca"),
+    ("minx", "mgmp"),
+    ("pyram", "pyrso"),
+    ("skewb", "skbso"),
+    ("sq-1", "sqrs"),
+    ("222", "222o"),
+    ("pyram", "pyro"),
+    ("skewb", "skbo"),
+    ("clock", "clko"),
+];
+
+/// The key of the session data that only pesto reads.
+pub(crate) const PESTO_KEY: &str = "pesto";
+
+/// The scramble type of sessions without one.
+const DEFAULT_SCRAMBLE_TYPE: &str = "333";
+
+/// The event of a csTimer scramble type, like `333oh`.
+pub fn event_from_scramble_type(scramble_type: &str) -> Option<Event> {
+    let &(str_id, _) = SCRAMBLE_TYPES
+        .iter()
+        .find(|&&(_, name)| name == scramble_type)?;
+
+    Event::ALL
+        .into_iter()
+        .find(|event| event.str_id() == str_id)
+}
+
+/// The csTimer scramble type of the WCA scrambles of `event`.
+pub fn scramble_type(event: Event) -> &'static str {
+    SCRAMBLE_TYPES
+        .iter()
+        .find(|&&(str_id, _)| str_id == event.str_id())
+        .map(|&(_, name)| name)
+        .expect("Every event has a scramble type")
+}
+
+/// The name of the csTimer session of `session` of `event`.
+pub(crate) fn session_name(event: &MaybeCustomEvent, session: &Session) -> String {
+    match (event, session) {
+        (MaybeCustomEvent::Official(event), Session::Main) => event.short_name().to_string(),
+        _ => format!("{}: {}", event.short_name(), session.name()),
+    }
+}
+
+/// The event and session of a csTimer session, the opposite of
+/// [`session_name`].
+///
+/// Sessions that are not named like that are sessions of the event of their
+/// scramble type.
+fn event_and_session(scramble_event: Event, name: &str) -> (ImportedEvent, String) {
+    let (event_name, session) = match name.split_once(": ") {
+        Some((event_name, session)) => (event_name, session),
+        None => (name, Session::Main.name()),
+    };
+
+    if event_name == scramble_event.short_name() {
+        (ImportedEvent::Official(scramble_event), session.to_string())
+    } else if name.contains(": ") {
+        let event = ImportedEvent::Custom {
+            name: event_name.to_string(),
+            scramble_type: Some(scramble_event),
+        };
+        (event, session.to_string())
+    } else {
+        (ImportedEvent::Official(scramble_event), name.to_string())
+    }
+}
+
+/// Imports every session of a csTimer export, all in one transaction.
+///
+/// Each csTimer session goes to the session with the same name of the event
+/// of its scramble type (see the [module docs](self) for the exceptions),
+/// which is created if it doesn't exist.
+pub fn import(db: &mut Db, json: &str) -> eyre::Result<ImportReport> {
+    let (sessions, skipped) = parse(json)?;
+    db.import_sessions(
+        sessions,
+        ImportReport {
+            skipped,
+            ..Default::default()
+        },
+    )
+}
+
+fn parse(json: &str) -> eyre::Result<(Vec<ImportedSession>, Vec<Skipped>)> {
+    let export: Value = serde_json::from_str(json).wrap_err("Invalid csTimer export")?;
+    let export = export
+        .as_object()
+        .ok_or_eyre("csTimer export is not an object")?;
+
+    let session_data: Value = match export
+        .get("properties")
+        .and_then(|properties| properties.get("sessionData"))
+    {
+        Some(Value::String(data)) => serde_json::from_str(data).wrap_err("Invalid session data")?,
+        Some(data) => data.clone(),
+        None => Value::Null,
+    };
+
+    let mut numbered = export
+        .iter()
+        .filter_map(|(key, solves)| {
+            let number = key.strip_prefix("session")?.parse::<usize>().ok()?;
+            Some((number, solves))
+        })
+        .collect::<Vec<_>>();
+    numbered.sort_by_key(|&(number, _)| number);
+
+    let mut sessions = Vec::new();
+    let mut skipped = Vec::new();
+    for (number, solves) in numbered {
+        let data = &session_data[number.to_string()];
+        let name = match &data["name"] {
+            Value::String(name) => name.clone(),
+            Value::Number(name) => name.to_string(),
+            _ => number.to_string(),
+        };
+
+        let solves = solves.as_array().map(Vec::as_slice).unwrap_or_default();
+        if solves.is_empty() {
+            continue;
+        }
+
+        let scramble_type = data["opt"]["scrType"]
+            .as_str()
+            .unwrap_or(DEFAULT_SCRAMBLE_TYPE);
+        let Some(event) = event_from_scramble_type(scramble_type) else {
+            skipped.push(Skipped {
+                session: name,
+                index: None,
+                reason: SkipReason::Unmappable(format!("Unknown scramble type {scramble_type:?}")),
+            });
+            continue;
+        };
+
+        let pesto = &data[PESTO_KEY];
+        let (mut event, session_name) = event_and_session(event, &name);
+        if let ImportedEvent::Custom { scramble_type, .. } = &mut event
+            && pesto["noScrambleType"].as_bool() == Some(true)
+        {
+            *scramble_type = None;
+        }
+
+        let phases: Vec<String> = pesto["phases"]
+            .as_array()
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|phase| phase.as_str().map(str::to_string))
+            .collect();
+
+        let mut session = ImportedSession {
+            event,
+            name: session_name,
+            solves: Vec::new(),
+        };
+
+        for (index, solve) in solves.iter().enumerate() {
+            match parse_solve(solve, &phases) {
+                Ok(solve) => session.solves.push((index, solve)),
+                Err(reason) => skipped.push(Skipped {
+                    session: name.clone(),
+                    index: Some(index),
+                    reason: SkipReason::Unmappable(reason),
+                }),
+            }
+        }
+
+        sessions.push(session);
+    }
+
+    Ok((sessions, skipped))
+}
+
+/// Parses `[[penalty, time_ms, ...], scramble, comment, timestamp]`.
+///
+/// The phases are named like in `phases` if there are as many, or `Phase 1`,
+/// `Phase 2` and so on otherwise.
+fn parse_solve(solve: &Value, phases: &[String]) -> Result<Solve, String> {
+    let penalty = match solve[0][0].as_i64() {
+        Some(0) => Penalty::None,
+        Some(2000) => Penalty::Plus2,
+        Some(-1) => Penalty::DNF,
+        Some(penalty) => return Err(format!("Unknown penalty {penalty}")),
+        None => return Err("Missing penalty".to_string()),
+    };
+
+    let time = Duration::from_millis(solve[0][1].as_u64().ok_or("Missing time")?);
+    let mut marks = solve[0]
+        .as_array()
+        .map(|times| &times[2.min(times.len())..])
+        .unwrap_or_default()
+        .iter()
+        .map(|mark| mark.as_u64().map(Duration::from_millis))
+        .collect::<Option<Vec<_>>>()
+        .ok_or("Invalid phase time")?;
+    marks.reverse();
+
+    if !marks.is_sorted() || marks.last().is_some_and(|&mark| mark > time) {
+        return Err("Phases out of order".to_string());
+    }
+
+    let splits = if marks.is_empty() {
+        Vec::new()
+    } else if phases.len() == marks.len() + 1 {
+        Split::from_marks(phases, &marks, time)
+    } else {
+        let phases: Vec<_> = (1..=marks.len() + 1)
+            .map(|phase| format!("Phase {phase}"))
+            .collect();
+        Split::from_marks(&phases, &marks, time)
+    };
+
+    let timestamp = solve[3].as_i64().ok_or("Missing date")?;
+    let end_date = Timestamp::from_second(timestamp)
+        .map_err(|err| format!("Invalid date: {err}"))?
+        .to_zoned(TimeZone::system());
+
+    Ok(Solve {
+        id: None,
+        time,
+        end_date,
+        scramble: solve[1].as_str().unwrap_or_default().to_string(),
+        penalty,
+        comment: solve[2]
+            .as_str()
+            .filter(|comment| !comment.is_empty())
+            .map(str::to_string),
+        splits,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXPORT: &str = r#"{
+        "session1": [
+            [[0, 12345], "R U R' U'", "", 1700000000],
+            [[2000, 9876], "F2 D", "nice", 1700000060],
+            [[-1, 20000], "L", "", 1700000120],
+            [[7, 1000], "B", "", 1700000180]
+        ],
+        "session2": [[[0, 30000], "R", "", 1700000000]],
+        "session3": [],
+        "properties": {
+            "sessionData": "{\"1\":{\"name\":\"home\",\"opt\":{}},\"2\":{\"name\":2,\"opt\":{\"scrType\":\"333oh\"}}}"
+        }
+    }"#;
+
+    #[test]
+    fn parses_sessions_and_solves() {
+        let (sessions, skipped) = parse(EXPORT).unwrap();
+
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].name, "home");
+        assert_eq!(sessions[0].event, ImportedEvent::Official(Event::Cube3));
+        assert_eq!(sessions[1].name, "2");
+        assert_eq!(sessions[1].event, ImportedEvent::Official(Event::OneHanded));
+
+        let solves = &sessions[0].solves;
+        assert_eq!(solves.len(), 3);
+        assert_eq!(solves[0].1.time, Duration::from_millis(12345));
+        assert_eq!(solves[1].1.penalty, Penalty::Plus2);
+        assert_eq!(solves[1].1.comment.as_deref(), Some("nice"));
+        assert_eq!(solves[2].1.penalty, Penalty::DNF);
+        assert_eq!(solves[2].1.end_date.timestamp().as_second(), 1700000120);
+
+        assert_eq!(
+            skipped,
+            [Skipped {
+                session: "home".to_string(),
+                index: Some(3),
+                reason: SkipReason::Unmappable("Unknown penalty 7".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn multi_phase_times_are_splits() {
+        let solve: Value = serde_json::from_str(r#"[[0, 10000, 7000, 2000], "", "", 0]"#).unwrap();
+        let splits = |phases: &[String]| {
+            parse_solve(&solve, phases)
+                .unwrap()
+                .splits
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            splits(&[]),
+            ["Phase 1 02.000", "Phase 2 05.000", "Phase 3 03.000"]
+        );
+        let phases = ["Cross", "F2L", "LL"].map(str::to_string);
+        assert_eq!(splits(&phases), ["Cross 02.000", "F2L 05.000", "LL 03.000"]);
+    }
+
+    #[test]
+    fn scramble_types_round_trip() {
+        for event in Event::ALL {
+            assert_eq!(event_from_scramble_type(scramble_type(event)), Some(event));
+        }
+    }
+}
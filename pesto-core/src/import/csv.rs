@@ -0,0 +1,205 @@
+//! Importing pesto's generic CSV format, which is meant to be easy to produce
+//! from (or read into) other apps and spreadsheets.
+//!
+//! The file is comma separated, quoted like [RFC 4180], and starts with this
+//! header (the columns must be in this order):
+//!
+//! ```text
+//! event,scramble_type,session,time_ms,penalty,end_date,scramble,comment,splits
+//! 3x3,,main,12345,,2025-01-01T10:00:00+01:00[Europe/Madrid],R U R' U',,
+//! Relay,4x4,home,60000,+2,2025-01-01T10:05:00+01:00[Europe/Madrid],Rw,nice,
+//! 3BLD,,main,45000,,2025-01-01T10:09:00+01:00[Europe/Madrid],F,,Memo:20000;Execution:25000
+//! ```
+//!
+//! - `event`: the short name of an official event (like `3x3` or `OH`), or
+//!   the name of a custom event, which is created if it doesn't exist.
+//! - `scramble_type`: for custom events, the short name of the official event
+//!   whose scrambles they use. Empty otherwise.
+//! - `session`: the name of the session, `main` for the main session.
+//! - `time_ms`: the time in milliseconds, without the +2.
+//! - `penalty`: empty, `+2` or `DNF`.
+//! - `end_date`: when the solve ended, according to [RFC 9557] (so it keeps
+//!   the time zone).
+//! - `comment`: empty if there is none.
+//! - `splits`: the phases of the solve, like `Memo:20000;Execution:25000`,
+//!   with the time of each one in milliseconds. Empty if the solve wasn't
+//!   split. This column is optional, so files without it can be imported too.
+//!
+//! Every field of a solve is kept, so exporting with [`crate::export::csv`]
+//! and importing again is lossless.
+//!
+//! [RFC 4180]: https://datatracker.ietf.org/doc/html/rfc4180
+//! [RFC 9557]: https://datatracker.ietf.org/doc/html/rfc9557
+
+use std::time::Duration;
+
+use color_eyre::eyre::{self, Context as _};
+use norcina::Event;
+
+use crate::{
+    Db, Solve,
+    import::{ImportReport, ImportedEvent, ImportedSession, SkipReason, Skipped},
+    solve::{Penalty, Split},
+};
+
+pub(crate) const HEADER: [&str; 9] = [
+    "event",
+    "scramble_type",
+    "session",
+    "time_ms",
+    "penalty",
+    "end_date",
+    "scramble",
+    "comment",
+    "splits",
+];
+
+pub(crate) const fn penalty_name(penalty: Penalty) -> &'static str {
+    match penalty {
+        Penalty::None => "",
+        Penalty::Plus2 => "+2",
+        Penalty::DNF => "DNF",
+    }
+}
+
+fn penalty_from_name(name: &str) -> Option<Penalty> {
+    Some(match name {
+        "" => Penalty::None,
+        "+2" => Penalty::Plus2,
+        "DNF" => Penalty::DNF,
+        _ => return None,
+    })
+}
+
+fn official_event(short_name: &str) -> Option<Event> {
+    Event::ALL
+        .into_iter()
+        .find(|event| event.short_name().eq_ignore_ascii_case(short_name))
+}
+
+/// Imports a CSV file in the generic format, all in one transaction.
+pub fn import(db: &mut Db, csv: &str) -> eyre::Result<ImportReport> {
+    let (sessions, skipped) = parse(csv)?;
+    db.import_sessions(
+        sessions,
+        ImportReport {
+            skipped,
+            ..Default::default()
+        },
+    )
+}
+
+fn parse(csv: &str) -> eyre::Result<(Vec<ImportedSession>, Vec<Skipped>)> {
+    let mut reader = csv::Reader::from_reader(csv.as_bytes());
+    let headers = reader.headers().wrap_err("Invalid CSV")?;
+    if headers.iter().ne(HEADER)
+        && headers
+            .iter()
+            .ne(HEADER[..HEADER.len() - 1].iter().copied())
+    {
+        eyre::bail!("Expected the CSV header to be {}", HEADER.join(","));
+    }
+
+    let mut sessions = Vec::<ImportedSession>::new();
+    let mut skipped = Vec::new();
+    for (index, record) in reader.records().enumerate() {
+        let record = record.wrap_err("Invalid CSV")?;
+        let field = |i: usize| record.get(i).unwrap_or_default();
+
+        let event = match official_event(field(0)) {
+            Some(event) => ImportedEvent::Official(event),
+            None => ImportedEvent::Custom {
+                name: field(0).to_string(),
+                scramble_type: official_event(field(1)),
+            },
+        };
+        let name = field(2).to_string();
+
+        let solve = match parse_solve(&record) {
+            Ok(solve) => solve,
+            Err(reason) => {
+                skipped.push(Skipped {
+                    session: name,
+                    index: Some(index),
+                    reason: SkipReason::Unmappable(reason),
+                });
+                continue;
+            }
+        };
+
+        match sessions
+            .iter_mut()
+            .find(|session| session.event == event && session.name == name)
+        {
+            Some(session) => session.solves.push((index, solve)),
+            None => sessions.push(ImportedSession {
+                event,
+                name,
+                solves: vec![(index, solve)],
+            }),
+        }
+    }
+
+    Ok((sessions, skipped))
+}
+
+fn parse_solve(record: &csv::StringRecord) -> Result<Solve, String> {
+    let field = |i: usize| record.get(i).unwrap_or_default();
+
+    if field(0).trim().is_empty() {
+        return Err("Missing event".to_string());
+    }
+
+    Ok(Solve {
+        id: None,
+        time: Duration::from_millis(
+            field(3)
+                .parse()
+                .map_err(|_| format!("Invalid time {:?}", field(3)))?,
+        ),
+        end_date: field(5)
+            .parse()
+            .map_err(|err| format!("Invalid date {:?}: {err}", field(5)))?,
+        scramble: field(6).to_string(),
+        penalty: penalty_from_name(field(4))
+            .ok_or_else(|| format!("Unknown penalty {:?}", field(4)))?,
+        comment: Some(field(7))
+            .filter(|comment| !comment.is_empty())
+            .map(str::to_string),
+        splits: parse_splits(field(8))?,
+    })
+}
+
+/// Parses splits like `Memo:20000;Execution:25000`.
+fn parse_splits(splits: &str) -> Result<Vec<Split>, String> {
+    if splits.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    splits
+        .split(';')
+        .map(|split| {
+            let (name, time) = split
+                .rsplit_once(':')
+                .ok_or_else(|| format!("Invalid split {split:?}"))?;
+            let time = time
+                .parse()
+                .map_err(|_| format!("Invalid split time {time:?}"))?;
+
+            Ok(Split {
+                name: name.to_string(),
+                time: Duration::from_millis(time),
+            })
+        })
+        .collect()
+}
+
+/// Formats splits like `Memo:20000;Execution:25000`, the opposite of
+/// [`parse_splits`].
+pub(crate) fn format_splits(splits: &[Split]) -> String {
+    splits
+        .iter()
+        .map(|split| format!("{}:{}", split.name, split.time.as_millis()))
+        .collect::<Vec<_>>()
+        .join(";")
+}
@@ -0,0 +1,337 @@
+//! Importing the backups of [Twisty Timer](https://github.com/aricneto/TwistyTimer).
+//!
+//! A backup has a header and then a line for each solve, with the fields
+//! quoted and separated by `;`:
+//!
+//! ```text
+//! Puzzle;Category;Time(millis);Date(millis);Scramble;Penalty;Comment
+//! "333";"Normal";"12345";"1700000000000";"R U R' U'";"0";""
+//! ```
+//!
+//! Twisty Timer only has puzzles, not events, and a category for each
+//! session. The mapping is:
+//! - Each puzzle is the event of that puzzle (like `333` for 3x3), and the
+//!   `Normal` category is the main session.
+//! - Events that use the same puzzle as another one (3BLD, OH, FM...) and
+//!   custom events have categories like `OH: main`, with the name of the
+//!   event and then the name of the session. Custom events use the puzzle of
+//!   their scramble type, or `333` if they have none.
+//! - Penalties are `0` (none), `1` (+2) and `2` (DNF), and times include the
+//!   two seconds of +2s.
+//!
+//! Twisty Timer can't hold everything, so importing an export doesn't always
+//! give back the same thing:
+//! - Custom events without a scramble type are exported as `333`, and come
+//!   back with 3x3 as their scramble type.
+//! - A custom session called `Normal` is exported as the main category, so
+//!   its solves come back in the main session.
+//! - The event is split from the session at the first `: `, so the solves of
+//!   a custom event called `Relay: 2-4` come back in `Relay`, with `2-4: `
+//!   before the category of each session (like `2-4: Normal` for the main
+//!   one).
+//! - Splits are dropped.
+//! - End dates are Unix timestamps in milliseconds, so they come back rounded
+//!   to the millisecond and in the time zone of the system.
+//!
+//! See [`crate::export::twisty_timer`] for the opposite.
+
+use std::time::Duration;
+
+use color_eyre::eyre::{self, Context as _};
+use jiff::{Timestamp, tz::TimeZone};
+use norcina::Event;
+
+use crate::{
+    Db, Solve,
+    event::{MaybeCustomEvent, Session},
+    import::{ImportReport, ImportedEvent, ImportedSession, SkipReason, Skipped},
+    solve::Penalty,
+};
+
+pub(crate) const HEADER: [&str; 7] = [
+    "Puzzle",
+    "Category",
+    "Time(millis)",
+    "Date(millis)",
+    "Scramble",
+    "Penalty",
+    "Comment",
+];
+
+/// The category of the main session.
+const MAIN_CATEGORY: &str = "Normal";
+
+/// The puzzle of each event, and whether the event has its name in the
+/// category because it shares the puzzle with another event.
+const PUZZLES: [(Event, &str, bool); 17] = [
+    (Event::Cube2, "222", false),
+    (Event::Cube3, "333", false),
+    (Event::Cube4, "444", false),
+    (Event::Cube5, "555", false),
+    (Event::Cube6, "666", false),
+    (Event::Cube7, "777", false),
+    (Event::Blind3, "333", true),
+    (Event::Blind4, "444", true),
+    (Event::Blind5, "555", true),
+    (Event::Multiblind, "333", true),
+    (Event::FewestMoves, "333", true),
+    (Event::OneHanded, "333", true),
+    (Event::Clock, "clock", false),
+    (Event::Megaminx, "mega", false),
+    (Event::Pyraminx, "pyra", false),
+    (Event::Skewb, "skewb", false),
+    (Event::Square1, "sq1", false),
+];
+
+pub(crate) const fn penalty_index(penalty: Penalty) -> u8 {
+    match penalty {
+        Penalty::None => 0,
+        Penalty::Plus2 => 1,
+        Penalty::DNF => 2,
+    }
+}
+
+fn penalty_from_index(index: &str) -> Option<Penalty> {
+    Some(match index {
+        "0" => Penalty::None,
+        "1" => Penalty::Plus2,
+        "2" => Penalty::DNF,
+        _ => return None,
+    })
+}
+
+/// The puzzle and category of the solves of `session` of `event`.
+pub(crate) fn puzzle_and_category(
+    event: &MaybeCustomEvent,
+    session: &Session,
+) -> (&'static str, String) {
+    let session = match session {
+        Session::Main => MAIN_CATEGORY,
+        Session::Custom { name, .. } => name,
+    };
+
+    let (puzzle, prefixed) = match event {
+        &MaybeCustomEvent::Official(event) => {
+            let &(_, puzzle, prefixed) = PUZZLES
+                .iter()
+                .find(|&&(e, ..)| e == event)
+                .expect("Every event has a puzzle");
+            (puzzle, prefixed)
+        }
+        MaybeCustomEvent::Unofficial(event) => {
+            let puzzle = event
+                .scramble_type
+                .and_then(|scramble_type| PUZZLES.iter().find(|&&(e, ..)| e == scramble_type))
+                .map_or("333", |&(_, puzzle, _)| puzzle);
+            (puzzle, true)
+        }
+    };
+
+    let category = if prefixed {
+        format!("{}: {session}", event.short_name())
+    } else {
+        session.to_string()
+    };
+
+    (puzzle, category)
+}
+
+/// The event and session name of a puzzle and category.
+fn event_and_session(puzzle: &str, category: &str) -> Option<(ImportedEvent, String)> {
+    let session = |name: &str| {
+        if name == MAIN_CATEGORY {
+            Session::Main.name().to_string()
+        } else {
+            name.to_string()
+        }
+    };
+
+    let puzzle_event = PUZZLES
+        .iter()
+        .find(|&&(_, p, prefixed)| p == puzzle && !prefixed)
+        .map(|&(event, ..)| event)?;
+
+    let Some((event_name, session_name)) = category.split_once(": ") else {
+        return Some((ImportedEvent::Official(puzzle_event), session(category)));
+    };
+
+    let official = PUZZLES
+        .iter()
+        .find(|&&(event, p, prefixed)| p == puzzle && prefixed && event.short_name() == event_name);
+
+    let event = match official {
+        Some(&(event, ..)) => ImportedEvent::Official(event),
+        None => ImportedEvent::Custom {
+            name: event_name.to_string(),
+            scramble_type: Some(puzzle_event),
+        },
+    };
+
+    Some((event, session(session_name)))
+}
+
+/// Imports a Twisty Timer backup, all in one transaction.
+pub fn import(db: &mut Db, backup: &str) -> eyre::Result<ImportReport> {
+    let (sessions, skipped) = parse(backup)?;
+    db.import_sessions(
+        sessions,
+        ImportReport {
+            skipped,
+            ..Default::default()
+        },
+    )
+}
+
+fn parse(backup: &str) -> eyre::Result<(Vec<ImportedSession>, Vec<Skipped>)> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(b';')
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(backup.as_bytes());
+
+    let mut sessions = Vec::<ImportedSession>::new();
+    let mut skipped = Vec::new();
+    for (index, record) in reader.records().enumerate() {
+        let record = record.wrap_err("Invalid Twisty Timer backup")?;
+        if index == 0 && record.get(0) == Some(HEADER[0]) {
+            continue;
+        }
+
+        let field = |i: usize| record.get(i).unwrap_or_default();
+        let (puzzle, category) = (field(0), field(1));
+        let Some((event, name)) = event_and_session(puzzle, category) else {
+            skipped.push(Skipped {
+                session: category.to_string(),
+                index: Some(index),
+                reason: SkipReason::Unmappable(format!("Unknown puzzle {puzzle:?}")),
+            });
+            continue;
+        };
+
+        let solve = match parse_solve(&record) {
+            Ok(solve) => solve,
+            Err(reason) => {
+                skipped.push(Skipped {
+                    session: category.to_string(),
+                    index: Some(index),
+                    reason: SkipReason::Unmappable(reason),
+                });
+                continue;
+            }
+        };
+
+        match sessions
+            .iter_mut()
+            .find(|session| session.event == event && session.name == name)
+        {
+            Some(session) => session.solves.push((index, solve)),
+            None => sessions.push(ImportedSession {
+                event,
+                name,
+                solves: vec![(index, solve)],
+            }),
+        }
+    }
+
+    Ok((sessions, skipped))
+}
+
+fn parse_solve(record: &csv::StringRecord) -> Result<Solve, String> {
+    let field = |i: usize| record.get(i).unwrap_or_default();
+
+    let time = field(2)
+        .parse::<u64>()
+        .map_err(|_| format!("Invalid time {:?}", field(2)))?;
+    let date = field(3)
+        .parse::<i64>()
+        .map_err(|_| format!("Invalid date {:?}", field(3)))?;
+    let penalty =
+        penalty_from_index(field(5)).ok_or_else(|| format!("Unknown penalty {:?}", field(5)))?;
+
+    let time = match penalty {
+        Penalty::Plus2 => time.saturating_sub(2000),
+        Penalty::None | Penalty::DNF => time,
+    };
+
+    let end_date = Timestamp::from_millisecond(date)
+        .map_err(|err| format!("Invalid date: {err}"))?
+        .to_zoned(TimeZone::system());
+
+    Ok(Solve {
+        id: None,
+        time: Duration::from_millis(time),
+        end_date,
+        scramble: field(4).to_string(),
+        penalty,
+        comment: Some(field(6))
+            .filter(|comment| !comment.is_empty())
+            .map(str::to_string),
+        splits: Vec::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_puzzles_and_categories() {
+        let backup = "Puzzle;Category;Time(millis);Date(millis);Scramble;Penalty;Comment
+\"333\";\"Normal\";\"12345\";\"1700000000000\";\"R U\";\"0\";\"\"
+\"333\";\"OH: home\";\"22000\";\"1700000001000\";\"F\";\"1\";\"ok\"
+\"sq1\";\"comp\";\"15000\";\"1700000002000\";\"(1, 0)\";\"2\";\"\"
+\"444\";\"Relay: main\";\"60000\";\"1700000003000\";\"Rw\";\"0\";\"\"
+\"kilo\";\"Normal\";\"1\";\"1\";\"\";\"0\";\"\"
+";
+
+        let (sessions, skipped) = parse(backup).unwrap();
+        let summary = sessions
+            .iter()
+            .map(|session| (session.event.clone(), session.name.as_str()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            summary,
+            [
+                (ImportedEvent::Official(Event::Cube3), "main"),
+                (ImportedEvent::Official(Event::OneHanded), "home"),
+                (ImportedEvent::Official(Event::Square1), "comp"),
+                (
+                    ImportedEvent::Custom {
+                        name: "Relay".to_string(),
+                        scramble_type: Some(Event::Cube4)
+                    },
+                    "main"
+                ),
+            ]
+        );
+
+        let plus2 = &sessions[1].solves[0].1;
+        assert_eq!(plus2.penalty, Penalty::Plus2);
+        assert_eq!(plus2.time, Duration::from_millis(20000));
+        assert_eq!(plus2.comment.as_deref(), Some("ok"));
+        assert_eq!(skipped.len(), 1);
+    }
+
+    #[test]
+    fn categories_round_trip() {
+        let sessions = [
+            Session::Main,
+            Session::Custom {
+                name: "home".to_string(),
+                id: 1,
+            },
+        ];
+
+        for event in Event::ALL {
+            for session in &sessions {
+                let (puzzle, category) =
+                    puzzle_and_category(&MaybeCustomEvent::Official(event), session);
+                assert_eq!(
+                    event_and_session(puzzle, &category),
+                    Some((ImportedEvent::Official(event), session.name().to_string()))
+                );
+            }
+        }
+    }
+}
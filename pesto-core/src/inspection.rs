@@ -0,0 +1,72 @@
+//! WCA inspection: 15 seconds to look at the puzzle before starting the
+//! solve, as in [regulation A3].
+//!
+//! [regulation A3]: https://www.worldcubeassociation.org/regulations/#A3
+
+use std::time::Duration;
+
+use norcina::Event;
+
+use crate::{event::MaybeCustomEvent, solve::Penalty};
+
+/// How long inspection lasts without a penalty.
+pub const DURATION: Duration = Duration::from_secs(15);
+
+/// After this long, the solve is a DNF. Between [`DURATION`] and this, it's
+/// a +2.
+pub const DNF_AFTER: Duration = Duration::from_secs(17);
+
+/// When judges call out how much time has passed.
+pub const WARNINGS: [Duration; 2] = [Duration::from_secs(8), Duration::from_secs(12)];
+
+/// The penalty of starting the solve after inspecting for `elapsed`.
+pub fn penalty(elapsed: Duration) -> Penalty {
+    if elapsed > DNF_AFTER {
+        Penalty::DNF
+    } else if elapsed > DURATION {
+        Penalty::Plus2
+    } else {
+        Penalty::None
+    }
+}
+
+/// Whether `event` has inspection by default. Blindfolded events don't,
+/// since memorizing is part of the time, and neither does FMC.
+pub fn is_enabled_by_default(event: &MaybeCustomEvent) -> bool {
+    !matches!(
+        event.scramble_type(),
+        Some(
+            Event::Blind3 | Event::Blind4 | Event::Blind5 | Event::Multiblind | Event::FewestMoves
+        )
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Db;
+
+    #[test]
+    fn penalties() {
+        assert_eq!(penalty(Duration::from_millis(14_999)), Penalty::None);
+        assert_eq!(penalty(DURATION), Penalty::None);
+        assert_eq!(penalty(Duration::from_millis(15_001)), Penalty::Plus2);
+        assert_eq!(penalty(DNF_AFTER), Penalty::Plus2);
+        assert_eq!(penalty(Duration::from_millis(17_001)), Penalty::DNF);
+    }
+
+    #[test]
+    fn enabled_per_event() {
+        let mut db = Db::in_memory();
+        let cube3 = MaybeCustomEvent::Official(Event::Cube3);
+        let blind3 = MaybeCustomEvent::Official(Event::Blind3);
+
+        assert!(db.inspection(&cube3).unwrap());
+        assert!(!db.inspection(&blind3).unwrap());
+
+        db.set_inspection(&cube3, false).unwrap();
+        db.set_inspection(&blind3, true).unwrap();
+        assert!(!db.inspection(&cube3).unwrap());
+        assert!(db.inspection(&blind3).unwrap());
+    }
+}
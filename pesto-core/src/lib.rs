@@ -1,6 +1,11 @@
 mod db;
-pub use db::Db;
+pub use db::{Db, OrphanedSolves};
 
 pub mod event;
+pub mod export;
+pub mod import;
+pub mod inspection;
+pub mod pb;
 pub mod solve;
+pub mod stats;
 pub use solve::Solve;
@@ -0,0 +1,274 @@
+//! Personal bests (PBs): the best single and the best of each window, and
+//! how they improved over time.
+
+use std::{fmt, str::FromStr, time::Duration};
+
+use color_eyre::eyre;
+use jiff::Zoned;
+
+use crate::{
+    Solve,
+    stats::{Rolling, Stat, Window},
+};
+
+/// Something that can be a personal best.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Record {
+    Single,
+    Window(Window),
+}
+
+impl Record {
+    /// The records that are tracked for every event and session.
+    pub const ALL: [Self; 5] = [
+        Self::Single,
+        Self::Window(Window::MO3),
+        Self::Window(Window::AO5),
+        Self::Window(Window::AO12),
+        Self::Window(Window::AO100),
+    ];
+
+    fn window(self) -> Window {
+        match self {
+            Self::Single => Window::mean(1),
+            Self::Window(window) => window,
+        }
+    }
+}
+
+impl fmt::Display for Record {
+    /// Like `single` or `ao5`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Single => write!(f, "single"),
+            Self::Window(window) => write!(f, "{window}"),
+        }
+    }
+}
+
+impl FromStr for Record {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "single" {
+            return Ok(Self::Single);
+        }
+
+        let window = match s.split_at_checked(2) {
+            Some(("mo", size)) => Window::mean(size.parse()?),
+            Some(("ao", size)) => Window::average(size.parse()?),
+            _ => eyre::bail!("Invalid record {s:?}"),
+        };
+
+        eyre::ensure!(window.is_valid(), "Invalid window size in {s:?}");
+        Ok(Self::Window(window))
+    }
+}
+
+/// A record that was broken.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pb {
+    pub record: Record,
+    pub time: Duration,
+    /// When the solve that broke the record was finished.
+    pub end_date: Zoned,
+}
+
+/// A record broken by a new solve, and the PB it improved on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BrokenRecord {
+    pub record: Record,
+    /// `None` if there was no PB before.
+    pub previous: Option<Duration>,
+    pub time: Duration,
+    /// Whether it's also better than the PBs of every other session of the
+    /// event.
+    pub event_pb: bool,
+}
+
+/// The records broken by a solve.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PbEvent {
+    pub broken: Vec<BrokenRecord>,
+}
+
+impl PbEvent {
+    /// Whether any record was broken.
+    pub fn is_pb(&self) -> bool {
+        !self.broken.is_empty()
+    }
+
+    pub fn get(&self, record: Record) -> Option<&BrokenRecord> {
+        self.broken.iter().find(|broken| broken.record == record)
+    }
+
+    /// Whether any record was broken among every session of the event.
+    pub fn is_event_pb(&self) -> bool {
+        self.broken.iter().any(|broken| broken.event_pb)
+    }
+}
+
+impl fmt::Display for PbEvent {
+    /// Like `single 9.870 (was 10.120), ao5 11.300`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, broken) in self.broken.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+
+            write!(f, "{} {}", broken.record, Stat::Time(broken.time))?;
+            if let Some(previous) = broken.previous {
+                write!(f, " (was {})", Stat::Time(previous))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The records broken by the last of `solves`, given the previous PBs of the
+/// session and of the whole event.
+///
+/// Only the last solves are needed, as many as the biggest window.
+pub(crate) fn broken_records(
+    solves: &[Solve],
+    previous: impl Fn(Record) -> Option<Duration>,
+    event_previous: impl Fn(Record) -> Option<Duration>,
+) -> PbEvent {
+    let stats = solves.iter().map(Stat::from).collect::<Vec<_>>();
+    let broken = Record::ALL
+        .into_iter()
+        .filter_map(|record| {
+            let time = record.window().compute(&stats)?.time()?;
+            let previous = previous(record);
+            previous
+                .is_none_or(|previous| time < previous)
+                .then(|| BrokenRecord {
+                    record,
+                    previous,
+                    time,
+                    event_pb: event_previous(record).is_none_or(|previous| time < previous),
+                })
+        })
+        .collect();
+
+    PbEvent { broken }
+}
+
+/// Every time `record` was broken over `solves`, in order.
+pub fn progression(solves: &[Solve], record: Record) -> Vec<Pb> {
+    let mut rolling = Rolling::new(record.window());
+    let mut pbs = Vec::<Pb>::new();
+    for solve in solves {
+        let Some(Stat::Time(time)) = rolling.push(Stat::from(solve)) else {
+            continue;
+        };
+
+        if pbs.last().is_none_or(|pb| time < pb.time) {
+            pbs.push(Pb {
+                record,
+                time,
+                end_date: solve.end_date.clone(),
+            });
+        }
+    }
+
+    pbs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solve::Penalty;
+
+    fn solves(times: &[(u64, Penalty)]) -> Vec<Solve> {
+        times
+            .iter()
+            .map(|&(secs, penalty)| Solve {
+                penalty,
+                ..Solve::new(Duration::from_secs(secs), String::new())
+            })
+            .collect()
+    }
+
+    fn secs(secs: u64) -> Option<Duration> {
+        Some(Duration::from_secs(secs))
+    }
+
+    #[test]
+    fn records_round_trip() {
+        for record in Record::ALL {
+            assert_eq!(record.to_string().parse::<Record>().unwrap(), record);
+        }
+
+        assert!("ao2".parse::<Record>().is_err());
+        assert!("mo0".parse::<Record>().is_err());
+        assert!("best".parse::<Record>().is_err());
+    }
+
+    #[test]
+    fn only_better_times_break_records() {
+        let solves = solves(&[
+            (12, Penalty::None),
+            (10, Penalty::None),
+            (11, Penalty::None),
+        ]);
+
+        let first = broken_records(&solves[..1], |_| None, |_| None);
+        assert_eq!(
+            first.broken,
+            [BrokenRecord {
+                record: Record::Single,
+                previous: None,
+                time: Duration::from_secs(12),
+                event_pb: true,
+            }]
+        );
+
+        // The mo3 is 11, and the single 11 is not better than 10.
+        let last = broken_records(
+            &solves,
+            |record| (record == Record::Single).then(|| Duration::from_secs(10)),
+            |_| secs(5),
+        );
+        assert_eq!(last.broken.len(), 1);
+        let mo3 = last.get(Record::Window(Window::MO3)).unwrap();
+        assert_eq!(mo3.time, Duration::from_secs(11));
+        assert!(!last.is_event_pb());
+    }
+
+    #[test]
+    fn dnfs_dont_break_records() {
+        let solves = solves(&[(10, Penalty::None), (9, Penalty::DNF), (8, Penalty::Plus2)]);
+        assert!(!broken_records(&solves[..2], |_| None, |_| None).is_pb());
+
+        // The +2 makes it a 10, which is not better than the 10.
+        let last = broken_records(&solves, |_| secs(10), |_| secs(10));
+        assert!(!last.is_pb());
+    }
+
+    #[test]
+    fn progression_of_records() {
+        let solves = solves(&[
+            (12, Penalty::None),
+            (14, Penalty::None),
+            (10, Penalty::None),
+            (11, Penalty::DNF),
+            (9, Penalty::None),
+            (15, Penalty::None),
+        ]);
+        let times = |record: Record| {
+            progression(&solves, record)
+                .into_iter()
+                .map(|pb| pb.time.as_secs())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(times(Record::Single), [12, 10, 9]);
+        // 12, 14 and 10 make 12, and every mo3 after that has the DNF.
+        assert_eq!(times(Record::Window(Window::MO3)), [12]);
+        // 12, 14 and 10 again, and then 14, 10 and 15.
+        assert_eq!(times(Record::Window(Window::AO5)), [12]);
+        assert!(times(Record::Window(Window::AO12)).is_empty());
+    }
+}
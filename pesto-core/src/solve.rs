@@ -31,25 +31,71 @@ impl Penalty {
     }
 }
 
+/// The time of one phase of a solve, like the cross in CFOP or the memo in
+/// BLD.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Split {
+    pub name: String,
+    pub time: Duration,
+}
+
+impl Split {
+    /// The splits of a solve of `time` that went through `phases`, where
+    /// `marks` are the times (since the start) when each phase but the last
+    /// ended.
+    pub fn from_marks(phases: &[String], marks: &[Duration], time: Duration) -> Vec<Self> {
+        let ends = marks.iter().copied().chain([time]);
+        let starts = [Duration::ZERO].into_iter().chain(marks.iter().copied());
+
+        phases
+            .iter()
+            .zip(starts.zip(ends))
+            .map(|(name, (start, end))| Self {
+                name: name.clone(),
+                time: end.saturating_sub(start),
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for Split {
+    /// Like `F2L 05.120`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ", self.name)?;
+        format_duration_text(f, self.time)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Solve {
+    /// The id of the solve in the database, or `None` if it hasn't been
+    /// inserted yet.
+    pub id: Option<usize>,
     /// The time that the solve took.
     pub time: Duration,
     /// The moment the solve was finished.
     pub end_date: Zoned,
     pub scramble: String,
     pub penalty: Penalty,
+    pub comment: Option<String>,
+    /// The phases of the solve, in order, which add up to `time`. Empty if
+    /// the solve wasn't split.
+    pub splits: Vec<Split>,
 }
 
 impl Solve {
     pub fn new(time: Duration, scramble: String) -> Self {
         Self {
+            id: None,
             time,
             end_date: Zoned::now(),
             scramble,
             penalty: Penalty::None,
+            comment: None,
+            splits: Vec::new(),
         }
     }
+
     /// The moment the solve was started.
     pub fn start_date(&self) -> Zoned {
         self.end_date.checked_sub(self.time).unwrap()
@@ -70,7 +116,7 @@ impl fmt::Display for Solve {
     }
 }
 
-fn format_duration_text(f: &mut fmt::Formatter<'_>, duration: Duration) -> fmt::Result {
+pub(crate) fn format_duration_text(f: &mut fmt::Formatter<'_>, duration: Duration) -> fmt::Result {
     if duration < Duration::from_secs(60) {
         write!(
             f,
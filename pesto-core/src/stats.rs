@@ -0,0 +1,563 @@
+//! WCA-style statistics of a sequence of solves: means (like mo3) and trimmed
+//! averages (like ao5, ao12 and ao100).
+//!
+//! Statistics are kept up to date incrementally with [`Rolling`], so adding a
+//! solve doesn't need to go over every previous one.
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fmt,
+    time::Duration,
+};
+
+use crate::{
+    Solve,
+    solve::{Penalty, format_duration_text},
+};
+
+/// The result of a solve (with its penalty applied) or of a statistic.
+///
+/// DNFs are worse than every time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Stat {
+    Time(Duration),
+    DNF,
+}
+
+impl Stat {
+    pub fn time(self) -> Option<Duration> {
+        match self {
+            Self::Time(time) => Some(time),
+            Self::DNF => None,
+        }
+    }
+}
+
+impl From<&Solve> for Stat {
+    fn from(solve: &Solve) -> Self {
+        match solve.penalty {
+            Penalty::None => Self::Time(solve.time),
+            Penalty::Plus2 => Self::Time(solve.time + Duration::from_secs(2)),
+            Penalty::DNF => Self::DNF,
+        }
+    }
+}
+
+impl fmt::Display for Stat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Time(time) => format_duration_text(f, *time),
+            Self::DNF => write!(f, "DNF"),
+        }
+    }
+}
+
+/// How a statistic is computed over its solves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Kind {
+    /// The plain mean. Any DNF makes it a DNF.
+    Mean,
+    /// The mean without the best and worst solves (5% on each side, at least
+    /// one). DNFs count as the worst solves, so there can be as many DNFs as
+    /// trimmed solves.
+    Average,
+}
+
+/// A statistic over the last `size` solves.
+///
+/// Not every size makes sense: a mean needs at least one solve, and an
+/// average needs some solves left after trimming (at least three). See
+/// [`Window::is_valid`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Window {
+    pub size: usize,
+    pub kind: Kind,
+}
+
+impl Window {
+    pub const MO3: Self = Self::mean(3);
+    pub const AO5: Self = Self::average(5);
+    pub const AO12: Self = Self::average(12);
+    pub const AO100: Self = Self::average(100);
+
+    pub const fn mean(size: usize) -> Self {
+        Self {
+            size,
+            kind: Kind::Mean,
+        }
+    }
+
+    pub const fn average(size: usize) -> Self {
+        Self {
+            size,
+            kind: Kind::Average,
+        }
+    }
+
+    /// Whether there are solves left to compute the statistic with.
+    pub const fn is_valid(self) -> bool {
+        self.size > 2 * self.trim()
+    }
+
+    /// The number of solves that are ignored on each side.
+    pub const fn trim(self) -> usize {
+        match self.kind {
+            Kind::Mean => 0,
+            Kind::Average => self.size.div_ceil(20),
+        }
+    }
+
+    /// The statistic of the last solves of `stats`, or `None` if there are
+    /// less than [`Window::size`] or the window is not
+    /// [valid](Window::is_valid).
+    pub fn compute(self, stats: &[Stat]) -> Option<Stat> {
+        if !self.is_valid() {
+            return None;
+        }
+
+        let start = stats.len().checked_sub(self.size)?;
+        let mut window = stats[start..].to_vec();
+        window.sort();
+
+        let trim = self.trim();
+        let counting = &window[trim..self.size - trim];
+        let times = counting
+            .iter()
+            .map(|stat| stat.time())
+            .collect::<Option<Vec<_>>>();
+
+        Some(match times {
+            Some(times) => Stat::Time(times.iter().sum::<Duration>() / times.len() as u32),
+            None => Stat::DNF,
+        })
+    }
+}
+
+impl fmt::Display for Window {
+    /// Like `mo3` or `ao12`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            Kind::Mean => write!(f, "mo{}", self.size),
+            Kind::Average => write!(f, "ao{}", self.size),
+        }
+    }
+}
+
+/// A statistic that is updated with each new solve.
+#[derive(Debug, Clone)]
+pub struct Rolling {
+    window: Window,
+    last: VecDeque<Stat>,
+    /// The same as `last`, sorted, with the number of times each one appears.
+    sorted: BTreeMap<Stat, usize>,
+    /// The sum of every time in `last` that is not a DNF.
+    sum: Duration,
+    dnfs: usize,
+    history: Vec<Option<Stat>>,
+    best: Option<Stat>,
+}
+
+impl Rolling {
+    pub fn new(window: Window) -> Self {
+        Self {
+            window,
+            last: VecDeque::with_capacity(window.size + 1),
+            sorted: BTreeMap::new(),
+            sum: Duration::ZERO,
+            dnfs: 0,
+            history: Vec::new(),
+            best: None,
+        }
+    }
+
+    pub fn window(&self) -> Window {
+        self.window
+    }
+
+    /// Adds a solve, and returns the new value of the statistic.
+    pub fn push(&mut self, stat: Stat) -> Option<Stat> {
+        self.insert(stat);
+        if self.last.len() > self.window.size {
+            let oldest = self.last.pop_front().expect("There is more than one");
+            self.remove(oldest);
+        }
+
+        let current = self.compute();
+        self.history.push(current);
+        if let Some(current) = current {
+            self.best = Some(self.best.map_or(current, |best| best.min(current)));
+        }
+
+        current
+    }
+
+    fn insert(&mut self, stat: Stat) {
+        self.last.push_back(stat);
+        *self.sorted.entry(stat).or_default() += 1;
+        match stat {
+            Stat::Time(time) => self.sum += time,
+            Stat::DNF => self.dnfs += 1,
+        }
+    }
+
+    fn remove(&mut self, stat: Stat) {
+        let count = self.sorted.get_mut(&stat).expect("Stat is in the window");
+        *count -= 1;
+        if *count == 0 {
+            self.sorted.remove(&stat);
+        }
+
+        match stat {
+            Stat::Time(time) => self.sum -= time,
+            Stat::DNF => self.dnfs -= 1,
+        }
+    }
+
+    /// Only looks at the trimmed solves, instead of the whole window.
+    fn compute(&self) -> Option<Stat> {
+        if !self.window.is_valid() || self.last.len() < self.window.size {
+            return None;
+        }
+
+        let trim = self.window.trim();
+        if self.dnfs > trim {
+            return Some(Stat::DNF);
+        }
+
+        let sorted = self
+            .sorted
+            .iter()
+            .flat_map(|(&stat, &count)| std::iter::repeat_n(stat, count));
+
+        // The DNFs are among the worst ones, which are not in the sum already.
+        let best: Duration = sorted.clone().take(trim).filter_map(Stat::time).sum();
+        let worst: Duration = sorted.rev().take(trim).filter_map(Stat::time).sum();
+
+        let counting = self.window.size - 2 * trim;
+        Some(Stat::Time((self.sum - best - worst) / counting as u32))
+    }
+
+    /// The value with the last solves, or `None` if there are not enough
+    /// solves yet.
+    pub fn current(&self) -> Option<Stat> {
+        self.history.last().copied().flatten()
+    }
+
+    /// The best value so far.
+    pub fn best(&self) -> Option<Stat> {
+        self.best
+    }
+
+    /// The value after each solve.
+    pub fn history(&self) -> &[Option<Stat>] {
+        &self.history
+    }
+}
+
+/// The statistics of one phase of split solves, like the memo in BLD.
+///
+/// Penalties don't apply to phases (a DNF still had a memo), and only the
+/// solves with the phase count.
+#[derive(Debug, Clone)]
+pub struct Phase {
+    name: String,
+    windows: Vec<Rolling>,
+    count: usize,
+    sum: Duration,
+}
+
+impl Phase {
+    fn new(name: String, windows: impl IntoIterator<Item = Window>) -> Self {
+        Self {
+            name,
+            windows: windows.into_iter().map(Rolling::new).collect(),
+            count: 0,
+            sum: Duration::ZERO,
+        }
+    }
+
+    fn push(&mut self, time: Duration) {
+        for window in &mut self.windows {
+            window.push(Stat::Time(time));
+        }
+
+        self.count += 1;
+        self.sum += time;
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The number of solves with this phase.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn mean(&self) -> Option<Duration> {
+        (self.count > 0).then(|| self.sum / self.count as u32)
+    }
+
+    pub fn windows(&self) -> &[Rolling] {
+        &self.windows
+    }
+
+    pub fn window(&self, window: Window) -> Option<&Rolling> {
+        self.windows
+            .iter()
+            .find(|rolling| rolling.window() == window)
+    }
+}
+
+/// The statistics of a session: the single solves, some windows and the
+/// same windows for each phase of split solves.
+#[derive(Debug, Clone)]
+pub struct Statistics {
+    singles: Rolling,
+    windows: Vec<Rolling>,
+    phases: Vec<Phase>,
+    count: usize,
+    solved: usize,
+    sum: Duration,
+}
+
+impl Statistics {
+    pub fn new(windows: impl IntoIterator<Item = Window>) -> Self {
+        Self {
+            singles: Rolling::new(Window::mean(1)),
+            windows: windows.into_iter().map(Rolling::new).collect(),
+            phases: Vec::new(),
+            count: 0,
+            solved: 0,
+            sum: Duration::ZERO,
+        }
+    }
+
+    /// The statistics of some solves, in order.
+    pub fn from_solves<'a>(
+        windows: impl IntoIterator<Item = Window>,
+        solves: impl IntoIterator<Item = &'a Solve>,
+    ) -> Self {
+        let mut statistics = Self::new(windows);
+        for solve in solves {
+            statistics.push(solve);
+        }
+
+        statistics
+    }
+
+    pub fn push(&mut self, solve: &Solve) {
+        let stat = Stat::from(solve);
+        self.singles.push(stat);
+        for window in &mut self.windows {
+            window.push(stat);
+        }
+
+        for split in &solve.splits {
+            let index = match self
+                .phases
+                .iter()
+                .position(|phase| phase.name == split.name)
+            {
+                Some(index) => index,
+                None => {
+                    let windows = self.windows.iter().map(Rolling::window);
+                    self.phases.push(Phase::new(split.name.clone(), windows));
+                    self.phases.len() - 1
+                }
+            };
+
+            self.phases[index].push(split.time);
+        }
+
+        self.count += 1;
+        if let Stat::Time(time) = stat {
+            self.solved += 1;
+            self.sum += time;
+        }
+    }
+
+    /// The number of solves, including DNFs.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// The number of solves that are not DNFs.
+    pub fn solved(&self) -> usize {
+        self.solved
+    }
+
+    pub fn best_single(&self) -> Option<Stat> {
+        self.singles.best()
+    }
+
+    /// The mean of every solve that is not a DNF.
+    pub fn session_mean(&self) -> Option<Duration> {
+        (self.solved > 0).then(|| self.sum / self.solved as u32)
+    }
+
+    pub fn windows(&self) -> &[Rolling] {
+        &self.windows
+    }
+
+    pub fn window(&self, window: Window) -> Option<&Rolling> {
+        self.windows
+            .iter()
+            .find(|rolling| rolling.window() == window)
+    }
+
+    /// Each phase, in the order they first appeared.
+    pub fn phases(&self) -> &[Phase] {
+        &self.phases
+    }
+
+    pub fn phase(&self, name: &str) -> Option<&Phase> {
+        self.phases.iter().find(|phase| phase.name == name)
+    }
+}
+
+impl Default for Statistics {
+    /// With mo3, ao5, ao12 and ao100.
+    fn default() -> Self {
+        Self::new([Window::MO3, Window::AO5, Window::AO12, Window::AO100])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solve::Split;
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    fn ms(millis: u64) -> Stat {
+        Stat::Time(Duration::from_millis(millis))
+    }
+
+    #[test]
+    fn windows_without_solves_left_are_rejected() {
+        assert!(!Window::mean(0).is_valid());
+        assert!(!Window::average(1).is_valid());
+        assert!(!Window::average(2).is_valid());
+        assert!(Window::mean(1).is_valid());
+        assert!(Window::average(3).is_valid());
+
+        assert_eq!(Window::average(2).compute(&[ms(1000), ms(2000)]), None);
+        assert_eq!(Window::mean(0).compute(&[ms(1000)]), None);
+        assert_eq!(Rolling::new(Window::average(1)).push(ms(1000)), None);
+    }
+
+    #[test]
+    fn one_dnf_is_trimmed_but_two_are_not() {
+        let one = [ms(10000), Stat::DNF, ms(12000), ms(11000), ms(9000)];
+        // Without the 9 and the DNF.
+        assert_eq!(Window::AO5.compute(&one), Some(ms(11000)));
+
+        let two = [ms(10000), Stat::DNF, ms(12000), Stat::DNF, ms(9000)];
+        assert_eq!(Window::AO5.compute(&two), Some(Stat::DNF));
+    }
+
+    #[test]
+    fn penalties_are_applied() {
+        let solves = [
+            (10000, Penalty::Plus2),
+            (9000, Penalty::None),
+            (12000, Penalty::None),
+        ]
+        .map(|(millis, penalty)| Solve {
+            penalty,
+            ..Solve::new(Duration::from_millis(millis), String::new())
+        });
+
+        assert_eq!(Stat::from(&solves[0]), ms(12000));
+        let statistics = Statistics::from_solves([Window::MO3], &solves);
+        assert_eq!(
+            statistics.window(Window::MO3).unwrap().current(),
+            Some(ms(11000))
+        );
+    }
+
+    #[test]
+    fn means_are_not_trimmed() {
+        assert_eq!(
+            Window::MO3.compute(&[ms(1000), ms(2000), ms(6000)]),
+            Some(ms(3000))
+        );
+        assert_eq!(
+            Window::MO3.compute(&[ms(1000), Stat::DNF, ms(6000)]),
+            Some(Stat::DNF)
+        );
+    }
+
+    #[test]
+    fn ao100_trims_five_on_each_side() {
+        assert_eq!(Window::AO100.trim(), 5);
+
+        // 1 to 100 seconds, with the best and worst 5 replaced by extremes.
+        let mut stats: Vec<_> = (1..=100).map(|secs| ms(secs * 1000)).collect();
+        stats[..5].fill(ms(0));
+        stats[95..].fill(Stat::DNF);
+        // The mean of 6 to 95.
+        assert_eq!(Window::AO100.compute(&stats), Some(ms(50500)));
+
+        stats[94] = Stat::DNF;
+        assert_eq!(Window::AO100.compute(&stats), Some(Stat::DNF));
+    }
+
+    #[test]
+    fn rolling_matches_compute() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let stats: Vec<_> = (0..300)
+            .map(|_| match rng.random_range(0..20) {
+                0 => Stat::DNF,
+                // Some repeated times too.
+                _ => ms(rng.random_range(5000..5100)),
+            })
+            .collect();
+
+        for window in [Window::MO3, Window::AO5, Window::AO12, Window::AO100] {
+            let mut rolling = Rolling::new(window);
+            for i in 1..=stats.len() {
+                assert_eq!(
+                    rolling.push(stats[i - 1]),
+                    window.compute(&stats[..i]),
+                    "{window} after {i} solves"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn phase_averages() {
+        let phases = ["Memo".to_string(), "Execution".to_string()];
+        let solves = [(10, 30), (20, 20), (12, 25), (14, 26), (16, 24)].map(|(memo, total)| {
+            let mut solve = Solve::new(Duration::from_secs(total), String::new());
+            solve.splits = Split::from_marks(&phases, &[Duration::from_secs(memo)], solve.time);
+            solve
+        });
+
+        let statistics = Statistics::from_solves([Window::AO5], &solves);
+        let memo = statistics.phase("Memo").unwrap();
+        let execution = statistics.phase("Execution").unwrap();
+
+        assert_eq!(
+            statistics
+                .phases()
+                .iter()
+                .map(Phase::name)
+                .collect::<Vec<_>>(),
+            ["Memo", "Execution"]
+        );
+        assert_eq!(memo.count(), 5);
+        assert_eq!(memo.mean(), Some(Duration::from_secs(72) / 5));
+        // Without the 10 and the 20.
+        assert_eq!(
+            memo.window(Window::AO5).unwrap().current(),
+            Some(Stat::Time(Duration::from_secs(14)))
+        );
+        // 20, 0, 13, 12, 8 without the 0 and the 20.
+        assert_eq!(
+            execution.window(Window::AO5).unwrap().current(),
+            Some(Stat::Time(Duration::from_secs(11)))
+        );
+    }
+}
@@ -0,0 +1,267 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::{Constraint, Flex, Layout},
+    prelude::{Buffer, Rect},
+    style::{Style, Stylize as _, palette::tailwind::SLATE},
+    text::Line,
+    widgets::{Block, Clear, HighlightSpacing, List, ListState, Paragraph, StatefulWidget, Widget},
+};
+
+/// What is managed in a [`Dialog`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    /// The sessions of the selected event.
+    Sessions,
+    /// The custom events.
+    Events,
+}
+
+#[derive(Debug, Clone)]
+pub struct Item {
+    pub id: usize,
+    pub name: String,
+    /// Whether it can be renamed, deleted and moved (the main session can't).
+    pub editable: bool,
+}
+
+/// What the user asked for in a [`Dialog`], to be done by the app.
+#[derive(Debug, Clone)]
+pub enum Action {
+    Close,
+    Select(usize),
+    Create(String),
+    Rename {
+        id: usize,
+        name: String,
+    },
+    /// For sessions, `keep_solves` moves its solves to the main session.
+    Delete {
+        id: usize,
+        keep_solves: bool,
+    },
+    /// The ids of the editable items, in their new order.
+    Reorder(Vec<usize>),
+}
+
+#[derive(Debug, Clone)]
+enum Mode {
+    Browse,
+    Input { rename: Option<usize>, text: String },
+    ConfirmDelete { id: usize },
+}
+
+/// A popup to create, rename, reorder, delete and select sessions or custom
+/// events.
+#[derive(Debug, Clone)]
+pub struct Dialog {
+    target: Target,
+    items: Vec<Item>,
+    list_state: ListState,
+    mode: Mode,
+    error: Option<String>,
+}
+
+impl Dialog {
+    pub fn new(target: Target, items: Vec<Item>) -> Self {
+        Self {
+            target,
+            list_state: ListState::default().with_selected((!items.is_empty()).then_some(0)),
+            items,
+            mode: Mode::Browse,
+            error: None,
+        }
+    }
+
+    pub const fn target(&self) -> Target {
+        self.target
+    }
+
+    /// Replaces the items, after they were changed.
+    pub fn set_items(&mut self, items: Vec<Item>) {
+        self.items = items;
+        let selected = self.list_state.selected().unwrap_or(0);
+        self.list_state
+            .select((!self.items.is_empty()).then(|| selected.min(self.items.len() - 1)));
+    }
+
+    /// Shows an error, like when a name is already taken.
+    pub fn set_error(&mut self, error: String) {
+        self.error = Some(error);
+    }
+
+    fn selected(&self) -> Option<&Item> {
+        self.items.get(self.list_state.selected()?)
+    }
+
+    pub fn handle_key(&mut self, key_event: KeyEvent) -> Option<Action> {
+        self.error = None;
+        match &mut self.mode {
+            Mode::Browse => self.handle_browse_key(key_event),
+            Mode::Input { rename, text } => match key_event.code {
+                KeyCode::Char(c) => {
+                    text.push(c);
+                    None
+                }
+                KeyCode::Backspace => {
+                    text.pop();
+                    None
+                }
+                KeyCode::Enter => {
+                    let name = std::mem::take(text);
+                    let action = match *rename {
+                        Some(id) => Action::Rename { id, name },
+                        None => Action::Create(name),
+                    };
+                    self.mode = Mode::Browse;
+                    Some(action)
+                }
+                KeyCode::Esc => {
+                    self.mode = Mode::Browse;
+                    None
+                }
+                _ => None,
+            },
+            &mut Mode::ConfirmDelete { id } => {
+                let keep_solves = match (self.target, key_event.code) {
+                    (Target::Sessions, KeyCode::Char('m')) => Some(true),
+                    (Target::Sessions, KeyCode::Char('d'))
+                    | (Target::Events, KeyCode::Char('y')) => Some(false),
+                    _ => None,
+                };
+
+                self.mode = Mode::Browse;
+                keep_solves.map(|keep_solves| Action::Delete { id, keep_solves })
+            }
+        }
+    }
+
+    fn handle_browse_key(&mut self, key_event: KeyEvent) -> Option<Action> {
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Char('q') => Some(Action::Close),
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.list_state.select_next();
+                None
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                self.list_state.select_previous();
+                None
+            }
+            KeyCode::Enter => self.selected().map(|item| Action::Select(item.id)),
+            KeyCode::Char('n') => {
+                self.mode = Mode::Input {
+                    rename: None,
+                    text: String::new(),
+                };
+                None
+            }
+            KeyCode::Char('r') => {
+                let item = self.selected().filter(|item| item.editable)?;
+                self.mode = Mode::Input {
+                    rename: Some(item.id),
+                    text: item.name.clone(),
+                };
+                None
+            }
+            KeyCode::Char('d') => {
+                let id = self.selected().filter(|item| item.editable)?.id;
+                self.mode = Mode::ConfirmDelete { id };
+                None
+            }
+            KeyCode::Char('J') => self.move_selected(1),
+            KeyCode::Char('K') => self.move_selected(-1),
+            _ => None,
+        }
+    }
+
+    /// Swaps the selected item with the one `offset` positions away, if both
+    /// can be moved.
+    fn move_selected(&mut self, offset: isize) -> Option<Action> {
+        let from = self.list_state.selected()?;
+        let to = from.checked_add_signed(offset)?;
+        if !self.items.get(from)?.editable || !self.items.get(to)?.editable {
+            return None;
+        }
+
+        self.items.swap(from, to);
+        self.list_state.select(Some(to));
+        Some(Action::Reorder(
+            self.items
+                .iter()
+                .filter(|item| item.editable)
+                .map(|item| item.id)
+                .collect(),
+        ))
+    }
+
+    pub fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        let [area] = Layout::horizontal([Constraint::Length(50)])
+            .flex(Flex::Center)
+            .areas(area);
+        let [area] = Layout::vertical([Constraint::Length(16)])
+            .flex(Flex::Center)
+            .areas(area);
+
+        let title = match self.target {
+            Target::Sessions => " Sessions ",
+            Target::Events => " Custom events ",
+        };
+
+        let instructions = match (&self.mode, self.target) {
+            (Mode::Browse, _) => Line::from(vec![
+                " new ".white(),
+                "<n>".blue().bold(),
+                " rename ".white(),
+                "<r>".blue().bold(),
+                " delete ".white(),
+                "<d>".blue().bold(),
+                " move ".white(),
+                "<J>/<K> ".blue().bold(),
+            ]),
+            (Mode::Input { .. }, _) => Line::from(vec![
+                " save ".white(),
+                "<enter>".blue().bold(),
+                " cancel ".white(),
+                "<esc> ".blue().bold(),
+            ]),
+            (Mode::ConfirmDelete { .. }, Target::Sessions) => Line::from(vec![
+                " move solves to main ".white(),
+                "<m>".blue().bold(),
+                " delete solves ".white(),
+                "<d>".blue().bold(),
+                " cancel ".white(),
+                "<esc> ".blue().bold(),
+            ]),
+            (Mode::ConfirmDelete { .. }, Target::Events) => Line::from(vec![
+                " delete with its solves ".white(),
+                "<y>".blue().bold(),
+                " cancel ".white(),
+                "<esc> ".blue().bold(),
+            ]),
+        };
+
+        Clear.render(area, buf);
+        let block = Block::bordered()
+            .title(Line::from(title.bold()).centered())
+            .title_bottom(instructions.centered());
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let [list_area, status_area] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(inner);
+
+        let list = List::new(self.items.iter().map(|item| item.name.as_str()))
+            .highlight_style(Style::new().bg(SLATE.c800).green())
+            .highlight_symbol("> ")
+            .highlight_spacing(HighlightSpacing::Always);
+        StatefulWidget::render(list, list_area, buf, &mut self.list_state);
+
+        let status = if let Some(error) = &self.error {
+            Line::from(error.as_str().red())
+        } else if let Mode::Input { text, .. } = &self.mode {
+            Line::from(vec!["Name: ".bold(), text.as_str().into(), "_".into()])
+        } else {
+            Line::default()
+        };
+        Paragraph::new(status).render(status_area, buf);
+    }
+}
@@ -1,7 +1,8 @@
 use color_eyre::eyre;
+use norcina::DynPuzzle;
 use pesto_core::{
     Db,
-    event::{MaybeCustomEvent, Session},
+    event::{CustomEvent, MaybeCustomEvent, Session},
 };
 use ratatui::{
     prelude::{Buffer, Rect},
@@ -16,6 +17,31 @@ pub struct EventItem {
     session_list_state: ListState,
     sessions: Vec<Session>,
     scramble: Option<String>,
+    scrambled_state: Option<DynPuzzle>,
+}
+
+impl EventItem {
+    fn new(event: MaybeCustomEvent, sessions: Vec<Session>) -> Self {
+        let scramble = event.gen_scramble(&mut rand::rng());
+        let scrambled_state = scramble
+            .as_deref()
+            .and_then(|scramble| event.scrambled_state(scramble));
+
+        Self {
+            event,
+            sessions,
+            session_list_state: ListState::default().with_selected(Some(0)),
+            scramble,
+            scrambled_state,
+        }
+    }
+
+    fn selected_session(&self) -> &Session {
+        &self.sessions[self
+            .session_list_state
+            .selected()
+            .expect("Always something is selected")]
+    }
 }
 
 impl From<&EventItem> for Text<'_> {
@@ -23,7 +49,7 @@ impl From<&EventItem> for Text<'_> {
         Text::from(format!(
             "{}/{}",
             value.event.short_name(),
-            value.sessions[0].name()
+            value.selected_session().name()
         ))
     }
 }
@@ -40,20 +66,80 @@ impl EventSessions {
             items: db
                 .get_events_and_sessions()?
                 .into_iter()
-                .map(|(event, sessions)| {
-                    let scramble = event.gen_scramble(&mut rand::rng());
-                    EventItem {
-                        event,
-                        sessions,
-                        session_list_state: ListState::default().with_selected(Some(0)),
-                        scramble,
-                    }
-                })
+                .map(|(event, sessions)| EventItem::new(event, sessions))
                 .collect(),
             state: ListState::default().with_selected(Some(1)),
         })
     }
 
+    /// Reads the events and sessions again, after they were changed. Keeps
+    /// the scrambles and what is selected, if it still exists.
+    pub fn reload(&mut self, db: &mut Db) -> eyre::Result<()> {
+        let selected_event = self.selected_event().id();
+        let selected_sessions = self
+            .items
+            .iter()
+            .map(|item| (item.event.id(), item.selected_session().id()))
+            .collect::<Vec<_>>();
+
+        let mut old_items = std::mem::take(&mut self.items);
+        for (event, sessions) in db.get_events_and_sessions()? {
+            let mut item = match old_items
+                .iter()
+                .position(|item| item.event.id() == event.id())
+            {
+                Some(i) => {
+                    let mut item = old_items.swap_remove(i);
+                    item.event = event;
+                    item.sessions = sessions;
+                    item
+                }
+                None => EventItem::new(event, sessions),
+            };
+
+            let selected_session = selected_sessions
+                .iter()
+                .find(|&&(event_id, _)| event_id == item.event.id())
+                .and_then(|&(_, session_id)| {
+                    item.sessions
+                        .iter()
+                        .position(|session| session.id() == session_id)
+                });
+            item.session_list_state
+                .select(Some(selected_session.unwrap_or(0)));
+            self.items.push(item);
+        }
+
+        self.select_event(selected_event);
+        Ok(())
+    }
+
+    /// Selects the event with id `id`, or the first one if there is none.
+    pub fn select_event(&mut self, id: usize) {
+        let index = self.items.iter().position(|item| item.event.id() == id);
+        self.state.select(Some(index.unwrap_or(0)));
+    }
+
+    /// Selects the session of the selected event with id `id`, if there is one.
+    pub fn select_session(&mut self, id: usize) {
+        let item = &mut self.items[self.state.selected().expect("Always something is selected")];
+        if let Some(index) = item.sessions.iter().position(|session| session.id() == id) {
+            item.session_list_state.select(Some(index));
+        }
+    }
+
+    /// The sessions of the selected event.
+    pub fn sessions(&self) -> &[Session] {
+        &self.items[self.state.selected().expect("Always something is selected")].sessions
+    }
+
+    pub fn custom_events(&self) -> impl Iterator<Item = &CustomEvent> {
+        self.items.iter().filter_map(|item| match &item.event {
+            MaybeCustomEvent::Official(_) => None,
+            MaybeCustomEvent::Unofficial(event) => Some(event),
+        })
+    }
+
     pub fn select_next(&mut self) {
         self.state.select_next();
     }
@@ -74,11 +160,7 @@ impl EventSessions {
         &self.items[self.state.selected().expect("Always something is selected")].event
     }
     pub fn selected_session(&self) -> &Session {
-        let item = &self.items[self.state.selected().expect("Always something is selected")];
-        &item.sessions[item
-            .session_list_state
-            .selected()
-            .expect("Always something is selected")]
+        self.items[self.state.selected().expect("Always something is selected")].selected_session()
     }
 
     pub fn current_scramble(&self) -> &str {
@@ -87,6 +169,11 @@ impl EventSessions {
             .as_ref()
             .expect("Active session should always have a scramble present")
     }
+
+    /// The state of the puzzle after the current scramble, if it can be shown.
+    pub fn current_scrambled_state(&self) -> Option<DynPuzzle> {
+        self.items[self.state.selected().expect("Always something is selected")].scrambled_state
+    }
 }
 
 impl Widget for &mut EventSessions {
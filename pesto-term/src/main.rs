@@ -1,7 +1,13 @@
+use ansi_to_tui::IntoText as _;
 use color_eyre::eyre::{self, Context};
 use crossterm::event::{self, Event, KeyCode, KeyEvent, poll};
 use device_query::{DeviceQuery as _, DeviceState, Keycode};
-use pesto_core::{Db, solve::Solve};
+use pesto_core::{
+    Db, OrphanedSolves,
+    event::Session,
+    pb::PbEvent,
+    solve::{Penalty, Solve},
+};
 use ratatui::{
     DefaultTerminal, Frame,
     buffer::Buffer,
@@ -21,6 +27,17 @@ use events_sessions::EventSessions;
 mod solve_list;
 use solve_list::SolveList;
 
+mod dialog;
+use dialog::{Dialog, Target};
+
+/// The phases that `p` cycles through. The first one is not splitting solves.
+const PHASE_PRESETS: [&[&str]; 4] = [
+    &[],
+    &["Memo", "Execution"],
+    &["Cross", "F2L", "LL"],
+    &["Cross", "F2L", "OLL", "PLL"],
+];
+
 fn main() -> eyre::Result<()> {
     let mut terminal = ratatui::init();
     let app_result = App::new()
@@ -36,6 +53,15 @@ pub struct App {
     timer: Timer,
     events_sessions: EventSessions,
     solve_list: SolveList,
+    /// The records broken by the last solve, shown until the next one starts.
+    pb_event: Option<PbEvent>,
+    /// A short message, like after toggling inspection, shown until the next
+    /// solve starts.
+    message: Option<String>,
+    /// The ids of the deleted solves, to undo the deletions.
+    deleted_solves: Vec<usize>,
+    /// The session or custom event dialog, if it's open.
+    dialog: Option<Dialog>,
     db: Db,
     exit: bool,
     last_draw: Instant,
@@ -61,6 +87,10 @@ impl App {
             timer: Timer::Idle,
             events_sessions,
             solve_list,
+            pb_event: None,
+            message: None,
+            deleted_solves: Vec::new(),
+            dialog: None,
             db,
             exit: false,
             last_draw: Instant::now(),
@@ -75,7 +105,7 @@ impl App {
         while !self.exit {
             let now = Instant::now();
 
-            if self.timer.is_running() {
+            if self.timer.is_ticking() {
                 // Only redraw if 10ms have passed since last draw
                 if now.duration_since(self.last_draw) >= self.timer_refresh_duration {
                     terminal.draw(|frame| self.draw(frame))?;
@@ -112,7 +142,11 @@ impl App {
                 }
             }
 
-            self.timer.release(self.min_press_duration);
+            let event = self.events_sessions.selected_event();
+            let inspection = self.db.inspection(event)?;
+            let phases = self.db.phases(event)?;
+            self.timer
+                .release(self.min_press_duration, inspection, &phases);
 
             // Discard crossterm events
             while event::poll(Duration::from_millis(0))? {
@@ -133,25 +167,103 @@ impl App {
     }
 
     fn handle_keypress_event(&mut self, key_event: KeyEvent) -> eyre::Result<()> {
+        if let Some(dialog) = &mut self.dialog {
+            if let Some(action) = dialog.handle_key(key_event) {
+                let target = dialog.target();
+                if let Err(err) = self.handle_dialog_action(target, action)
+                    && let Some(dialog) = &mut self.dialog
+                {
+                    dialog.set_error(err.to_string());
+                }
+            }
+
+            return Ok(());
+        }
+
+        // While the timer is ticking, the solve and its event and session
+        // can't be changed, and dialogs can't be opened.
+        if self.timer.is_ticking()
+            && !matches!(key_event.code, KeyCode::Char(' ' | 'q') | KeyCode::F(1))
+        {
+            return Ok(());
+        }
+
         match key_event.code {
             KeyCode::Char('q') => self.exit(),
             KeyCode::F(1) => self.debug = !self.debug,
             KeyCode::Char(' ') => {
-                if let Some(time) = self.timer.press(self.min_stop_duration) {
-                    self.db.insert_solve(
-                        Solve::new(time, self.events_sessions.current_scramble().to_string()),
+                if let Some(measurement) = self.timer.press(self.min_stop_duration) {
+                    let solve = Solve {
+                        penalty: measurement.penalty,
+                        splits: measurement.splits,
+                        ..Solve::new(
+                            measurement.time,
+                            self.events_sessions.current_scramble().to_string(),
+                        )
+                    };
+                    let pb_event = self.db.insert_solve(
+                        solve,
                         self.events_sessions.selected_event(),
                         self.events_sessions.selected_session(),
                     )?;
+                    self.pb_event = pb_event.is_pb().then_some(pb_event);
+                    self.refresh_solves()?;
+                } else if self.timer.is_pressed() {
+                    self.pb_event = None;
+                    self.message = None;
+                }
+            }
 
-                    self.solve_list.refresh(
-                        &mut self.db,
-                        self.events_sessions.selected_event(),
-                        self.events_sessions.selected_session(),
-                    )?;
+            // Editing the selected solve, or the last one
+            KeyCode::Char('J') => self.solve_list.select_next(),
+            KeyCode::Char('K') => self.solve_list.select_previous(),
+            KeyCode::Esc => self.solve_list.clear_selection(),
+            KeyCode::Char('2') => self.toggle_penalty(Penalty::Plus2)?,
+            KeyCode::Char('d') => self.toggle_penalty(Penalty::DNF)?,
+            KeyCode::Char('x') | KeyCode::Delete => {
+                if let Some(id) = self.solve_list.target().and_then(|solve| solve.id) {
+                    self.db.delete_solve(id)?;
+                    self.deleted_solves.push(id);
+                    self.refresh_solves()?;
+                }
+            }
+            KeyCode::Char('u') => {
+                if let Some(id) = self.deleted_solves.pop() {
+                    self.db.restore_solve(id)?;
+                    self.refresh_solves()?;
                 }
             }
 
+            KeyCode::Char('i') => {
+                let event = self.events_sessions.selected_event();
+                let inspection = self.db.inspection(event)?;
+                self.db.set_inspection(event, !inspection)?;
+                self.message = Some(format!(
+                    "Inspection {} for {}",
+                    if inspection { "off" } else { "on" },
+                    event.short_name()
+                ));
+            }
+
+            KeyCode::Char('p') => {
+                let event = self.events_sessions.selected_event();
+                let phases = self.db.phases(event)?;
+                let next = PHASE_PRESETS
+                    .iter()
+                    .position(|preset| preset.iter().eq(&phases))
+                    .map_or(0, |i| (i + 1) % PHASE_PRESETS.len());
+
+                self.db.set_phases(event, PHASE_PRESETS[next])?;
+                self.message = Some(match PHASE_PRESETS[next] {
+                    [] => format!("No phases for {}", event.short_name()),
+                    phases => format!("Phases for {}: {}", event.short_name(), phases.join(", ")),
+                });
+            }
+
+            // Managing sessions and custom events
+            KeyCode::Char('s') => self.open_dialog(Target::Sessions),
+            KeyCode::Char('E') => self.open_dialog(Target::Events),
+
             // Event navigation
             KeyCode::Char('j') | KeyCode::Down => self.events_sessions.select_next(),
             KeyCode::Char('k') | KeyCode::Up => self.events_sessions.select_previous(),
@@ -167,6 +279,126 @@ impl App {
         Ok(())
     }
 
+    fn dialog_items(&self, target: Target) -> Vec<dialog::Item> {
+        match target {
+            Target::Sessions => self
+                .events_sessions
+                .sessions()
+                .iter()
+                .map(|session| dialog::Item {
+                    id: session.id(),
+                    name: session.name().to_string(),
+                    editable: !matches!(session, Session::Main),
+                })
+                .collect(),
+            Target::Events => self
+                .events_sessions
+                .custom_events()
+                .map(|event| dialog::Item {
+                    id: event.id,
+                    name: event.name.clone(),
+                    editable: true,
+                })
+                .collect(),
+        }
+    }
+
+    fn open_dialog(&mut self, target: Target) {
+        self.dialog = Some(Dialog::new(target, self.dialog_items(target)));
+    }
+
+    fn handle_dialog_action(&mut self, target: Target, action: dialog::Action) -> eyre::Result<()> {
+        let event = self.events_sessions.selected_event().clone();
+        let session = |id| {
+            self.events_sessions
+                .sessions()
+                .iter()
+                .find(|session| session.id() == id)
+                .cloned()
+                .ok_or_else(|| eyre::eyre!("No session with id {id}"))
+        };
+
+        match (target, action) {
+            (_, dialog::Action::Close) => {
+                self.dialog = None;
+                return Ok(());
+            }
+            (Target::Sessions, dialog::Action::Select(id)) => {
+                self.events_sessions.select_session(id);
+                self.dialog = None;
+            }
+            (Target::Sessions, dialog::Action::Create(name)) => {
+                self.db.create_session(&event, &name)?;
+            }
+            (Target::Sessions, dialog::Action::Rename { id, name }) => {
+                self.db.rename_session(&event, &session(id)?, &name)?;
+            }
+            (Target::Sessions, dialog::Action::Delete { id, keep_solves }) => {
+                let solves = if keep_solves {
+                    OrphanedSolves::MoveTo(Session::Main)
+                } else {
+                    OrphanedSolves::Delete
+                };
+                self.db.delete_session(&event, &session(id)?, solves)?;
+            }
+            (Target::Sessions, dialog::Action::Reorder(ids)) => {
+                self.db.reorder_sessions(&event, &ids)?;
+            }
+            (Target::Events, dialog::Action::Select(id)) => {
+                self.events_sessions.select_event(id);
+                self.dialog = None;
+            }
+            (Target::Events, dialog::Action::Create(name)) => {
+                self.db.create_custom_event(&name, None)?;
+            }
+            (Target::Events, dialog::Action::Rename { id, name }) => {
+                self.db.rename_custom_event(id, &name)?;
+            }
+            (Target::Events, dialog::Action::Delete { id, .. }) => {
+                self.db.delete_custom_event(id)?;
+            }
+            (Target::Events, dialog::Action::Reorder(ids)) => {
+                self.db.reorder_custom_events(&ids)?;
+            }
+        }
+
+        self.events_sessions.reload(&mut self.db)?;
+        let items = self.dialog_items(target);
+        if let Some(dialog) = &mut self.dialog {
+            dialog.set_items(items);
+        }
+
+        self.refresh_solves()
+    }
+
+    /// Sets `penalty` on the target solve, or removes it if it already has it.
+    fn toggle_penalty(&mut self, penalty: Penalty) -> eyre::Result<()> {
+        let Some(solve) = self.solve_list.target() else {
+            return Ok(());
+        };
+
+        let Some(id) = solve.id else {
+            return Ok(());
+        };
+
+        let penalty = if solve.penalty == penalty {
+            Penalty::None
+        } else {
+            penalty
+        };
+
+        self.db.update_penalty(id, penalty)?;
+        self.refresh_solves()
+    }
+
+    fn refresh_solves(&mut self) -> eyre::Result<()> {
+        self.solve_list.refresh(
+            &mut self.db,
+            self.events_sessions.selected_event(),
+            self.events_sessions.selected_session(),
+        )
+    }
+
     fn exit(&mut self) {
         self.exit = true;
     }
@@ -187,10 +419,24 @@ impl Widget for &mut App {
 
         let title = Line::from(" pesto-term! ".bold().green());
         let instructions = Line::from(vec![
-            " start/stop timer ".white(),
+            " start/split/stop timer ".white(),
             "<space>".blue().bold(),
+            " inspection ".white(),
+            "<i>".blue().bold(),
+            " phases ".white(),
+            "<p>".blue().bold(),
             " select event ".white(),
             "<e>".blue().bold(),
+            " +2/DNF ".white(),
+            "<2>/<d>".blue().bold(),
+            " delete/undo ".white(),
+            "<x>/<u>".blue().bold(),
+            " select solve ".white(),
+            "<J>/<K>".blue().bold(),
+            " sessions ".white(),
+            "<s>".blue().bold(),
+            " custom events ".white(),
+            "<E>".blue().bold(),
             " quit ".white(),
             "<q> ".blue().bold(),
         ]);
@@ -215,12 +461,13 @@ impl Widget for &mut App {
         let layout_center = Layout::vertical([
             Constraint::Length(5),
             Constraint::Length(7),
+            Constraint::Length(1),
             Constraint::Fill(1),
         ])
         .spacing(1)
         .margin(1);
 
-        let [scramble_rect, timer_rect, _extra_rect] = layout_center.areas(center_rect);
+        let [scramble_rect, timer_rect, pb_rect, state_rect] = layout_center.areas(center_rect);
 
         Paragraph::new(vec![
             Line::from(""),
@@ -238,5 +485,34 @@ impl Widget for &mut App {
             self.min_press_duration,
             self.min_stop_duration,
         );
+
+        if let Some(pb_event) = &self.pb_event {
+            let badge = if pb_event.is_event_pb() {
+                " New PB! "
+            } else {
+                " New session PB! "
+            };
+            Line::from(vec![
+                badge.bold().black().on_yellow(),
+                " ".into(),
+                pb_event.to_string().yellow(),
+            ])
+            .centered()
+            .render(pb_rect, buf);
+        } else if let Some(message) = &self.message {
+            Line::from(message.as_str().italic())
+                .centered()
+                .render(pb_rect, buf);
+        }
+
+        if let Some(state) = self.events_sessions.current_scrambled_state()
+            && let Ok(text) = state.to_string().into_text()
+        {
+            Paragraph::new(text).centered().render(state_rect, buf);
+        }
+
+        if let Some(dialog) = &mut self.dialog {
+            dialog.render(area, buf);
+        }
     }
 }
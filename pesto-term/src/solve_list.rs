@@ -47,6 +47,26 @@ impl SolveList {
 
         Ok(())
     }
+
+    pub fn select_next(&mut self) {
+        self.list_state.select_next();
+    }
+
+    pub fn select_previous(&mut self) {
+        self.list_state.select_previous();
+    }
+
+    pub fn clear_selection(&mut self) {
+        self.list_state.select(None);
+    }
+
+    /// The selected solve, or the last one if there's nothing selected.
+    pub fn target(&self) -> Option<&pesto_core::Solve> {
+        let index = self.list_state.selected().unwrap_or(0);
+        self.solves
+            .get(index.min(self.solves.len().saturating_sub(1)))
+            .map(|solve| &solve.0)
+    }
 }
 
 impl Widget for &mut SolveList {
@@ -67,6 +87,9 @@ impl Widget for &mut SolveList {
 
 impl From<&Solve> for Text<'_> {
     fn from(value: &Solve) -> Self {
-        Text::from(value.0.to_string())
+        match &value.0.comment {
+            Some(comment) => Text::from(format!("{} \"{comment}\"", value.0)),
+            None => Text::from(value.0.to_string()),
+        }
     }
 }
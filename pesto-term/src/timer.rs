@@ -1,54 +1,108 @@
 use std::time::{Duration, Instant};
 
+use pesto_core::{
+    inspection,
+    solve::{Penalty, Split},
+};
 use ratatui::{
     crossterm::style::Color,
     layout::{Constraint, Flex, Layout},
     prelude::{Buffer, Rect},
     style::Stylize as _,
-    text::Line,
+    text::{Line, Span},
     widgets::Widget,
 };
 use tui_big_text::{BigText, PixelSize};
 
-#[derive(Debug, Default, Clone, Copy)]
+/// What the timer measured, once it's stopped.
+#[derive(Debug, Clone)]
+pub struct Measurement {
+    pub time: Duration,
+    /// The penalty for taking too long to inspect.
+    pub penalty: Penalty,
+    /// Empty unless the event has phases.
+    pub splits: Vec<Split>,
+}
+
+#[derive(Debug, Default, Clone)]
 pub enum Timer {
     /// Before having pressed anything, "white"
     #[default]
     Idle,
 
-    /// Started pressing on the timer, not released yet
-    Pressed { press_start: Instant },
+    /// Started pressing on the timer, not released yet. If it was pressed
+    /// while inspecting, `inspection_start` is when inspection started.
+    Pressed {
+        press_start: Instant,
+        inspection_start: Option<Instant>,
+    },
+
+    /// Inspecting the puzzle, counting down from 15 seconds.
+    Inspecting { start: Instant },
 
-    /// Timer is running.
-    Running { start: Instant },
+    /// Timer is running. `penalty` is the one for taking too long to inspect.
+    ///
+    /// With more than one phase, each press ends a phase, and `marks` are the
+    /// times (since `start`) when they ended. The last press stops the timer.
+    Running {
+        start: Instant,
+        penalty: Penalty,
+        phases: Vec<String>,
+        marks: Vec<Duration>,
+    },
 
     Stopped {
-        time: Duration,
+        measurement: Measurement,
         stopped_instant: Instant,
     },
 }
 
 impl Timer {
-    pub fn press(&mut self, min_stop_duration: Duration) -> Option<Duration> {
+    /// Returns what was measured if the timer was stopped.
+    pub fn press(&mut self, min_stop_duration: Duration) -> Option<Measurement> {
         match self {
             // If idle, start
             Self::Idle => {
                 *self = Self::Pressed {
                     press_start: Instant::now(),
+                    inspection_start: None,
                 }
             }
 
             // If pressed and pressed again, just keep pressing
             Self::Pressed { .. } => (),
 
-            // If running, stop and return time.
-            Self::Running { start } => {
+            // If inspecting, get ready to start
+            &mut Self::Inspecting { start } => {
+                *self = Self::Pressed {
+                    press_start: Instant::now(),
+                    inspection_start: Some(start),
+                }
+            }
+
+            // If running, end the phase, or stop and return time if it's the last one.
+            Self::Running {
+                start,
+                penalty,
+                phases,
+                marks,
+            } => {
                 let time = Instant::now().duration_since(*start);
-                *self = Self::Stopped {
+                if marks.len() + 1 < phases.len() {
+                    marks.push(time);
+                    return None;
+                }
+
+                let measurement = Measurement {
                     time,
+                    penalty: *penalty,
+                    splits: Split::from_marks(phases, marks, time),
+                };
+                *self = Self::Stopped {
+                    measurement: measurement.clone(),
                     stopped_instant: Instant::now(),
                 };
-                return Some(time);
+                return Some(measurement);
             }
 
             // If stopped, make sure enough time has passed, then press.
@@ -58,6 +112,7 @@ impl Timer {
                 if Instant::now().duration_since(*stopped_instant) >= min_stop_duration {
                     *self = Self::Pressed {
                         press_start: Instant::now(),
+                        inspection_start: None,
                     }
                 }
             }
@@ -66,20 +121,43 @@ impl Timer {
         None
     }
 
-    pub fn release(&mut self, min_press_duration: Duration) {
-        match self {
-            Self::Pressed { press_start } => {
-                let press_duration = Instant::now().duration_since(*press_start);
+    /// With `inspection`, releasing the first press starts inspecting, and
+    /// the timer starts when releasing the next one. With more than one of
+    /// `phases`, the solve is split into them.
+    pub fn release(&mut self, min_press_duration: Duration, inspection: bool, phases: &[String]) {
+        match *self {
+            Self::Pressed {
+                inspection_start: None,
+                ..
+            } if inspection => {
+                *self = Self::Inspecting {
+                    start: Instant::now(),
+                }
+            }
+            Self::Pressed {
+                press_start,
+                inspection_start,
+            } => {
+                let press_duration = Instant::now().duration_since(press_start);
                 if press_duration < min_press_duration {
-                    *self = Self::Idle
+                    *self = match inspection_start {
+                        Some(start) => Self::Inspecting { start },
+                        None => Self::Idle,
+                    }
                 } else {
+                    // NOTE: We recalculate the start time to be more accurate.
+                    let start = Instant::now();
                     *self = Self::Running {
-                        // NOTE: We recalculate the start time to be more accurate.
-                        start: Instant::now(),
+                        start,
+                        penalty: inspection_start.map_or(Penalty::None, |inspection_start| {
+                            inspection::penalty(start.duration_since(inspection_start))
+                        }),
+                        phases: phases.to_vec(),
+                        marks: Vec::new(),
                     }
                 }
             }
-            Self::Idle | Self::Running { .. } | Self::Stopped { .. } => (),
+            Self::Idle | Self::Inspecting { .. } | Self::Running { .. } | Self::Stopped { .. } => {}
         }
     }
 
@@ -87,8 +165,92 @@ impl Timer {
         matches!(self, Self::Pressed { .. })
     }
 
-    pub const fn is_running(&self) -> bool {
-        matches!(self, Self::Running { .. })
+    /// Whether the display changes by itself, so it has to be redrawn.
+    pub const fn is_ticking(&self) -> bool {
+        matches!(self, Self::Running { .. } | Self::Inspecting { .. })
+    }
+
+    /// Each finished phase with its split, and the current one (if running)
+    /// without it.
+    fn splits(&self) -> Vec<(&str, Option<Duration>)> {
+        match self {
+            Self::Running { phases, marks, .. } if phases.len() > 1 => {
+                let starts = [Duration::ZERO].into_iter().chain(marks.iter().copied());
+                let finished = marks
+                    .iter()
+                    .zip(starts)
+                    .map(|(&end, start)| Some(end - start));
+
+                phases
+                    .iter()
+                    .map(String::as_str)
+                    .zip(finished.chain([None]))
+                    .collect()
+            }
+            Self::Stopped { measurement, .. } => measurement
+                .splits
+                .iter()
+                .map(|split| (split.name.as_str(), Some(split.time)))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Renders the inspection countdown, which is yellow after the first
+    /// warning and red after the second.
+    fn render_inspection(
+        &self,
+        area: Rect,
+        buf: &mut Buffer,
+        inspection_start: Instant,
+        color: Option<Color>,
+    ) {
+        let elapsed = Instant::now().duration_since(inspection_start);
+        let countdown = match inspection::penalty(elapsed) {
+            Penalty::None => (inspection::DURATION - elapsed)
+                .as_secs_f64()
+                .ceil()
+                .to_string(),
+            Penalty::Plus2 => "+2".to_string(),
+            Penalty::DNF => "DNF".to_string(),
+        };
+
+        let warning = inspection::WARNINGS
+            .into_iter()
+            .rev()
+            .find(|&warning| elapsed >= warning);
+        let color = color.unwrap_or(match warning {
+            None => Color::White,
+            Some(warning) if warning == inspection::WARNINGS[0] => Color::Yellow,
+            Some(_) => Color::Red,
+        });
+
+        let [_, countdown_area, warning_area] = Layout::horizontal([
+            Constraint::Fill(1),
+            Constraint::Length(16),
+            Constraint::Fill(1),
+        ])
+        .areas(area);
+
+        let [_, warning_area] = Layout::vertical([Constraint::Length(1), Constraint::Fill(1)])
+            .flex(Flex::Start)
+            .areas(warning_area);
+
+        BigText::builder()
+            .centered()
+            .pixel_size(PixelSize::HalfHeight)
+            .lines([Line::from(countdown.fg(color))])
+            .build()
+            .render(countdown_area, buf);
+
+        if let Some(warning) = warning {
+            BigText::builder()
+                .left_aligned()
+                .pixel_size(PixelSize::Sextant)
+                .lines([Line::from(format!("{}s", warning.as_secs()).fg(color))])
+                .build()
+                .render(warning_area, buf);
+        }
     }
 
     pub fn render(
@@ -98,31 +260,58 @@ impl Timer {
         min_press_duration: Duration,
         min_stop_duration: Duration,
     ) {
-        let (duration, color) = match *self {
-            Timer::Idle => (Duration::ZERO, Color::White),
-            Timer::Pressed { press_start } => {
-                if Instant::now().duration_since(press_start) < min_press_duration {
-                    (Duration::ZERO, Color::Yellow)
+        let [area, splits_area] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(area);
+
+        let (duration, penalty, color) = match *self {
+            Timer::Idle => (Duration::ZERO, Penalty::None, Color::White),
+            Timer::Pressed {
+                press_start,
+                inspection_start,
+            } => {
+                let color = if Instant::now().duration_since(press_start) < min_press_duration {
+                    Color::Yellow
                 } else {
-                    (Duration::ZERO, Color::Green)
+                    Color::Green
+                };
+
+                if let Some(inspection_start) = inspection_start {
+                    return self.render_inspection(area, buf, inspection_start, Some(color));
                 }
+
+                (Duration::ZERO, Penalty::None, color)
+            }
+            Timer::Inspecting { start } => {
+                return self.render_inspection(area, buf, start, None);
+            }
+            Timer::Running { start, penalty, .. } => {
+                (Instant::now().duration_since(start), penalty, Color::Blue)
             }
-            Timer::Running { start } => (Instant::now().duration_since(start), Color::Blue),
             Timer::Stopped {
-                time,
+                ref measurement,
                 stopped_instant,
             } => {
                 if Instant::now().duration_since(stopped_instant) < min_stop_duration {
-                    (time, Color::Green)
+                    (measurement.time, measurement.penalty, Color::Green)
                 } else {
-                    (time, Color::White)
+                    (measurement.time, measurement.penalty, Color::White)
                 }
             }
         };
 
+        let splits = self.splits().into_iter().map(|(name, time)| match time {
+            Some(time) => Span::from(format!(" {name} {:.3} ", time.as_secs_f64())),
+            None => Span::from(format!(" {name} ")).bold().fg(color),
+        });
+        Line::from_iter(splits).centered().render(splits_area, buf);
+
         let mins = format!("{:0>2}:", duration.as_secs() / 60);
         let secs = format!("{:0>2}", duration.as_secs() % 60);
-        let milis = format!(".{:0>3}", duration.as_millis() % 1000);
+        let milis = match penalty {
+            Penalty::None => format!(".{:0>3}", duration.as_millis() % 1000),
+            Penalty::Plus2 => format!(".{:0>3} +2", duration.as_millis() % 1000),
+            Penalty::DNF => format!(".{:0>3} DNF", duration.as_millis() % 1000),
+        };
 
         let [mins_area, secs_area, milis_area] = Layout::horizontal([
             Constraint::Fill(1),